@@ -2,8 +2,8 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use hue::api::{
-    GroupedLightUpdate, LightUpdate, ResourceLink, RoomUpdate, Scene, SceneUpdate,
-    ZigbeeDeviceDiscoveryUpdate,
+    DeviceSoftwareUpdateUpdate, DeviceUpdate, GroupedLightUpdate, LightUpdate, ResourceLink, Room,
+    RoomUpdate, Scene, SceneUpdate, ZigbeeDeviceDiscoveryUpdate, Zone, ZoneUpdate,
 };
 use hue::stream::HueStreamLightsV2;
 
@@ -14,6 +14,9 @@ use crate::error::BifrostResult;
 #[allow(clippy::large_enum_variant)]
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub enum BackendRequest {
+    DeviceUpdate(ResourceLink, DeviceUpdate),
+    DeviceSoftwareUpdate(ResourceLink, DeviceSoftwareUpdateUpdate),
+
     LightUpdate(ResourceLink, LightUpdate),
 
     SceneCreate(ResourceLink, u32, Scene),
@@ -21,8 +24,12 @@ pub enum BackendRequest {
 
     GroupedLightUpdate(ResourceLink, GroupedLightUpdate),
 
+    RoomCreate(ResourceLink, Room),
     RoomUpdate(ResourceLink, RoomUpdate),
 
+    ZoneCreate(ResourceLink, Zone),
+    ZoneUpdate(ResourceLink, ZoneUpdate),
+
     Delete(ResourceLink),
 
     EntertainmentStart(Uuid),
@@ -2,7 +2,7 @@ use std::net::Ipv4Addr;
 use std::{collections::BTreeMap, num::NonZeroU32};
 
 use camino::Utf8PathBuf;
-use hue::api::RoomArchetype;
+use hue::api::{ButtonEvent, RoomArchetype};
 use serde::{Deserialize, Serialize};
 use url::Url;
 
@@ -31,6 +31,9 @@ pub struct BridgeConfig {
 pub struct BifrostConfig {
     pub state_file: Utf8PathBuf,
     pub cert_file: Utf8PathBuf,
+    /// Allow pressing the virtual link button from other hosts than localhost
+    #[serde(default)]
+    pub remote_linkbutton: bool,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
@@ -45,6 +48,17 @@ pub struct Z2mServer {
     pub group_prefix: Option<String>,
     pub disable_tls_verify: Option<bool>,
     pub streaming_fps: Option<NonZeroU32>,
+    pub rename_devices: Option<bool>,
+    pub base_topic: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct HomeAssistantConfig {
+    pub url: Url,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub discovery_prefix: Option<String>,
+    pub base_topic: Option<String>,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize, Default, Eq, PartialEq)]
@@ -53,6 +67,13 @@ pub struct RoomConfig {
     pub icon: Option<RoomArchetype>,
 }
 
+/// Mapping of a z2m `action` value to an event on a (named) button
+#[derive(Clone, Debug, Serialize, Deserialize, Eq, PartialEq)]
+pub struct ButtonMapping {
+    pub button: String,
+    pub event: ButtonEvent,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct AppConfig {
     pub bridge: BridgeConfig,
@@ -60,12 +81,36 @@ pub struct AppConfig {
     pub bifrost: BifrostConfig,
     #[serde(default)]
     pub rooms: BTreeMap<String, RoomConfig>,
+    pub homeassistant: Option<HomeAssistantConfig>,
+    /// Extra button mappings, by model id and action
+    #[serde(default)]
+    pub buttons: BTreeMap<String, BTreeMap<String, ButtonMapping>>,
 }
 
 impl Z2mServer {
+    pub const DEFAULT_MQTT_PORT: u16 = 1883;
+
+    /// Returns true if this server is reached directly through its mqtt
+    /// broker, rather than through the z2m frontend websocket
+    #[must_use]
+    pub fn is_mqtt(&self) -> bool {
+        self.url.scheme() == "mqtt"
+    }
+
+    #[must_use]
+    pub fn get_base_topic(&self) -> &str {
+        self.base_topic.as_deref().unwrap_or("zigbee2mqtt")
+    }
+
     #[must_use]
     pub fn get_url(&self) -> Url {
         let mut url = self.url.clone();
+
+        // the websocket compatibility rules below do not apply to mqtt urls
+        if self.is_mqtt() {
+            return url;
+        }
+
         // z2m version 1.x allows both / and /api as endpoints for the
         // websocket, but version 2.x only allows /api. By adding /api (if
         // missing), we ensure compatibility with both versions.
@@ -110,7 +155,28 @@ impl Z2mServer {
 
     #[must_use]
     pub fn get_sanitized_url(&self) -> String {
-        Self::sanitize_url(self.get_url().as_str())
+        let mut url = self.get_url();
+
+        // mqtt credentials are given as part of the url
+        if url.password().is_some() {
+            let _ = url.set_password(Some("<<REDACTED>>"));
+        }
+
+        Self::sanitize_url(url.as_str())
+    }
+}
+
+impl HomeAssistantConfig {
+    pub const DEFAULT_PORT: u16 = 1883;
+
+    #[must_use]
+    pub fn get_discovery_prefix(&self) -> &str {
+        self.discovery_prefix.as_deref().unwrap_or("homeassistant")
+    }
+
+    #[must_use]
+    pub fn get_base_topic(&self) -> &str {
+        self.base_topic.as_deref().unwrap_or("bifrost")
     }
 }
 
@@ -1,6 +1,7 @@
 pub mod backend;
 pub mod config;
 pub mod error;
+pub mod linkbutton;
 pub mod service;
 pub mod websocket;
 
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+
+use crate::Client;
+use crate::error::BifrostResult;
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, Default, Eq, PartialEq)]
+pub struct LinkButton {
+    /// True while the pairing window is open
+    pub active: bool,
+}
+
+impl Client {
+    pub async fn linkbutton(&self) -> BifrostResult<LinkButton> {
+        self.get("linkbutton").await
+    }
+
+    pub async fn linkbutton_press(&self) -> BifrostResult<LinkButton> {
+        self.post("linkbutton", ()).await
+    }
+}
@@ -205,10 +205,11 @@ pub struct SceneCycleExtended {
 pub mod configuration {
     use std::time::Duration as StdDuration;
 
-    use chrono::Weekday;
+    use chrono::{DateTime, NaiveDate, NaiveTime, TimeDelta, TimeZone, Weekday};
     use serde::{Deserialize, Serialize};
 
     use crate::api::ResourceLink;
+    use crate::sun::{Sun, SunEvent, event_time};
 
     #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
     pub struct Duration {
@@ -230,17 +231,60 @@ pub mod configuration {
     #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
     #[serde(tag = "type", rename_all = "snake_case")]
     pub enum TimePoint {
-        Time { time: Time },
+        Time {
+            time: Time,
+        },
+        Sunrise {
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            offset: Option<Offset>,
+        },
+        Sunset {
+            #[serde(default, skip_serializing_if = "Option::is_none")]
+            offset: Option<Offset>,
+        },
     }
 
     impl TimePoint {
-        pub const fn time(&self) -> &Time {
+        /// Resolve this time point on the given date, in the timezone `tz`
+        ///
+        /// Sunrise and sunset can only be resolved if the bridge location
+        /// (`sun`) is known, and the sun actually rises or sets on that day.
+        pub fn on_date<Tz: TimeZone>(
+            &self,
+            date: NaiveDate,
+            sun: Option<&Sun>,
+            tz: &Tz,
+        ) -> Option<DateTime<Tz>> {
             match self {
-                Self::Time { time } => time,
+                Self::Time { time } => {
+                    let time = NaiveTime::from_hms_opt(time.hour, time.minute, 0)?;
+                    tz.from_local_datetime(&date.and_time(time)).latest()
+                }
+                Self::Sunrise { offset } => {
+                    let sunrise = event_time(sun, SunEvent::Sunrise, date, tz)?;
+                    Some(sunrise + offset.unwrap_or_default().to_delta())
+                }
+                Self::Sunset { offset } => {
+                    let sunset = event_time(sun, SunEvent::Sunset, date, tz)?;
+                    Some(sunset + offset.unwrap_or_default().to_delta())
+                }
             }
         }
     }
 
+    /// Offset from sunrise or sunset (negative values are before)
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+    pub struct Offset {
+        pub minutes: i32,
+    }
+
+    impl Offset {
+        #[must_use]
+        pub fn to_delta(self) -> TimeDelta {
+            TimeDelta::minutes(i64::from(self.minutes))
+        }
+    }
+
     #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
     pub struct Time {
         pub hour: u32,
@@ -335,3 +379,87 @@ impl AddAssign<BehaviorInstanceUpdate> for BehaviorInstance {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use serde_json::json;
+
+    use crate::api::configuration::{Offset, Time, TimePoint};
+    use crate::sun::Sun;
+
+    const COPENHAGEN: Sun = Sun::new(55.6761, 12.5683);
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 6, 21).unwrap()
+    }
+
+    #[test]
+    fn time_point_deserialize() {
+        let tp: TimePoint = serde_json::from_value(json!({
+            "type": "time",
+            "time": {"hour": 7, "minute": 30},
+        }))
+        .unwrap();
+        assert_eq!(
+            tp,
+            TimePoint::Time {
+                time: Time {
+                    hour: 7,
+                    minute: 30
+                }
+            }
+        );
+
+        let tp: TimePoint = serde_json::from_value(json!({
+            "type": "sunset",
+            "offset": {"minutes": -30},
+        }))
+        .unwrap();
+        assert_eq!(
+            tp,
+            TimePoint::Sunset {
+                offset: Some(Offset { minutes: -30 })
+            }
+        );
+
+        let tp: TimePoint = serde_json::from_value(json!({"type": "sunrise"})).unwrap();
+        assert_eq!(tp, TimePoint::Sunrise { offset: None });
+    }
+
+    #[test]
+    fn time_point_time() {
+        let tp = TimePoint::Time {
+            time: Time {
+                hour: 7,
+                minute: 30,
+            },
+        };
+        let res = tp.on_date(date(), None, &Utc).unwrap();
+        assert_eq!(res, Utc.with_ymd_and_hms(2024, 6, 21, 7, 30, 0).unwrap());
+    }
+
+    #[test]
+    fn time_point_solar() {
+        let sun = COPENHAGEN.day(date());
+
+        let tp = TimePoint::Sunrise { offset: None };
+        let res = tp.on_date(date(), Some(&COPENHAGEN), &Utc);
+        assert_eq!(res, sun.sunrise());
+
+        let tp = TimePoint::Sunset {
+            offset: Some(Offset { minutes: -30 }),
+        };
+        let res = tp.on_date(date(), Some(&COPENHAGEN), &Utc).unwrap();
+        assert_eq!(
+            res,
+            sun.sunset().unwrap() - Offset { minutes: 30 }.to_delta()
+        );
+    }
+
+    #[test]
+    fn time_point_solar_without_location() {
+        let tp = TimePoint::Sunset { offset: None };
+        assert_eq!(tp.on_date(date(), None, &Utc), None);
+    }
+}
@@ -1,6 +1,6 @@
 use std::ops::AddAssign;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeDelta, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::api::ResourceLink;
@@ -37,7 +37,7 @@ pub struct ButtonReport {
     pub event: ButtonEvent,
 }
 
-#[derive(Debug, Serialize, Deserialize, Copy, Clone)]
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum ButtonEvent {
     InitialPress,
@@ -115,3 +115,143 @@ impl AddAssign<ButtonDataUpdate> for ButtonData {
         }
     }
 }
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RelativeRotary {
+    pub owner: ResourceLink,
+    #[serde(default)]
+    pub relative_rotary: RelativeRotaryData,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct RelativeRotaryData {
+    /// Deprecated by hue, in favor of `rotary_report`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_event: Option<RelativeRotaryEvent>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotary_report: Option<RelativeRotaryReport>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RelativeRotaryEvent {
+    pub action: RelativeRotaryAction,
+    pub rotation: RelativeRotaryRotation,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RelativeRotaryReport {
+    #[serde(with = "date_format::utc_ms")]
+    pub updated: DateTime<Utc>,
+    pub action: RelativeRotaryAction,
+    pub rotation: RelativeRotaryRotation,
+}
+
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RelativeRotaryAction {
+    Start,
+    Repeat,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct RelativeRotaryRotation {
+    pub direction: RelativeRotaryDirection,
+    /// Amount of rotation, in device-specific steps
+    pub steps: u32,
+    /// Duration of the rotation, in milliseconds
+    pub duration: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RelativeRotaryDirection {
+    ClockWise,
+    CounterClockWise,
+}
+
+impl RelativeRotary {
+    /// Rotations in the same direction, within this time of the previous
+    /// report, continue that rotation (as a `repeat` event).
+    pub const REPEAT_WINDOW: TimeDelta = TimeDelta::milliseconds(1500);
+
+    #[must_use]
+    pub fn new(owner: ResourceLink) -> Self {
+        Self {
+            owner,
+            relative_rotary: RelativeRotaryData::default(),
+        }
+    }
+
+    pub fn report(&mut self, rotation: RelativeRotaryRotation) {
+        self.report_at(Utc::now(), rotation);
+    }
+
+    fn report_at(&mut self, updated: DateTime<Utc>, rotation: RelativeRotaryRotation) {
+        let action = match &self.relative_rotary.rotary_report {
+            Some(prev)
+                if prev.rotation.direction == rotation.direction
+                    && updated - prev.updated < Self::REPEAT_WINDOW =>
+            {
+                RelativeRotaryAction::Repeat
+            }
+            _ => RelativeRotaryAction::Start,
+        };
+
+        self.relative_rotary = RelativeRotaryData {
+            last_event: Some(RelativeRotaryEvent {
+                action,
+                rotation: rotation.clone(),
+            }),
+            rotary_report: Some(RelativeRotaryReport {
+                updated,
+                action,
+                rotation,
+            }),
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::{
+        RType, RelativeRotary, RelativeRotaryAction, RelativeRotaryDirection,
+        RelativeRotaryRotation,
+    };
+    use chrono::{TimeDelta, Utc};
+    use uuid::Uuid;
+
+    fn rotation(direction: RelativeRotaryDirection) -> RelativeRotaryRotation {
+        RelativeRotaryRotation {
+            direction,
+            steps: 30,
+            duration: 400,
+        }
+    }
+
+    fn last_action(rr: &RelativeRotary) -> RelativeRotaryAction {
+        rr.relative_rotary.rotary_report.as_ref().unwrap().action
+    }
+
+    #[test]
+    fn report_start_repeat() {
+        let mut rr = RelativeRotary::new(RType::Device.link_to(Uuid::new_v4()));
+        let now = Utc::now();
+
+        rr.report_at(now, rotation(RelativeRotaryDirection::ClockWise));
+        assert_eq!(last_action(&rr), RelativeRotaryAction::Start);
+
+        let now = now + TimeDelta::milliseconds(400);
+        rr.report_at(now, rotation(RelativeRotaryDirection::ClockWise));
+        assert_eq!(last_action(&rr), RelativeRotaryAction::Repeat);
+
+        // changing direction starts a new rotation
+        let now = now + TimeDelta::milliseconds(400);
+        rr.report_at(now, rotation(RelativeRotaryDirection::CounterClockWise));
+        assert_eq!(last_action(&rr), RelativeRotaryAction::Start);
+
+        // as does rotating again after a pause
+        let now = now + RelativeRotary::REPEAT_WINDOW;
+        rr.report_at(now, rotation(RelativeRotaryDirection::CounterClockWise));
+        assert_eq!(last_action(&rr), RelativeRotaryAction::Start);
+    }
+}
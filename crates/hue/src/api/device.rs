@@ -168,6 +168,103 @@ impl Sub<&Device> for &Device {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DevicePower {
+    pub owner: ResourceLink,
+    pub power_state: PowerState,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct PowerState {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub battery_state: Option<BatteryState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub battery_level: Option<u8>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BatteryState {
+    Normal,
+    Low,
+    Critical,
+}
+
+impl BatteryState {
+    /// Battery level (in percent) below which the battery is reported as low
+    pub const LOW_LEVEL: u8 = 20;
+
+    /// Battery level (in percent) below which the battery is reported as critical
+    pub const CRITICAL_LEVEL: u8 = 5;
+
+    #[must_use]
+    pub const fn from_level(level: u8) -> Self {
+        if level < Self::CRITICAL_LEVEL {
+            Self::Critical
+        } else if level < Self::LOW_LEVEL {
+            Self::Low
+        } else {
+            Self::Normal
+        }
+    }
+}
+
+impl DevicePower {
+    #[must_use]
+    pub fn new(owner: ResourceLink) -> Self {
+        Self {
+            owner,
+            power_state: PowerState::default(),
+        }
+    }
+
+    /// Update battery level and state from a battery percentage
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn report_battery(&mut self, percent: f64) {
+        let level = percent.round().clamp(0.0, 100.0) as u8;
+
+        self.power_state = PowerState {
+            battery_state: Some(BatteryState::from_level(level)),
+            battery_level: Some(level),
+        };
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DeviceSoftwareUpdate {
+    pub owner: ResourceLink,
+    pub state: DeviceSoftwareUpdateState,
+    pub problems: Vec<Value>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceSoftwareUpdateState {
+    #[default]
+    NoUpdate,
+    UpdatePending,
+    ReadyToInstall,
+    Transferring,
+    Installing,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct DeviceSoftwareUpdateUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub install: Option<bool>,
+}
+
+impl DeviceSoftwareUpdate {
+    #[must_use]
+    pub const fn new(owner: ResourceLink) -> Self {
+        Self {
+            owner,
+            state: DeviceSoftwareUpdateState::NoUpdate,
+            problems: vec![],
+        }
+    }
+}
+
 #[derive(Debug, Default, Serialize, Deserialize, Clone, Eq, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum DeviceArchetype {
@@ -226,3 +323,18 @@ pub enum DeviceArchetype {
     #[serde(untagged)]
     Other(String),
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::api::BatteryState;
+
+    #[test]
+    fn battery_state_from_level() {
+        assert_eq!(BatteryState::from_level(100), BatteryState::Normal);
+        assert_eq!(BatteryState::from_level(20), BatteryState::Normal);
+        assert_eq!(BatteryState::from_level(19), BatteryState::Low);
+        assert_eq!(BatteryState::from_level(5), BatteryState::Low);
+        assert_eq!(BatteryState::from_level(4), BatteryState::Critical);
+        assert_eq!(BatteryState::from_level(0), BatteryState::Critical);
+    }
+}
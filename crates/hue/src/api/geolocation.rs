@@ -0,0 +1,49 @@
+use chrono::{Local, NaiveDate, NaiveTime, Timelike};
+use serde::{Deserialize, Serialize};
+
+use crate::sun::{DayType, Sun};
+
+/// The geolocation resource, as reported by the bridge
+///
+/// The hue bridge never reports the location itself back, so the coordinates
+/// are not part of this resource. They are kept separately, and passed in as
+/// a [`Sun`] where needed.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct Geolocation {
+    pub is_configured: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sun_today: Option<SunToday>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SunToday {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sunset_time: Option<NaiveTime>,
+    pub day_type: DayType,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GeolocationUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latitude: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub longitude: Option<f64>,
+}
+
+impl Geolocation {
+    /// Update the configuration state and `sun_today` for the given location
+    /// (`sun`) and (local) date
+    pub fn update_sun_today(&mut self, sun: Option<&Sun>, date: NaiveDate) {
+        self.is_configured = sun.is_some();
+        self.sun_today = sun.map(|sun| {
+            let day = sun.day(date);
+            SunToday {
+                sunset_time: day
+                    .sunset()
+                    .map(|sunset| sunset.with_timezone(&Local).time())
+                    .and_then(|time| time.with_nanosecond(0)),
+                day_type: day.day_type(),
+            }
+        });
+    }
+}
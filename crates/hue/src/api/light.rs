@@ -5,7 +5,9 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::api::device::DeviceIdentifyUpdate;
-use crate::api::{DeviceArchetype, Identify, Metadata, MetadataUpdate, ResourceLink, Stub};
+use crate::api::{
+    DeviceArchetype, Identify, Metadata, MetadataUpdate, ResourceLink, SceneAction, Stub,
+};
 use crate::hs::HS;
 use crate::legacy_api::ApiLightStateUpdate;
 use crate::xy::XY;
@@ -169,6 +171,29 @@ impl Light {
         self.color.as_ref().map(|col| col.xy)
     }
 
+    /// The current state of the light, as a scene action
+    #[must_use]
+    pub fn as_scene_action(&self) -> SceneAction {
+        let mirek = self
+            .color_temperature
+            .as_ref()
+            .filter(|ct| ct.mirek_valid)
+            .and_then(|ct| ct.mirek);
+
+        SceneAction {
+            color: mirek
+                .is_none()
+                .then(|| self.as_color_opt())
+                .flatten()
+                .map(|xy| ColorUpdate { xy }),
+            color_temperature: mirek.map(ColorTemperatureUpdate::new),
+            dimming: self.as_dimming_opt(),
+            on: Some(self.on),
+            gradient: self.as_gradient_opt(),
+            effects: Value::Null,
+        }
+    }
+
     #[must_use]
     pub fn as_gradient_opt(&self) -> Option<LightGradientUpdate> {
         self.gradient.as_ref().map(|grad| LightGradientUpdate {
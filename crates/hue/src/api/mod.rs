@@ -4,11 +4,14 @@ mod button;
 mod device;
 mod entertainment;
 mod entertainment_config;
+mod geolocation;
 mod grouped_light;
 mod light;
 mod resource;
 mod room;
 mod scene;
+mod sensor;
+mod smart_scene;
 mod stream;
 mod stubs;
 mod update;
@@ -23,8 +26,13 @@ pub use behavior::{
 pub use bridge_home::BridgeHome;
 pub use button::{
     Button, ButtonData, ButtonDataUpdate, ButtonEvent, ButtonMetadata, ButtonReport, ButtonUpdate,
+    RelativeRotary, RelativeRotaryAction, RelativeRotaryData, RelativeRotaryDirection,
+    RelativeRotaryEvent, RelativeRotaryReport, RelativeRotaryRotation,
+};
+pub use device::{
+    BatteryState, Device, DeviceArchetype, DevicePower, DeviceProductData, DeviceSoftwareUpdate,
+    DeviceSoftwareUpdateState, DeviceSoftwareUpdateUpdate, DeviceUpdate, Identify, PowerState,
 };
-pub use device::{Device, DeviceArchetype, DeviceProductData, DeviceUpdate, Identify};
 pub use entertainment::{Entertainment, EntertainmentSegment, EntertainmentSegments};
 pub use entertainment_config::{
     EntertainmentConfiguration, EntertainmentConfigurationAction,
@@ -37,6 +45,7 @@ pub use entertainment_config::{
     EntertainmentConfigurationStreamProxyMode, EntertainmentConfigurationStreamProxyUpdate,
     EntertainmentConfigurationType, EntertainmentConfigurationUpdate, Position,
 };
+pub use geolocation::{Geolocation, GeolocationUpdate, SunToday};
 pub use grouped_light::{GroupedLight, GroupedLightDynamicsUpdate, GroupedLightUpdate};
 pub use light::{
     ColorGamut, ColorTemperature, ColorTemperatureUpdate, ColorUpdate, ContentConfiguration,
@@ -52,18 +61,30 @@ pub use light::{
     OrientationType,
 };
 pub use resource::{RType, ResourceLink, ResourceRecord};
-pub use room::{Room, RoomArchetype, RoomMetadata, RoomMetadataUpdate, RoomUpdate};
+pub use room::{
+    Room, RoomArchetype, RoomMetadata, RoomMetadataUpdate, RoomUpdate, Zone, ZoneUpdate,
+};
 pub use scene::{
-    Scene, SceneAction, SceneActionElement, SceneActive, SceneMetadata, SceneRecall, SceneStatus,
-    SceneStatusEnum, SceneUpdate,
+    Scene, SceneAction, SceneActionElement, SceneActive, SceneMetadata, SceneMetadataUpdate,
+    ScenePalette, ScenePaletteColor, ScenePaletteColorTemperature, ScenePaletteEffect, SceneRecall,
+    SceneStatus, SceneStatusEnum, SceneUpdate,
+};
+pub use sensor::{
+    Contact, ContactReport, ContactState, LightLevel, LightLevelData, LightLevelReport, Motion,
+    MotionData, MotionReport, MotionSensitivity, MotionSensitivityStatus, Tamper, TamperReport,
+    TamperSource, TamperState, Temperature, TemperatureData, TemperatureReport,
 };
 use serde::ser::SerializeMap;
+pub use smart_scene::{
+    SmartScene, SmartSceneActiveTimeslot, SmartSceneDayTimeslots, SmartSceneNew, SmartSceneRecall,
+    SmartSceneRecallAction, SmartSceneState, SmartSceneTime, SmartSceneTimeslot,
+    SmartSceneTimeslotStart, SmartSceneUpdate, Weekday,
+};
 pub use stream::HueStreamKey;
 pub use stubs::{
-    Bridge, DevicePower, DeviceSoftwareUpdate, DollarRef, GeofenceClient, Geolocation,
-    GroupedLightLevel, GroupedMotion, Homekit, LightLevel, Matter, Metadata, MetadataUpdate,
-    Motion, PrivateGroup, PublicImage, RelativeRotary, SmartScene, Taurus, Temperature, TimeZone,
-    ZigbeeConnectivity, ZigbeeConnectivityStatus, Zone,
+    Bridge, DollarRef, GeofenceClient, GroupedLightLevel, GroupedMotion, Homekit, Matter, Metadata,
+    MetadataUpdate, PrivateGroup, PublicImage, Taurus, TimeZone, ZigbeeConnectivity,
+    ZigbeeConnectivityStatus,
 };
 pub use update::Update;
 pub use zigbee_device_discovery::{
@@ -102,6 +123,7 @@ pub enum Resource {
     Bridge(Bridge),
     BridgeHome(BridgeHome),
     Button(Button),
+    Contact(Contact),
     Device(Device),
     DevicePower(DevicePower),
     DeviceSoftwareUpdate(DeviceSoftwareUpdate),
@@ -125,6 +147,7 @@ pub enum Resource {
     SmartScene(SmartScene),
     #[serde(rename = "taurus_7455")]
     Taurus(Taurus),
+    Tamper(Tamper),
     Temperature(Temperature),
     ZigbeeConnectivity(ZigbeeConnectivity),
     ZigbeeDeviceDiscovery(ZigbeeDeviceDiscovery),
@@ -132,10 +155,8 @@ pub enum Resource {
 
     /* Unmapped variants */
     CameraMotion(Value),
-    Contact(Value),
     MatterFabric(Value),
     ServiceGroup(Value),
-    Tamper(Value),
     ZgpConnectivity(Value),
 }
 
@@ -194,6 +215,7 @@ impl Resource {
             Self::Bridge(obj) => Some(obj.owner),
             Self::BridgeHome(_) => None,
             Self::Button(obj) => Some(obj.owner),
+            Self::Contact(obj) => Some(obj.owner),
             Self::Device(_) => None,
             Self::DevicePower(obj) => Some(obj.owner),
             Self::DeviceSoftwareUpdate(obj) => Some(obj.owner),
@@ -216,6 +238,7 @@ impl Resource {
             Self::Scene(_) => None,
             Self::SmartScene(_) => None,
             Self::Taurus(obj) => Some(obj.owner),
+            Self::Tamper(obj) => Some(obj.owner),
             Self::Temperature(obj) => Some(obj.owner),
             Self::ZigbeeConnectivity(obj) => Some(obj.owner),
             Self::ZigbeeDeviceDiscovery(obj) => Some(obj.owner),
@@ -223,10 +246,8 @@ impl Resource {
 
             /* Unmapped variants */
             Self::CameraMotion(_) => None,
-            Self::Contact(_) => None,
             Self::MatterFabric(_) => None,
             Self::ServiceGroup(_) => None,
-            Self::Tamper(_) => None,
             Self::ZgpConnectivity(_) => None,
         }
     }
@@ -239,6 +260,7 @@ impl Resource {
             RType::Bridge => Self::Bridge(from_value(obj)?),
             RType::BridgeHome => Self::BridgeHome(from_value(obj)?),
             RType::Button => Self::Button(from_value(obj)?),
+            RType::Contact => Self::Contact(from_value(obj)?),
             RType::Device => Self::Device(from_value(obj)?),
             RType::DevicePower => Self::DevicePower(from_value(obj)?),
             RType::DeviceSoftwareUpdate => Self::DeviceSoftwareUpdate(from_value(obj)?),
@@ -261,15 +283,14 @@ impl Resource {
             RType::Scene => Self::Scene(from_value(obj)?),
             RType::SmartScene => Self::SmartScene(from_value(obj)?),
             RType::Taurus => Self::Taurus(from_value(obj)?),
+            RType::Tamper => Self::Tamper(from_value(obj)?),
             RType::Temperature => Self::Temperature(from_value(obj)?),
             RType::ZigbeeConnectivity => Self::ZigbeeConnectivity(from_value(obj)?),
             RType::ZigbeeDeviceDiscovery => Self::ZigbeeDeviceDiscovery(from_value(obj)?),
             RType::Zone => Self::Zone(from_value(obj)?),
             RType::CameraMotion => Self::CameraMotion(obj),
-            RType::Contact => Self::Contact(obj),
             RType::MatterFabric => Self::MatterFabric(obj),
             RType::ServiceGroup => Self::ServiceGroup(obj),
-            RType::Tamper => Self::Tamper(obj),
             RType::ZgpConnectivity => Self::ZgpConnectivity(obj),
         };
         Ok(res)
@@ -377,6 +398,7 @@ resource_conversion_impl!(BehaviorScript);
 resource_conversion_impl!(Bridge);
 resource_conversion_impl!(BridgeHome);
 resource_conversion_impl!(Button);
+resource_conversion_impl!(Contact);
 resource_conversion_impl!(Device);
 resource_conversion_impl!(DevicePower);
 resource_conversion_impl!(DeviceSoftwareUpdate);
@@ -399,6 +421,7 @@ resource_conversion_impl!(Room);
 resource_conversion_impl!(Scene);
 resource_conversion_impl!(SmartScene);
 resource_conversion_impl!(Taurus);
+resource_conversion_impl!(Tamper);
 resource_conversion_impl!(Temperature);
 resource_conversion_impl!(ZigbeeConnectivity);
 resource_conversion_impl!(ZigbeeDeviceDiscovery);
@@ -434,6 +457,41 @@ impl<'a> V1Reply<'a> {
         Self::new(format!("/groups/{id}"))
     }
 
+    #[must_use]
+    pub fn for_scene(id: u32) -> Self {
+        Self::new(format!("/scenes/{id}"))
+    }
+
+    #[must_use]
+    pub fn for_scene_path(id: u32, path: &str) -> Self {
+        Self::new(format!("/scenes/{id}/{path}"))
+    }
+
+    #[must_use]
+    pub fn for_sensor(id: u32) -> Self {
+        Self::new(format!("/sensors/{id}"))
+    }
+
+    #[must_use]
+    pub fn for_sensor_path(id: u32, path: &str) -> Self {
+        Self::new(format!("/sensors/{id}/{path}"))
+    }
+
+    #[must_use]
+    pub fn for_rule(id: u32) -> Self {
+        Self::new(format!("/rules/{id}"))
+    }
+
+    #[must_use]
+    pub fn for_schedule(id: u32) -> Self {
+        Self::new(format!("/schedules/{id}"))
+    }
+
+    #[must_use]
+    pub fn for_resourcelink(id: u32) -> Self {
+        Self::new(format!("/resourcelinks/{id}"))
+    }
+
     pub fn with_light_state_update(self, upd: &ApiLightStateUpdate) -> HueResult<Self> {
         self.add_option("on", upd.on)?
             .add_option("bri", upd.bri)?
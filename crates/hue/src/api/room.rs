@@ -25,6 +25,15 @@ pub struct Room {
     pub services: BTreeSet<ResourceLink>,
 }
 
+/// Zones are user-defined groups of lights, which (unlike rooms) can overlap
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Zone {
+    pub children: BTreeSet<ResourceLink>,
+    pub metadata: RoomMetadata,
+    #[serde(default)]
+    pub services: BTreeSet<ResourceLink>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct RoomUpdate {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -44,6 +53,18 @@ impl Room {
     }
 }
 
+/// Zones are updated with the same fields as rooms
+pub type ZoneUpdate = RoomUpdate;
+
+impl Zone {
+    #[must_use]
+    pub fn grouped_light_service(&self) -> Option<&ResourceLink> {
+        self.services
+            .iter()
+            .find(|rl| rl.rtype == RType::GroupedLight)
+    }
+}
+
 impl RoomUpdate {
     #[must_use]
     pub fn new() -> Self {
@@ -144,6 +165,17 @@ impl AddAssign<&RoomUpdate> for Room {
     }
 }
 
+impl AddAssign<&ZoneUpdate> for Zone {
+    fn add_assign(&mut self, rhs: &ZoneUpdate) {
+        if let Some(md) = &rhs.metadata {
+            self.metadata += md;
+        }
+        if let Some(children) = &rhs.children {
+            self.children.clone_from(children);
+        }
+    }
+}
+
 impl AddAssign<&RoomMetadataUpdate> for RoomMetadata {
     fn add_assign(&mut self, upd: &RoomMetadataUpdate) {
         if let Some(name) = &upd.name {
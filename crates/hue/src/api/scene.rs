@@ -1,11 +1,13 @@
 use std::ops::{AddAssign, Sub};
+use std::time::Duration;
 
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::api::{
-    ColorTemperatureUpdate, ColorUpdate, DimmingUpdate, LightGradientUpdate, On, ResourceLink,
+    ColorTemperatureUpdate, ColorUpdate, DimmingUpdate, LightEffect, LightGradientUpdate,
+    LightUpdate, On, ResourceLink,
 };
 use crate::date_format;
 
@@ -43,23 +45,8 @@ pub struct Scene {
     pub auto_dynamic: bool,
     pub group: ResourceLink,
     pub metadata: SceneMetadata,
-    /* palette: { */
-    /*     color: [], */
-    /*     color_temperature: [ */
-    /*         { */
-    /*             color_temperature: { */
-    /*                 mirek: u32 */
-    /*             }, */
-    /*             dimming: { */
-    /*                 brightness: f64, */
-    /*             } */
-    /*         } */
-    /*     ], */
-    /*     dimming: [], */
-    /*     effects: [] */
-    /* }, */
-    #[serde(default, skip_serializing_if = "Value::is_null")]
-    pub palette: Value,
+    #[serde(default)]
+    pub palette: ScenePalette,
     #[serde(default)]
     pub speed: f64,
     pub status: Option<SceneStatus>,
@@ -67,6 +54,71 @@ pub struct Scene {
     pub recall: SceneRecall,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct ScenePalette {
+    #[serde(default)]
+    pub color: Vec<ScenePaletteColor>,
+    #[serde(default)]
+    pub dimming: Vec<DimmingUpdate>,
+    #[serde(default)]
+    pub color_temperature: Vec<ScenePaletteColorTemperature>,
+    #[serde(default)]
+    pub effects: Vec<ScenePaletteEffect>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ScenePaletteColor {
+    pub color: ColorUpdate,
+    pub dimming: DimmingUpdate,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ScenePaletteColorTemperature {
+    pub color_temperature: ColorTemperatureUpdate,
+    pub dimming: DimmingUpdate,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ScenePaletteEffect {
+    pub effect: LightEffect,
+}
+
+impl ScenePalette {
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.color.is_empty()
+            && self.dimming.is_empty()
+            && self.color_temperature.is_empty()
+            && self.effects.is_empty()
+    }
+
+    /// All light states in the palette, in the order they should be cycled through
+    ///
+    /// Effects are not included, so a palette with only effects has no light
+    /// states.
+    #[must_use]
+    pub fn light_updates(&self) -> Vec<LightUpdate> {
+        let colors = self.color.iter().map(|pc| {
+            LightUpdate::new()
+                .with_color_xy(pc.color.xy)
+                .with_brightness(Some(pc.dimming.brightness))
+        });
+
+        let color_temperatures = self.color_temperature.iter().map(|pct| {
+            LightUpdate::new()
+                .with_color_temperature(pct.color_temperature.mirek)
+                .with_brightness(Some(pct.dimming.brightness))
+        });
+
+        let dimming = self
+            .dimming
+            .iter()
+            .map(|dim| LightUpdate::new().with_brightness(Some(dim.brightness)));
+
+        colors.chain(color_temperatures).chain(dimming).collect()
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SceneAction {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -116,13 +168,70 @@ pub struct SceneUpdate {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub metadata: Option<SceneMetadataUpdate>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub palette: Option<Value>,
+    pub palette: Option<ScenePalette>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub speed: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub auto_dynamic: Option<bool>,
 }
 
+impl Scene {
+    /* Time between palette steps, for dynamic scenes at the slowest and fastest speed */
+    const DYNAMIC_STEP_SLOWEST: f64 = 60.0;
+    const DYNAMIC_STEP_FASTEST: f64 = 2.0;
+
+    /// Time spent on each palette step, when the scene is played back
+    /// dynamically. This is derived from `speed`, where 0.0 is slowest and
+    /// 1.0 is fastest.
+    #[must_use]
+    pub fn dynamic_step_duration(&self) -> Duration {
+        let speed = if self.speed.is_finite() {
+            self.speed.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+
+        Duration::from_secs_f64(
+            (Self::DYNAMIC_STEP_FASTEST - Self::DYNAMIC_STEP_SLOWEST)
+                .mul_add(speed, Self::DYNAMIC_STEP_SLOWEST),
+        )
+    }
+
+    /// Light states to cycle through, when the scene is played back
+    /// dynamically. If the scene has no palette, the states of the lights in
+    /// the scene are used instead.
+    ///
+    /// Effects are not played back, so a palette with only effects results in
+    /// no states at all (and the scene is not played back dynamically).
+    #[must_use]
+    pub fn dynamic_palette(&self) -> Vec<LightUpdate> {
+        if !self.palette.is_empty() {
+            return self.palette.light_updates();
+        }
+
+        self.actions
+            .iter()
+            .filter(|sae| sae.action.on.is_none_or(|on| on.on))
+            .map(|sae| {
+                LightUpdate::new()
+                    .with_color_xy(sae.action.color.map(|col| col.xy))
+                    .with_color_temperature(sae.action.color_temperature.and_then(|ct| ct.mirek))
+                    .with_brightness(sae.action.dimming.map(|dim| dim.brightness))
+            })
+            .collect()
+    }
+
+    /// Lights that take part in dynamic playback (those turned on by the scene)
+    #[must_use]
+    pub fn dynamic_lights(&self) -> Vec<ResourceLink> {
+        self.actions
+            .iter()
+            .filter(|sae| sae.action.on.is_none_or(|on| on.on))
+            .map(|sae| sae.target)
+            .collect()
+    }
+}
+
 impl SceneUpdate {
     #[must_use]
     pub fn new() -> Self {
@@ -216,3 +325,98 @@ pub struct SceneRecall {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dimming: Option<DimmingUpdate>,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::api::{RType, Scene};
+
+    fn scene(palette: &serde_json::Value, speed: f64) -> Scene {
+        serde_json::from_value(json!({
+            "actions": [
+                {
+                    "target": RType::Light.link_to(Uuid::from_bytes([1; 16])),
+                    "action": {"on": {"on": true}, "dimming": {"brightness": 80.0}},
+                },
+                {
+                    "target": RType::Light.link_to(Uuid::from_bytes([2; 16])),
+                    "action": {"on": {"on": false}},
+                },
+            ],
+            "group": RType::Room.link_to(Uuid::nil()),
+            "metadata": {"name": "Tropical twilight"},
+            "palette": palette,
+            "speed": speed,
+            "status": null,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn palette_colors() {
+        let scn = scene(
+            &json!({
+                "color": [
+                    {"color": {"xy": {"x": 0.6, "y": 0.3}}, "dimming": {"brightness": 50.0}},
+                    {"color": {"xy": {"x": 0.2, "y": 0.1}}, "dimming": {"brightness": 70.0}},
+                ],
+                "color_temperature": [
+                    {"color_temperature": {"mirek": 300}, "dimming": {"brightness": 40.0}},
+                ],
+            }),
+            0.5,
+        );
+
+        let palette = scn.dynamic_palette();
+        assert_eq!(palette.len(), 3);
+        assert_eq!(palette[0].color.unwrap().xy.x, 0.6);
+        assert_eq!(palette[1].dimming.unwrap().brightness, 70.0);
+        assert_eq!(palette[2].color_temperature.unwrap().mirek, Some(300));
+    }
+
+    #[test]
+    #[allow(clippy::float_cmp)]
+    fn palette_fallback_to_actions() {
+        let scn = scene(&json!({}), 0.5);
+
+        let palette = scn.dynamic_palette();
+        assert_eq!(palette.len(), 1);
+        assert_eq!(palette[0].dimming.unwrap().brightness, 80.0);
+
+        let lights = scn.dynamic_lights();
+        assert_eq!(lights, [RType::Light.link_to(Uuid::from_bytes([1; 16]))]);
+    }
+
+    #[test]
+    fn palette_effects_only() {
+        let scn = scene(&json!({"effects": [{"effect": "candle"}]}), 0.5);
+
+        assert!(!scn.palette.is_empty());
+        assert!(scn.dynamic_palette().is_empty());
+    }
+
+    #[test]
+    fn step_duration() {
+        assert_eq!(
+            scene(&json!({}), 0.0).dynamic_step_duration(),
+            Duration::from_secs(60)
+        );
+        assert_eq!(
+            scene(&json!({}), 1.0).dynamic_step_duration(),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            scene(&json!({}), 0.5).dynamic_step_duration(),
+            Duration::from_secs(31)
+        );
+        assert_eq!(
+            scene(&json!({}), 7.0).dynamic_step_duration(),
+            Duration::from_secs(2)
+        );
+    }
+}
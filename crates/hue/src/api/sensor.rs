@@ -0,0 +1,356 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::api::ResourceLink;
+use crate::date_format;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Motion {
+    pub owner: ResourceLink,
+    pub enabled: bool,
+    pub motion: MotionData,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sensitivity: Option<MotionSensitivity>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MotionData {
+    /// Deprecated by hue, in favor of `motion_report`
+    pub motion: bool,
+    pub motion_valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub motion_report: Option<MotionReport>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MotionReport {
+    #[serde(with = "date_format::utc_ms")]
+    pub changed: DateTime<Utc>,
+    pub motion: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MotionSensitivityStatus {
+    Set,
+    Changing,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MotionSensitivity {
+    pub status: MotionSensitivityStatus,
+    pub sensitivity: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sensitivity_max: Option<u32>,
+}
+
+impl Motion {
+    #[must_use]
+    pub fn new(owner: ResourceLink) -> Self {
+        Self {
+            owner,
+            enabled: true,
+            motion: MotionData::default(),
+            sensitivity: None,
+        }
+    }
+
+    pub fn report(&mut self, motion: bool) {
+        if self.motion.motion_valid && self.motion.motion == motion {
+            return;
+        }
+
+        self.motion = MotionData {
+            motion,
+            motion_valid: true,
+            motion_report: Some(MotionReport {
+                changed: Utc::now(),
+                motion,
+            }),
+        };
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LightLevel {
+    pub owner: ResourceLink,
+    pub enabled: bool,
+    pub light: LightLevelData,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct LightLevelData {
+    /// Deprecated by hue, in favor of `light_level_report`
+    pub light_level: u32,
+    pub light_level_valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub light_level_report: Option<LightLevelReport>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LightLevelReport {
+    #[serde(with = "date_format::utc_ms")]
+    pub changed: DateTime<Utc>,
+    pub light_level: u32,
+}
+
+impl LightLevel {
+    #[must_use]
+    pub fn new(owner: ResourceLink) -> Self {
+        Self {
+            owner,
+            enabled: true,
+            light: LightLevelData::default(),
+        }
+    }
+
+    /// Convert illuminance (in lux) to the logarithmic hue light level scale
+    ///
+    /// The hue api defines light level as `10000 * log10(lux) + 1`
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    #[must_use]
+    pub fn lux_to_light_level(lux: f64) -> u32 {
+        if lux < 1.0 {
+            return 0;
+        }
+
+        10000.0f64.mul_add(lux.log10(), 1.0).round() as u32
+    }
+
+    pub fn report(&mut self, lux: f64) {
+        let light_level = Self::lux_to_light_level(lux);
+
+        if self.light.light_level_valid && self.light.light_level == light_level {
+            return;
+        }
+
+        self.light = LightLevelData {
+            light_level,
+            light_level_valid: true,
+            light_level_report: Some(LightLevelReport {
+                changed: Utc::now(),
+                light_level,
+            }),
+        };
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Temperature {
+    pub owner: ResourceLink,
+    pub enabled: bool,
+    pub temperature: TemperatureData,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TemperatureData {
+    /// Deprecated by hue, in favor of `temperature_report`
+    pub temperature: f64,
+    pub temperature_valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature_report: Option<TemperatureReport>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TemperatureReport {
+    #[serde(with = "date_format::utc_ms")]
+    pub changed: DateTime<Utc>,
+    pub temperature: f64,
+}
+
+impl Temperature {
+    #[must_use]
+    pub fn new(owner: ResourceLink) -> Self {
+        Self {
+            owner,
+            enabled: true,
+            temperature: TemperatureData::default(),
+        }
+    }
+
+    #[allow(clippy::float_cmp)]
+    pub fn report(&mut self, temperature: f64) {
+        if self.temperature.temperature_valid && self.temperature.temperature == temperature {
+            return;
+        }
+
+        self.temperature = TemperatureData {
+            temperature,
+            temperature_valid: true,
+            temperature_report: Some(TemperatureReport {
+                changed: Utc::now(),
+                temperature,
+            }),
+        };
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Contact {
+    pub owner: ResourceLink,
+    pub enabled: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact_report: Option<ContactReport>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ContactReport {
+    #[serde(with = "date_format::utc_ms")]
+    pub changed: DateTime<Utc>,
+    pub state: ContactState,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ContactState {
+    Contact,
+    NoContact,
+}
+
+impl Contact {
+    #[must_use]
+    pub const fn new(owner: ResourceLink) -> Self {
+        Self {
+            owner,
+            enabled: true,
+            contact_report: None,
+        }
+    }
+
+    /// Report the contact state (`true` when closed, like zigbee2mqtt)
+    pub fn report(&mut self, contact: bool) {
+        let state = if contact {
+            ContactState::Contact
+        } else {
+            ContactState::NoContact
+        };
+
+        if self
+            .contact_report
+            .as_ref()
+            .is_some_and(|report| report.state == state)
+        {
+            return;
+        }
+
+        self.contact_report = Some(ContactReport {
+            changed: Utc::now(),
+            state,
+        });
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Tamper {
+    pub owner: ResourceLink,
+    pub tamper_reports: Vec<TamperReport>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TamperReport {
+    #[serde(with = "date_format::utc_ms")]
+    pub changed: DateTime<Utc>,
+    pub source: TamperSource,
+    pub state: TamperState,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TamperSource {
+    BatteryDoor,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TamperState {
+    Tampered,
+    NotTampered,
+}
+
+impl Tamper {
+    #[must_use]
+    pub const fn new(owner: ResourceLink) -> Self {
+        Self {
+            owner,
+            tamper_reports: Vec::new(),
+        }
+    }
+
+    pub fn report(&mut self, tampered: bool) {
+        let state = if tampered {
+            TamperState::Tampered
+        } else {
+            TamperState::NotTampered
+        };
+
+        if self
+            .tamper_reports
+            .iter()
+            .any(|report| report.state == state)
+        {
+            return;
+        }
+
+        // zigbee2mqtt only reports a single tamper state, so there is only
+        // ever one source
+        self.tamper_reports = vec![TamperReport {
+            changed: Utc::now(),
+            source: TamperSource::BatteryDoor,
+            state,
+        }];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::api::{Contact, ContactState, LightLevel, RType, Tamper, TamperState};
+
+    #[test]
+    fn light_level_dark() {
+        assert_eq!(LightLevel::lux_to_light_level(0.0), 0);
+        assert_eq!(LightLevel::lux_to_light_level(0.5), 0);
+        assert_eq!(LightLevel::lux_to_light_level(-1.0), 0);
+    }
+
+    #[test]
+    fn light_level_scale() {
+        assert_eq!(LightLevel::lux_to_light_level(1.0), 1);
+        assert_eq!(LightLevel::lux_to_light_level(10.0), 10001);
+        assert_eq!(LightLevel::lux_to_light_level(1000.0), 30001);
+    }
+
+    #[test]
+    fn contact_report() {
+        let mut contact = Contact::new(RType::Device.link_to(Uuid::new_v4()));
+        assert!(contact.contact_report.is_none());
+
+        contact.report(true);
+        let report = contact.contact_report.clone().unwrap();
+        assert_eq!(report.state, ContactState::Contact);
+
+        // repeated reports of the same state keep the original timestamp
+        contact.report(true);
+        assert_eq!(
+            contact.contact_report.as_ref().unwrap().changed,
+            report.changed
+        );
+
+        contact.report(false);
+        assert_eq!(
+            contact.contact_report.unwrap().state,
+            ContactState::NoContact
+        );
+    }
+
+    #[test]
+    fn tamper_report() {
+        let mut tamper = Tamper::new(RType::Device.link_to(Uuid::new_v4()));
+
+        tamper.report(false);
+        tamper.report(true);
+        assert_eq!(tamper.tamper_reports.len(), 1);
+        assert_eq!(tamper.tamper_reports[0].state, TamperState::Tampered);
+    }
+}
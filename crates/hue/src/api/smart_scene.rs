@@ -0,0 +1,432 @@
+use std::ops::AddAssign;
+
+use chrono::{DateTime, Datelike, Days, NaiveDate, NaiveTime, TimeZone};
+use serde::{Deserialize, Serialize};
+
+use crate::api::{ResourceLink, SceneMetadata, SceneMetadataUpdate};
+use crate::sun::{Sun, SunEvent, event_time};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SmartScene {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub active_timeslot: Option<SmartSceneActiveTimeslot>,
+    pub group: ResourceLink,
+    pub metadata: SceneMetadata,
+    pub state: SmartSceneState,
+    pub transition_duration: u32,
+    pub week_timeslots: Vec<SmartSceneDayTimeslots>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SmartSceneState {
+    Active,
+    #[default]
+    Inactive,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct SmartSceneActiveTimeslot {
+    pub timeslot_id: u32,
+    pub weekday: Weekday,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SmartSceneDayTimeslots {
+    pub timeslots: Vec<SmartSceneTimeslot>,
+    pub recurrence: Vec<Weekday>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SmartSceneTimeslot {
+    pub start_time: SmartSceneTimeslotStart,
+    pub target: ResourceLink,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SmartSceneTimeslotStart {
+    Time { time: SmartSceneTime },
+    Sunrise,
+    Sunset,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct SmartSceneTime {
+    pub hour: u32,
+    pub minute: u32,
+    #[serde(default)]
+    pub second: u32,
+}
+
+/// Day of the week, in the (lowercase) format used by the hue api
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl From<chrono::Weekday> for Weekday {
+    fn from(value: chrono::Weekday) -> Self {
+        match value {
+            chrono::Weekday::Mon => Self::Monday,
+            chrono::Weekday::Tue => Self::Tuesday,
+            chrono::Weekday::Wed => Self::Wednesday,
+            chrono::Weekday::Thu => Self::Thursday,
+            chrono::Weekday::Fri => Self::Friday,
+            chrono::Weekday::Sat => Self::Saturday,
+            chrono::Weekday::Sun => Self::Sunday,
+        }
+    }
+}
+
+impl From<Weekday> for chrono::Weekday {
+    fn from(value: Weekday) -> Self {
+        match value {
+            Weekday::Monday => Self::Mon,
+            Weekday::Tuesday => Self::Tue,
+            Weekday::Wednesday => Self::Wed,
+            Weekday::Thursday => Self::Thu,
+            Weekday::Friday => Self::Fri,
+            Weekday::Saturday => Self::Sat,
+            Weekday::Sunday => Self::Sun,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SmartSceneRecallAction {
+    Activate,
+    Deactivate,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct SmartSceneRecall {
+    pub action: SmartSceneRecallAction,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SmartSceneNew {
+    pub group: ResourceLink,
+    pub metadata: SceneMetadata,
+    #[serde(default = "SmartScene::default_transition_duration")]
+    pub transition_duration: u32,
+    pub week_timeslots: Vec<SmartSceneDayTimeslots>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recall: Option<SmartSceneRecall>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct SmartSceneUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<SceneMetadataUpdate>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transition_duration: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub week_timeslots: Option<Vec<SmartSceneDayTimeslots>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recall: Option<SmartSceneRecall>,
+}
+
+impl SmartSceneUpdate {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[must_use]
+    pub fn with_recall_action(self, action: SmartSceneRecallAction) -> Self {
+        Self {
+            recall: Some(SmartSceneRecall { action }),
+            ..self
+        }
+    }
+}
+
+impl SmartSceneTimeslotStart {
+    /// Start of this timeslot on the given date, in the timezone `tz`
+    ///
+    /// Sunrise and sunset can only be calculated if the location (`sun`) is
+    /// known, and only on days where the sun actually rises and sets.
+    pub fn start_on<Tz: TimeZone>(
+        &self,
+        date: NaiveDate,
+        sun: Option<&Sun>,
+        tz: &Tz,
+    ) -> Option<DateTime<Tz>> {
+        match self {
+            Self::Time { time } => {
+                let time = NaiveTime::from_hms_opt(time.hour, time.minute, time.second)?;
+                tz.from_local_datetime(&date.and_time(time)).earliest()
+            }
+            Self::Sunrise => event_time(sun, SunEvent::Sunrise, date, tz),
+            Self::Sunset => event_time(sun, SunEvent::Sunset, date, tz),
+        }
+    }
+}
+
+impl SmartScene {
+    const DEFAULT_TRANSITION_DURATION: u32 = 60_000;
+
+    #[must_use]
+    pub const fn default_transition_duration() -> u32 {
+        Self::DEFAULT_TRANSITION_DURATION
+    }
+
+    #[must_use]
+    pub fn timeslots(&self, weekday: Weekday) -> &[SmartSceneTimeslot] {
+        self.week_timeslots
+            .iter()
+            .find(|day| day.recurrence.contains(&weekday))
+            .map_or(&[], |day| &day.timeslots)
+    }
+
+    /// All timeslots on the given date, ordered by start time
+    fn schedule<Tz: TimeZone>(
+        &self,
+        date: NaiveDate,
+        sun: Option<&Sun>,
+        tz: &Tz,
+    ) -> Vec<(DateTime<Tz>, SmartSceneActiveTimeslot, ResourceLink)> {
+        let weekday = date.weekday().into();
+
+        let mut res: Vec<_> = (0..)
+            .zip(self.timeslots(weekday))
+            .filter_map(|(timeslot_id, slot)| {
+                let start = slot.start_time.start_on(date, sun, tz)?;
+                let active = SmartSceneActiveTimeslot {
+                    timeslot_id,
+                    weekday,
+                };
+                Some((start, active, slot.target))
+            })
+            .collect();
+
+        res.sort_by(|a, b| a.0.cmp(&b.0));
+        res
+    }
+
+    /// Find the timeslot active at `now`, and the scene it should recall
+    ///
+    /// If no timeslot has started yet today, the last timeslot of the
+    /// previous (scheduled) day is still active.
+    pub fn timeslot_at<Tz: TimeZone>(
+        &self,
+        now: &DateTime<Tz>,
+        sun: Option<&Sun>,
+    ) -> Option<(SmartSceneActiveTimeslot, ResourceLink)> {
+        let tz = now.timezone();
+        let today = now.date_naive();
+
+        (0..=7)
+            .filter_map(|days| today.checked_sub_days(Days::new(days)))
+            .find_map(|date| {
+                self.schedule(date, sun, &tz)
+                    .into_iter()
+                    .rfind(|(start, _, _)| start <= now)
+            })
+            .map(|(_, active, target)| (active, target))
+    }
+
+    /// Find the start of the first timeslot after `now`
+    pub fn next_start<Tz: TimeZone>(
+        &self,
+        now: &DateTime<Tz>,
+        sun: Option<&Sun>,
+    ) -> Option<DateTime<Tz>> {
+        let tz = now.timezone();
+        let today = now.date_naive();
+
+        (0..=7)
+            .filter_map(|days| today.checked_add_days(Days::new(days)))
+            .find_map(|date| {
+                self.schedule(date, sun, &tz)
+                    .into_iter()
+                    .find(|(start, _, _)| start > now)
+            })
+            .map(|(start, _, _)| start)
+    }
+}
+
+impl AddAssign<&SmartSceneUpdate> for SmartScene {
+    fn add_assign(&mut self, upd: &SmartSceneUpdate) {
+        if let Some(md) = &upd.metadata {
+            self.metadata += md;
+        }
+        if let Some(transition_duration) = upd.transition_duration {
+            self.transition_duration = transition_duration;
+        }
+        if let Some(week_timeslots) = &upd.week_timeslots {
+            self.week_timeslots.clone_from(week_timeslots);
+        }
+        match upd.recall.map(|recall| recall.action) {
+            Some(SmartSceneRecallAction::Activate) => {
+                self.state = SmartSceneState::Active;
+            }
+            Some(SmartSceneRecallAction::Deactivate) => {
+                self.state = SmartSceneState::Inactive;
+                self.active_timeslot = None;
+            }
+            None => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeZone, Utc};
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use crate::api::{
+        RType, ResourceLink, SmartScene, SmartSceneActiveTimeslot, SmartSceneState, Weekday,
+    };
+    use crate::sun::Sun;
+
+    fn scene(n: u8) -> ResourceLink {
+        RType::Scene.link_to(Uuid::from_bytes([n; 16]))
+    }
+
+    fn smart_scene() -> SmartScene {
+        serde_json::from_value(json!({
+            "group": RType::Room.link_to(Uuid::nil()),
+            "metadata": {"name": "Natural light"},
+            "state": "inactive",
+            "transition_duration": 60000,
+            "week_timeslots": [{
+                "timeslots": [
+                    {
+                        "start_time": {"kind": "time", "time": {"hour": 7, "minute": 0, "second": 0}},
+                        "target": scene(1),
+                    },
+                    {
+                        "start_time": {"kind": "sunset"},
+                        "target": scene(2),
+                    },
+                    {
+                        "start_time": {"kind": "time", "time": {"hour": 22, "minute": 30}},
+                        "target": scene(3),
+                    },
+                ],
+                "recurrence": [
+                    "monday", "tuesday", "wednesday", "thursday",
+                    "friday", "saturday", "sunday"
+                ],
+            }],
+        }))
+        .unwrap()
+    }
+
+    fn at(month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, month, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    const COPENHAGEN: Sun = Sun::new(55.6761, 12.5683);
+
+    #[test]
+    fn deserialize() {
+        let ss = smart_scene();
+        assert_eq!(ss.state, SmartSceneState::Inactive);
+        assert_eq!(ss.active_timeslot, None);
+        assert_eq!(ss.timeslots(Weekday::Friday).len(), 3);
+
+        let json = serde_json::to_value(&ss).unwrap();
+        assert_eq!(json["week_timeslots"][0]["recurrence"][0], "monday");
+        assert_eq!(
+            json["week_timeslots"][0]["timeslots"][1]["start_time"]["kind"],
+            "sunset"
+        );
+    }
+
+    #[test]
+    fn timeslot_at() {
+        let ss = smart_scene();
+
+        // 2024-06-21 is a friday, with sunset around 19:57 UTC
+        let (active, target) = ss
+            .timeslot_at(&at(6, 21, 12, 0), Some(&COPENHAGEN))
+            .unwrap();
+        assert_eq!(
+            active,
+            SmartSceneActiveTimeslot {
+                timeslot_id: 0,
+                weekday: Weekday::Friday
+            }
+        );
+        assert_eq!(target, scene(1));
+
+        let (active, target) = ss
+            .timeslot_at(&at(6, 21, 21, 0), Some(&COPENHAGEN))
+            .unwrap();
+        assert_eq!(active.timeslot_id, 1);
+        assert_eq!(target, scene(2));
+    }
+
+    #[test]
+    fn timeslot_at_early_morning() {
+        let ss = smart_scene();
+
+        // before the first timeslot, the last one from yesterday is active
+        let (active, target) = ss.timeslot_at(&at(6, 21, 3, 0), Some(&COPENHAGEN)).unwrap();
+        assert_eq!(
+            active,
+            SmartSceneActiveTimeslot {
+                timeslot_id: 2,
+                weekday: Weekday::Thursday
+            }
+        );
+        assert_eq!(target, scene(3));
+    }
+
+    #[test]
+    fn timeslot_without_location() {
+        let ss = smart_scene();
+
+        // sunset cannot be calculated, so the morning scene stays active
+        let (active, _) = ss.timeslot_at(&at(6, 21, 21, 0), None).unwrap();
+        assert_eq!(active.timeslot_id, 0);
+    }
+
+    #[test]
+    fn next_start() {
+        let ss = smart_scene();
+        let sun = Some(&COPENHAGEN);
+
+        let next = ss.next_start(&at(6, 21, 12, 0), sun).unwrap();
+        assert_eq!(
+            next,
+            COPENHAGEN
+                .day(at(6, 21, 0, 0).date_naive())
+                .sunset()
+                .unwrap()
+        );
+
+        let next = ss.next_start(&at(6, 21, 23, 0), sun).unwrap();
+        assert_eq!(next, at(6, 22, 7, 0));
+    }
+
+    #[test]
+    fn no_timeslots() {
+        let mut ss = smart_scene();
+        ss.week_timeslots[0].recurrence = vec![Weekday::Monday];
+
+        // 2024-06-21 is a friday, so the most recent timeslot is from monday
+        let (active, _) = ss.timeslot_at(&at(6, 21, 12, 0), None).unwrap();
+        assert_eq!(active.weekday, Weekday::Monday);
+
+        let next = ss.next_start(&at(6, 21, 12, 0), None).unwrap();
+        assert_eq!(next, at(6, 24, 7, 0));
+
+        ss.week_timeslots.clear();
+        assert!(ss.timeslot_at(&at(6, 21, 12, 0), None).is_none());
+        assert!(ss.next_start(&at(6, 21, 12, 0), None).is_none());
+    }
+}
@@ -1,7 +1,9 @@
 use hex::FromHexError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 use crate::error::{HueError, HueResult};
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct HueStreamKey {
     key: [u8; Self::BYTE_SIZE],
 }
@@ -56,3 +58,41 @@ impl TryFrom<&str> for HueStreamKey {
         Ok(Self::new(key))
     }
 }
+
+impl Serialize for HueStreamKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for HueStreamKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        Self::try_from(value.as_str()).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::api::HueStreamKey;
+
+    const KEY: HueStreamKey = HueStreamKey::new(*b"0123456789abcdef");
+    const KEY_HEX: &str = "30313233343536373839616263646566";
+
+    #[test]
+    fn serialize_stream_key() {
+        assert_eq!(serde_json::to_value(KEY).unwrap(), KEY_HEX);
+    }
+
+    #[test]
+    fn deserialize_stream_key() {
+        let key: HueStreamKey = serde_json::from_value(KEY_HEX.into()).unwrap();
+        assert_eq!(key, KEY);
+    }
+
+    #[test]
+    fn deserialize_stream_key_too_short() {
+        let res = serde_json::from_value::<HueStreamKey>("3031".into());
+        assert!(res.is_err());
+    }
+}
@@ -1,9 +1,7 @@
-use std::collections::BTreeSet;
-
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use crate::api::{DeviceArchetype, LightFunction, ResourceLink, SceneMetadata};
+use crate::api::{DeviceArchetype, LightFunction, ResourceLink};
 use crate::best_guess_timezone;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -19,31 +17,11 @@ pub struct DollarRef {
     pub dref: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct DevicePower {
-    pub owner: ResourceLink,
-    pub power_state: Value,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct DeviceSoftwareUpdate {
-    pub owner: ResourceLink,
-    pub state: Value,
-    pub problems: Vec<Value>,
-}
-
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GeofenceClient {
     pub name: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Geolocation {
-    pub is_configured: bool,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub sun_today: Option<Value>,
-}
-
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct GroupedMotion {
     pub owner: ResourceLink,
@@ -78,66 +56,25 @@ impl Default for Homekit {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct LightLevel {
-    pub enabled: bool,
-    pub light: Value,
-    pub owner: ResourceLink,
-}
-
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Matter {
     pub has_qr_code: bool,
     pub max_fabrics: u32,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Motion {
-    pub enabled: bool,
-    pub owner: ResourceLink,
-    pub motion: Value,
-    #[serde(default)]
-    pub sensitivity: Value,
-}
-
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PrivateGroup {}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PublicImage {}
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct RelativeRotary {
-    pub owner: ResourceLink,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub relative_rotary: Option<Value>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub rotary_report: Option<Value>,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct SmartScene {
-    /* active_timeslot: { */
-    /*     timeslot_id: 3, */
-    /*     weekday: monday */
-    /* }, */
-    #[serde(default)]
-    #[serde(skip_serializing_if = "Value::is_null")]
-    pub active_timeslot: Value,
-    pub group: ResourceLink,
-    pub metadata: SceneMetadata,
-    pub state: String,
-    pub transition_duration: u32,
-    pub week_timeslots: Value,
-}
-
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Taurus {
     pub capabilities: Vec<String>,
     pub owner: ResourceLink,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum ZigbeeConnectivityStatus {
     Connected,
@@ -155,21 +92,6 @@ pub struct ZigbeeConnectivity {
     pub status: ZigbeeConnectivityStatus,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Zone {
-    pub metadata: Metadata,
-    pub children: BTreeSet<ResourceLink>,
-    #[serde(default)]
-    pub services: BTreeSet<ResourceLink>,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct Temperature {
-    pub enabled: bool,
-    pub owner: ResourceLink,
-    pub temperature: Value,
-}
-
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TimeZone {
     pub time_zone: String,
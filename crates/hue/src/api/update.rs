@@ -2,16 +2,13 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::api::{
-    BehaviorInstanceUpdate, DeviceUpdate, EntertainmentConfigurationUpdate, GroupedLightUpdate,
-    LightUpdate, RType, RoomUpdate, SceneUpdate,
+    BehaviorInstanceUpdate, DeviceUpdate, EntertainmentConfigurationUpdate, GeolocationUpdate,
+    GroupedLightUpdate, LightUpdate, RType, RoomUpdate, SceneUpdate, SmartSceneUpdate, ZoneUpdate,
 };
 
 type BridgeUpdate = Value;
 type BridgeHomeUpdate = Value;
 type ZigbeeDeviceDiscoveryUpdate = Value;
-type SmartSceneUpdate = Value;
-type ZoneUpdate = Value;
-type GeolocationUpdate = Value;
 
 #[allow(clippy::large_enum_variant)]
 #[derive(Debug, Serialize, Deserialize, Clone)]
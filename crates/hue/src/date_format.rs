@@ -158,6 +158,36 @@ pub mod legacy_utc_opt {
     date_deserializer_utc_opt!(DateTime<Utc>, super::FORMAT_LOCAL);
 }
 
+/// Like `legacy_utc_opt`, but using the string "none" for missing values, as
+/// the v1 api does for fields like `lasttriggered`
+pub mod legacy_utc_none {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Deserializer, Serializer, de::Error};
+
+    pub fn serialize<S>(date: &Option<DateTime<Utc>>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match date {
+            Some(d) => serializer.serialize_str(&format!("{}", d.format(super::FORMAT_LOCAL))),
+            None => serializer.serialize_str("none"),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<DateTime<Utc>>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        if s == "none" {
+            return Ok(None);
+        }
+        let dt = chrono::NaiveDateTime::parse_from_str(&s, super::FORMAT_LOCAL)
+            .map_err(Error::custom)?;
+        Ok(Some(DateTime::from_naive_utc_and_offset(dt, Utc)))
+    }
+}
+
 #[cfg_attr(coverage_nightly, coverage(off))]
 #[cfg(test)]
 mod tests {
@@ -321,4 +351,21 @@ mod tests {
         let (s1, dt) = date_legacy_utc();
         se(s1, |ser| super::legacy_utc::serialize(&dt, ser))
     }
+
+    #[test]
+    fn legacy_utc_none_some() -> HueResult<()> {
+        let (s1, dt) = date_legacy_utc();
+        se(s1, |ser| super::legacy_utc_none::serialize(&Some(dt), ser))?;
+        de(s1, &Some(dt), |de| super::legacy_utc_none::deserialize(de))
+    }
+
+    #[test]
+    fn legacy_utc_none_none() -> HueResult<()> {
+        se("\"none\"", |ser| {
+            super::legacy_utc_none::serialize(&None, ser)
+        })?;
+        de("\"none\"", &None, |de| {
+            super::legacy_utc_none::deserialize(de)
+        })
+    }
 }
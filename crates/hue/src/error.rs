@@ -58,10 +58,13 @@ pub enum HueError {
 
     #[error("Effect duration out of range: {0}")]
     EffectDurationOutOfRange(u32),
+
+    #[error("Invalid time pattern: {0:?}")]
+    InvalidTimePattern(String),
 }
 
 /// Error types for Hue Bridge v1 API
-#[derive(Error, Debug, Clone, Copy)]
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HueApiV1Error {
     /// Type 1
     #[error("Unauthorized")]
@@ -103,6 +106,10 @@ pub enum HueApiV1Error {
     #[error("Portal connection is required")]
     PortalConnectionIsRequired = 12,
 
+    /// Type 101
+    #[error("Link button not pressed")]
+    LinkButtonNotPressed = 101,
+
     /// Type 901
     #[error("Internal bridge error")]
     BridgeInternalError = 901,
@@ -1,13 +1,17 @@
+use std::ops::AddAssign;
 use std::{collections::HashMap, net::Ipv4Addr};
 
 use chrono::{DateTime, Local, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
-use serde_json::{Value, json};
+use serde_json::{Map, Value, json};
 use uuid::Uuid;
 
 use crate::api::{ColorGamut, DeviceProductData};
 use crate::date_format;
+use crate::error::{HueApiV1Error, HueResult};
 use crate::hs::RawHS;
+use crate::legacy_time::{self, ApiScheduleTime, ApiTimeInterval, ApiTimePattern};
+use crate::xy::XY;
 use crate::{api, best_guess_timezone};
 
 #[cfg(feature = "mac")]
@@ -93,7 +97,7 @@ impl ApiShortConfig {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum ApiResourceType {
     Config,
@@ -172,7 +176,7 @@ impl Default for ApiBackup {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SwUpdate {
     #[serde(with = "date_format::legacy_utc")]
     lastinstall: DateTime<Utc>,
@@ -188,7 +192,7 @@ impl Default for SwUpdate {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
 #[serde(rename_all = "lowercase")]
 pub enum SwUpdateState {
     NoUpdates,
@@ -305,7 +309,7 @@ pub enum ApiGroupType {
     Zone,
 }
 
-#[derive(Debug, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
 pub enum ApiGroupClass {
     #[serde(rename = "Living room")]
     LivingRoom,
@@ -359,6 +363,100 @@ pub enum ApiGroupClass {
     Free,
 }
 
+impl From<ApiGroupClass> for api::RoomArchetype {
+    fn from(class: ApiGroupClass) -> Self {
+        match class {
+            ApiGroupClass::LivingRoom => Self::LivingRoom,
+            ApiGroupClass::Kitchen => Self::Kitchen,
+            ApiGroupClass::Dining => Self::Dining,
+            ApiGroupClass::Bedroom => Self::Bedroom,
+            ApiGroupClass::KidsBedroom => Self::KidsBedroom,
+            ApiGroupClass::Bathroom => Self::Bathroom,
+            ApiGroupClass::Nursery => Self::Nursery,
+            ApiGroupClass::Recreation => Self::Recreation,
+            ApiGroupClass::Office => Self::Office,
+            ApiGroupClass::Gym => Self::Gym,
+            ApiGroupClass::Hallway => Self::Hallway,
+            ApiGroupClass::Toilet => Self::Toilet,
+            ApiGroupClass::FrontDoor => Self::FrontDoor,
+            ApiGroupClass::Garage => Self::Garage,
+            ApiGroupClass::Terrace => Self::Terrace,
+            ApiGroupClass::Garden => Self::Garden,
+            ApiGroupClass::Driveway => Self::Driveway,
+            ApiGroupClass::Carport => Self::Carport,
+            ApiGroupClass::Other | ApiGroupClass::Free => Self::Other,
+            ApiGroupClass::Home => Self::Home,
+            ApiGroupClass::Downstairs => Self::Downstairs,
+            ApiGroupClass::Upstairs => Self::Upstairs,
+            ApiGroupClass::TopFloor => Self::TopFloor,
+            ApiGroupClass::Attic => Self::Attic,
+            ApiGroupClass::GuestRoom => Self::GuestRoom,
+            ApiGroupClass::Staircase => Self::Staircase,
+            ApiGroupClass::Lounge => Self::Lounge,
+            ApiGroupClass::ManCave => Self::ManCave,
+            ApiGroupClass::Computer => Self::Computer,
+            ApiGroupClass::Studio => Self::Studio,
+            ApiGroupClass::Music => Self::Music,
+            ApiGroupClass::TV => Self::Tv,
+            ApiGroupClass::Reading => Self::Reading,
+            ApiGroupClass::Closet => Self::Closet,
+            ApiGroupClass::Storage => Self::Storage,
+            ApiGroupClass::LaundryRoom => Self::LaundryRoom,
+            ApiGroupClass::Balcony => Self::Balcony,
+            ApiGroupClass::Porch => Self::Porch,
+            ApiGroupClass::Barbecue => Self::Barbecue,
+            ApiGroupClass::Pool => Self::Pool,
+        }
+    }
+}
+
+impl From<api::RoomArchetype> for ApiGroupClass {
+    fn from(archetype: api::RoomArchetype) -> Self {
+        match archetype {
+            api::RoomArchetype::LivingRoom => Self::LivingRoom,
+            api::RoomArchetype::Kitchen => Self::Kitchen,
+            api::RoomArchetype::Dining => Self::Dining,
+            api::RoomArchetype::Bedroom => Self::Bedroom,
+            api::RoomArchetype::KidsBedroom => Self::KidsBedroom,
+            api::RoomArchetype::Bathroom => Self::Bathroom,
+            api::RoomArchetype::Nursery => Self::Nursery,
+            api::RoomArchetype::Office => Self::Office,
+            api::RoomArchetype::GuestRoom => Self::GuestRoom,
+            api::RoomArchetype::Toilet => Self::Toilet,
+            api::RoomArchetype::Staircase => Self::Staircase,
+            api::RoomArchetype::Hallway => Self::Hallway,
+            api::RoomArchetype::LaundryRoom => Self::LaundryRoom,
+            api::RoomArchetype::Storage => Self::Storage,
+            api::RoomArchetype::Closet => Self::Closet,
+            api::RoomArchetype::Garage => Self::Garage,
+            api::RoomArchetype::Other => Self::Other,
+            api::RoomArchetype::Gym => Self::Gym,
+            api::RoomArchetype::Lounge => Self::Lounge,
+            api::RoomArchetype::Tv => Self::TV,
+            api::RoomArchetype::Computer => Self::Computer,
+            api::RoomArchetype::Recreation => Self::Recreation,
+            api::RoomArchetype::ManCave => Self::ManCave,
+            api::RoomArchetype::Music => Self::Music,
+            api::RoomArchetype::Reading => Self::Reading,
+            api::RoomArchetype::Studio => Self::Studio,
+            api::RoomArchetype::Garden => Self::Garden,
+            api::RoomArchetype::Terrace => Self::Terrace,
+            api::RoomArchetype::Balcony => Self::Balcony,
+            api::RoomArchetype::Driveway => Self::Driveway,
+            api::RoomArchetype::Carport => Self::Carport,
+            api::RoomArchetype::FrontDoor => Self::FrontDoor,
+            api::RoomArchetype::Porch => Self::Porch,
+            api::RoomArchetype::Barbecue => Self::Barbecue,
+            api::RoomArchetype::Pool => Self::Pool,
+            api::RoomArchetype::Downstairs => Self::Downstairs,
+            api::RoomArchetype::Upstairs => Self::Upstairs,
+            api::RoomArchetype::TopFloor => Self::TopFloor,
+            api::RoomArchetype::Attic => Self::Attic,
+            api::RoomArchetype::Home => Self::Home,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ApiGroup {
     pub name: String,
@@ -404,15 +502,33 @@ impl ApiGroup {
         }
     }
 
-    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
     #[must_use]
     pub fn from_lights_and_room(
         glight: &api::GroupedLight,
         lights: Vec<String>,
         room: api::Room,
+    ) -> Self {
+        Self::from_lights_and_metadata(glight, lights, room.metadata, ApiGroupType::Room)
+    }
+
+    #[must_use]
+    pub fn from_lights_and_zone(
+        glight: &api::GroupedLight,
+        lights: Vec<String>,
+        zone: api::Zone,
+    ) -> Self {
+        Self::from_lights_and_metadata(glight, lights, zone.metadata, ApiGroupType::Zone)
+    }
+
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    fn from_lights_and_metadata(
+        glight: &api::GroupedLight,
+        lights: Vec<String>,
+        metadata: api::RoomMetadata,
+        group_type: ApiGroupType,
     ) -> Self {
         Self {
-            name: room.metadata.name,
+            name: metadata.name,
             lights,
             action: ApiGroupAction {
                 on: glight.on.is_some_and(|on| on.on),
@@ -425,8 +541,8 @@ impl ApiGroup {
                 alert: ApiAlert::None,
                 colormode: None,
             },
-            class: ApiGroupClass::default(),
-            group_type: ApiGroupType::Room,
+            class: metadata.archetype.into(),
+            group_type,
             recycle: false,
             sensors: vec![],
             state: ApiGroupState::default(),
@@ -502,9 +618,21 @@ pub struct Active {
 pub struct ApiGroupUpdate2 {
     pub lights: Option<Vec<String>>,
     pub name: Option<String>,
+    pub class: Option<ApiGroupClass>,
     pub stream: Option<Active>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiLightUpdate {
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiConfigUpdate {
+    pub name: Option<String>,
+    pub linkbutton: Option<bool>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 #[serde(untagged)]
 pub enum ApiGroupActionUpdate {
@@ -512,6 +640,48 @@ pub enum ApiGroupActionUpdate {
     LightUpdate(ApiLightStateUpdate),
 }
 
+impl From<&ApiLightStateUpdate> for api::SceneAction {
+    fn from(upd: &ApiLightStateUpdate) -> Self {
+        Self {
+            color: upd
+                .hs
+                .map(|hs| XY::from_hs(hs.into()).0)
+                .or_else(|| upd.xy.map(Into::into))
+                .map(api::ColorUpdate::new),
+            color_temperature: upd.ct.map(api::ColorTemperatureUpdate::new),
+            dimming: upd
+                .bri
+                .map(|bri| api::DimmingUpdate::new(f64::from(bri) / 2.54)),
+            on: upd.on.map(api::On::new),
+            gradient: None,
+            effects: Value::Null,
+        }
+    }
+}
+
+/// Merge a v1 lightstate into a scene action. Setting either color or color
+/// temperature replaces the other.
+impl AddAssign<&ApiLightStateUpdate> for api::SceneAction {
+    fn add_assign(&mut self, upd: &ApiLightStateUpdate) {
+        let action = Self::from(upd);
+
+        if action.on.is_some() {
+            self.on = action.on;
+        }
+        if action.dimming.is_some() {
+            self.dimming = action.dimming;
+        }
+        if action.color.is_some() {
+            self.color = action.color;
+            self.color_temperature = None;
+        }
+        if action.color_temperature.is_some() {
+            self.color_temperature = action.color_temperature;
+            self.color = None;
+        }
+    }
+}
+
 impl From<api::SceneAction> for ApiLightStateUpdate {
     #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
     fn from(action: api::SceneAction) -> Self {
@@ -549,7 +719,12 @@ pub struct ApiLight {
 impl ApiLight {
     #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
     #[must_use]
-    pub fn from_dev_and_light(uuid: &Uuid, dev: &api::Device, light: &api::Light) -> Self {
+    pub fn from_dev_and_light(
+        uuid: &Uuid,
+        dev: &api::Device,
+        light: &api::Light,
+        reachable: bool,
+    ) -> Self {
         let colormode = if light.color.is_some() {
             LightColorMode::Xy
         } else {
@@ -572,7 +747,7 @@ impl ApiLight {
                 alert: "select".into(),
                 colormode: Some(colormode),
                 mode: "homeautomation".to_string(),
-                reachable: true,
+                reachable,
             },
             swupdate: SwUpdate::default(),
             name: light.metadata.name.clone(),
@@ -624,33 +799,260 @@ impl ApiLight {
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ApiResourceLink {
     #[serde(rename = "type")]
     pub link_type: String,
     pub name: String,
     pub description: String,
     pub classid: u32,
-    pub owner: Uuid,
+    pub owner: String,
     pub recycle: bool,
     pub links: Vec<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+pub struct ApiResourceLinkNew {
+    pub name: Option<String>,
+    #[serde(default)]
+    pub description: String,
+    pub classid: u32,
+    #[serde(default)]
+    pub recycle: bool,
+    pub links: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ApiResourceLinkUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub classid: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub links: Option<Vec<String>>,
+}
+
+impl ApiResourceLink {
+    /// Maximum number of links in a resourcelink
+    pub const MAX_LINKS: usize = 64;
+
+    #[must_use]
+    pub fn new(new: ApiResourceLinkNew, owner: &str, id: u32) -> Self {
+        Self {
+            link_type: "Link".to_string(),
+            name: new.name.unwrap_or_else(|| format!("Resourcelink {id}")),
+            description: new.description,
+            classid: new.classid,
+            owner: owner.to_string(),
+            recycle: new.recycle,
+            links: new.links,
+        }
+    }
+}
+
+impl AddAssign<ApiResourceLinkUpdate> for ApiResourceLink {
+    fn add_assign(&mut self, upd: ApiResourceLinkUpdate) {
+        if let Some(name) = upd.name {
+            self.name = name;
+        }
+        if let Some(description) = upd.description {
+            self.description = description;
+        }
+        if let Some(classid) = upd.classid {
+            self.classid = classid;
+        }
+        if let Some(links) = upd.links {
+            self.links = links;
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiRuleStatus {
+    #[default]
+    Enabled,
+    Disabled,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ApiRuleOperator {
+    #[serde(rename = "eq")]
+    Eq,
+    #[serde(rename = "gt")]
+    Gt,
+    #[serde(rename = "lt")]
+    Lt,
+    #[serde(rename = "dx")]
+    Dx,
+    #[serde(rename = "ddx")]
+    Ddx,
+    #[serde(rename = "stable")]
+    Stable,
+    #[serde(rename = "not stable")]
+    NotStable,
+    #[serde(rename = "in")]
+    In,
+    #[serde(rename = "not in")]
+    NotIn,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ApiRuleCondition {
+    pub address: String,
+    pub operator: ApiRuleOperator,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
+}
+
+impl ApiRuleCondition {
+    /// Check that the condition has a value, and that it makes sense for the operator
+    pub fn validate(&self) -> Result<(), HueApiV1Error> {
+        let value = self.value.as_deref();
+        let invalid = |_| HueApiV1Error::InvalidValueForParameter;
+
+        match self.operator {
+            ApiRuleOperator::Dx => Ok(()),
+            ApiRuleOperator::Eq | ApiRuleOperator::Gt | ApiRuleOperator::Lt => value
+                .map(drop)
+                .ok_or(HueApiV1Error::MissingParametersInBody),
+            ApiRuleOperator::Ddx | ApiRuleOperator::Stable | ApiRuleOperator::NotStable => {
+                let value = value.ok_or(HueApiV1Error::MissingParametersInBody)?;
+                legacy_time::parse_duration(value)
+                    .map(drop)
+                    .map_err(invalid)
+            }
+            ApiRuleOperator::In | ApiRuleOperator::NotIn => {
+                let value = value.ok_or(HueApiV1Error::MissingParametersInBody)?;
+                value.parse::<ApiTimeInterval>().map(drop).map_err(invalid)
+            }
+        }
+    }
+
+    /// Compare the current value of the attribute against the condition,
+    /// for the `eq`, `gt` and `lt` operators. All other operators depend on
+    /// the history of the attribute, and are evaluated by the rules engine.
+    #[must_use]
+    pub fn compare(&self, current: &Value) -> bool {
+        let Some(value) = self.value.as_deref() else {
+            return false;
+        };
+
+        match (self.operator, current) {
+            (ApiRuleOperator::Eq, Value::Bool(b)) => value.parse() == Ok(*b),
+            (ApiRuleOperator::Eq, Value::String(s)) => value == s,
+            (ApiRuleOperator::Eq, Value::Number(num)) => num.as_f64() == value.parse::<f64>().ok(),
+            (ApiRuleOperator::Gt, Value::Number(num)) => value
+                .parse::<f64>()
+                .is_ok_and(|val| num.as_f64().is_some_and(|num| num > val)),
+            (ApiRuleOperator::Lt, Value::Number(num)) => value
+                .parse::<f64>()
+                .is_ok_and(|val| num.as_f64().is_some_and(|num| num < val)),
+            _ => false,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum ApiMethod {
+    Put,
+    Post,
+    Delete,
+}
+
+/// A request against the v1 api, as used by rule actions and schedule commands
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ApiRequest {
+    pub address: String,
+    pub method: ApiMethod,
+    #[serde(default)]
+    pub body: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ApiRule {
     pub name: String,
     pub recycle: bool,
-    pub status: String,
-    pub conditions: Vec<Value>,
-    pub actions: Vec<Value>,
-    pub owner: Uuid,
+    pub status: ApiRuleStatus,
+    pub conditions: Vec<ApiRuleCondition>,
+    pub actions: Vec<ApiRequest>,
+    pub owner: String,
     pub timestriggered: u32,
     #[serde(with = "date_format::legacy_utc")]
     pub created: DateTime<Utc>,
-    pub lasttriggered: String,
+    #[serde(with = "date_format::legacy_utc_none", default)]
+    pub lasttriggered: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+pub struct ApiRuleNew {
+    pub name: Option<String>,
+    #[serde(default)]
+    pub status: ApiRuleStatus,
+    #[serde(default)]
+    pub recycle: bool,
+    pub conditions: Vec<ApiRuleCondition>,
+    pub actions: Vec<ApiRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ApiRuleUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ApiRuleStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub conditions: Option<Vec<ApiRuleCondition>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub actions: Option<Vec<ApiRequest>>,
+}
+
+impl ApiRule {
+    /// Maximum number of conditions (and actions) in a rule
+    pub const MAX_ITEMS: usize = 8;
+
+    #[must_use]
+    pub fn new(new: ApiRuleNew, owner: &str, id: u32) -> Self {
+        Self {
+            name: new.name.unwrap_or_else(|| format!("Rule {id}")),
+            recycle: new.recycle,
+            status: new.status,
+            conditions: new.conditions,
+            actions: new.actions,
+            owner: owner.to_string(),
+            timestriggered: 0,
+            created: Utc::now(),
+            lasttriggered: None,
+        }
+    }
+
+    pub fn trigger(&mut self) {
+        self.timestriggered += 1;
+        self.lasttriggered = Some(Utc::now());
+    }
+}
+
+impl AddAssign<ApiRuleUpdate> for ApiRule {
+    fn add_assign(&mut self, upd: ApiRuleUpdate) {
+        if let Some(name) = upd.name {
+            self.name = name;
+        }
+        if let Some(status) = upd.status {
+            self.status = status;
+        }
+        if let Some(conditions) = upd.conditions {
+            self.conditions = conditions;
+        }
+        if let Some(actions) = upd.actions {
+            self.actions = actions;
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub enum ApiSceneType {
     LightScene,
     GroupScene,
@@ -661,6 +1063,32 @@ pub enum ApiSceneVersion {
     V2 = 2,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiSceneNew {
+    pub name: String,
+    #[serde(rename = "type", default = "ApiSceneNew::default_type")]
+    pub scene_type: ApiSceneType,
+    #[serde(default)]
+    pub lights: Vec<String>,
+    pub group: Option<String>,
+    #[serde(default)]
+    pub lightstates: HashMap<String, ApiLightStateUpdate>,
+}
+
+impl ApiSceneNew {
+    const fn default_type() -> ApiSceneType {
+        ApiSceneType::LightScene
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiSceneUpdate {
+    pub name: Option<String>,
+    pub lights: Option<Vec<String>>,
+    #[serde(default)]
+    pub storelightstate: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ApiSceneAppData {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -691,14 +1119,22 @@ pub struct ApiScene {
     pub group: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ApiScheduleStatus {
+    #[default]
+    Enabled,
+    Disabled,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ApiSchedule {
     pub recycle: bool,
     pub name: String,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub autodelete: Option<bool>,
     pub description: String,
-    pub command: Value,
+    pub command: ApiRequest,
     #[serde(with = "date_format::legacy_utc")]
     pub created: DateTime<Utc>,
     #[serde(
@@ -709,13 +1145,170 @@ pub struct ApiSchedule {
     pub starttime: Option<DateTime<Utc>>,
     pub time: String,
     pub localtime: String,
-    pub status: String,
+    pub status: ApiScheduleStatus,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
+pub struct ApiScheduleNew {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub command: ApiRequest,
+    pub localtime: Option<String>,
+    pub time: Option<String>,
+    #[serde(default)]
+    pub status: ApiScheduleStatus,
+    pub autodelete: Option<bool>,
+    #[serde(default)]
+    pub recycle: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default)]
+pub struct ApiScheduleUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<ApiRequest>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub localtime: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ApiScheduleStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub autodelete: Option<bool>,
+}
+
+impl ApiScheduleUpdate {
+    /// The new `localtime` of the schedule, if any. Older clients only set
+    /// `time`, which is in utc.
+    #[must_use]
+    pub fn localtime(&self) -> Option<String> {
+        self.localtime
+            .clone()
+            .or_else(|| self.time.as_deref().map(legacy_time::absolute_from_utc))
+    }
+}
+
+impl ApiSchedule {
+    /// Parse and check a schedule time, as given by a client
+    pub fn parse_localtime(localtime: &str) -> Result<ApiScheduleTime, HueApiV1Error> {
+        let time: ApiScheduleTime = localtime
+            .parse()
+            .map_err(|_| HueApiV1Error::InvalidValueForParameter)?;
+
+        if let ApiTimePattern::Absolute(at) = time.pattern
+            && at <= Local::now().naive_local()
+        {
+            return Err(HueApiV1Error::InvalidValueForParameter);
+        }
+
+        Ok(time)
+    }
+
+    pub fn new(new: ApiScheduleNew) -> Result<Self, HueApiV1Error> {
+        let localtime = new
+            .localtime
+            .or_else(|| new.time.as_deref().map(legacy_time::absolute_from_utc))
+            .ok_or(HueApiV1Error::MissingParametersInBody)?;
+
+        let time = Self::parse_localtime(&localtime)?;
+        let now = Utc::now();
+
+        Ok(Self {
+            recycle: new.recycle,
+            name: new.name.unwrap_or_else(|| "schedule".to_string()),
+            autodelete: new.autodelete,
+            description: new.description.unwrap_or_default(),
+            command: new.command,
+            created: now,
+            starttime: matches!(time.pattern, ApiTimePattern::Timer(_)).then_some(now),
+            time: legacy_time::absolute_to_utc(&localtime),
+            localtime,
+            status: new.status,
+        })
+    }
+
+    pub fn schedule_time(&self) -> HueResult<ApiScheduleTime> {
+        self.localtime.parse()
+    }
+
+    /// Whether a non-recurring schedule is deleted after it has run (the
+    /// default), rather than disabled
+    #[must_use]
+    pub fn autodelete(&self) -> bool {
+        self.autodelete.unwrap_or(true)
+    }
+
+    /// (Re)start the timer of the schedule, if it has one
+    fn restart(&mut self) {
+        if let Ok(ApiTimePattern::Timer(_)) = self.schedule_time().map(|time| time.pattern) {
+            self.starttime = Some(Utc::now());
+        } else {
+            self.starttime = None;
+        }
+    }
+}
+
+impl AddAssign<ApiScheduleUpdate> for ApiSchedule {
+    fn add_assign(&mut self, upd: ApiScheduleUpdate) {
+        if let Some(localtime) = upd.localtime() {
+            self.time = legacy_time::absolute_to_utc(&localtime);
+            self.localtime = localtime;
+            self.restart();
+        }
+        if let Some(name) = upd.name {
+            self.name = name;
+        }
+        if let Some(description) = upd.description {
+            self.description = description;
+        }
+        if let Some(command) = upd.command {
+            self.command = command;
+        }
+        if let Some(status) = upd.status {
+            if status == ApiScheduleStatus::Enabled && self.status == ApiScheduleStatus::Disabled {
+                self.restart();
+            }
+            self.status = status;
+        }
+        if let Some(autodelete) = upd.autodelete {
+            self.autodelete = Some(autodelete);
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum ApiSensorType {
+    #[serde(rename = "ZLLSwitch")]
+    ZllSwitch,
+    #[serde(rename = "ZLLPresence")]
+    ZllPresence,
+    #[serde(rename = "ZLLLightLevel")]
+    ZllLightLevel,
+    #[serde(rename = "ZLLTemperature")]
+    ZllTemperature,
+    #[serde(rename = "CLIPGenericStatus")]
+    ClipGenericStatus,
+    #[serde(rename = "CLIPGenericFlag")]
+    ClipGenericFlag,
+    Daylight,
+}
+
+impl ApiSensorType {
+    /// Virtual ("CLIP") sensors can be created by clients, and hold whatever
+    /// state the clients (or rules) put in them
+    #[must_use]
+    pub const fn is_clip(self) -> bool {
+        matches!(self, Self::ClipGenericStatus | Self::ClipGenericFlag)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ApiSensor {
     #[serde(rename = "type")]
-    pub sensor_type: String,
+    pub sensor_type: ApiSensorType,
     pub config: Value,
     pub name: String,
     pub state: Value,
@@ -736,12 +1329,294 @@ pub struct ApiSensor {
     pub capabilities: Value,
 }
 
+/// Format the `lastupdated` field of a sensor state, which is "none" until
+/// the sensor has reported anything.
+fn sensor_lastupdated(changed: Option<DateTime<Utc>>) -> Value {
+    changed.map_or_else(
+        || json!("none"),
+        |dt| json!(dt.format("%Y-%m-%dT%H:%M:%S").to_string()),
+    )
+}
+
 impl ApiSensor {
+    /* Zigbee endpoint and cluster of each sensor type, used to make v1
+     * unique ids on the form "<device>-<endpoint>-<cluster>" */
+    const ENDPOINT: u8 = 0x02;
+    const CLUSTER_SWITCH: u16 = 0xFC00;
+    const CLUSTER_OCCUPANCY: u16 = 0x0406;
+    const CLUSTER_ILLUMINANCE: u16 = 0x0400;
+    const CLUSTER_TEMPERATURE: u16 = 0x0402;
+
+    /* Default thresholds for the `dark` and `daylight` light level flags */
+    const LIGHTLEVEL_THOLD_DARK: u32 = 16000;
+    const LIGHTLEVEL_THOLD_OFFSET: u32 = 7000;
+
+    /// The v1 `buttonevent` code for a button event, on the form
+    /// `<button>00<event>`. For example, 1002 is a short release of the first
+    /// button (`control_id` 1).
+    #[must_use]
+    pub const fn button_event(control_id: u32, event: api::ButtonEvent) -> u32 {
+        let code = match event {
+            api::ButtonEvent::InitialPress => 0,
+            api::ButtonEvent::Repeat | api::ButtonEvent::LongPress => 1,
+            api::ButtonEvent::ShortRelease | api::ButtonEvent::DoubleShortRelease => 2,
+            api::ButtonEvent::LongRelease => 3,
+        };
+        control_id * 1000 + code
+    }
+
+    fn from_device(
+        dev_id: &Uuid,
+        dev: &api::Device,
+        sensor_type: ApiSensorType,
+        cluster: u16,
+        config: Value,
+        state: Value,
+    ) -> Self {
+        let product_data = dev.product_data.clone();
+
+        Self {
+            sensor_type,
+            config,
+            name: dev.metadata.name.clone(),
+            state,
+            manufacturername: product_data.manufacturer_name,
+            modelid: product_data.model_id,
+            swversion: product_data.software_version,
+            swupdate: Some(SwUpdate::default()),
+            uniqueid: Some(format!(
+                "{}-{:02x}-{cluster:04x}",
+                dev_id.as_simple(),
+                Self::ENDPOINT
+            )),
+            diversityid: None,
+            productname: Some(product_data.product_name),
+            recycle: Some(false),
+            capabilities: Value::Null,
+        }
+    }
+
+    /// Make a `ZLLSwitch` sensor from the buttons of a device. The state
+    /// reflects the most recently pressed button.
+    #[must_use]
+    pub fn from_dev_and_buttons(
+        dev_id: &Uuid,
+        dev: &api::Device,
+        buttons: &[&api::Button],
+        battery: Option<u8>,
+    ) -> Self {
+        let last = buttons
+            .iter()
+            .filter_map(|btn| Some((btn.metadata.control_id, btn.button.button_report.as_ref()?)))
+            .max_by_key(|(_, report)| report.updated);
+
+        let state = json!({
+            "buttonevent": last.map(|(control_id, report)| Self::button_event(control_id, report.event)),
+            "lastupdated": sensor_lastupdated(last.map(|(_, report)| report.updated)),
+        });
+
+        let config = json!({
+            "on": true,
+            "battery": battery,
+            "reachable": true,
+            "pending": [],
+        });
+
+        Self::from_device(
+            dev_id,
+            dev,
+            ApiSensorType::ZllSwitch,
+            Self::CLUSTER_SWITCH,
+            config,
+            state,
+        )
+    }
+
+    #[must_use]
+    pub fn from_dev_and_motion(
+        dev_id: &Uuid,
+        dev: &api::Device,
+        motion: &api::Motion,
+        battery: Option<u8>,
+    ) -> Self {
+        let report = motion.motion.motion_report.as_ref();
+
+        let state = json!({
+            "presence": report.map_or(motion.motion.motion, |rep| rep.motion),
+            "lastupdated": sensor_lastupdated(report.map(|rep| rep.changed)),
+        });
+
+        let config = json!({
+            "on": motion.enabled,
+            "battery": battery,
+            "reachable": true,
+            "alert": "none",
+            "sensitivity": motion.sensitivity.as_ref().map(|sens| sens.sensitivity),
+            "sensitivitymax": motion.sensitivity.as_ref().and_then(|sens| sens.sensitivity_max),
+            "pending": [],
+        });
+
+        Self::from_device(
+            dev_id,
+            dev,
+            ApiSensorType::ZllPresence,
+            Self::CLUSTER_OCCUPANCY,
+            config,
+            state,
+        )
+    }
+
+    #[must_use]
+    pub fn from_dev_and_light_level(
+        dev_id: &Uuid,
+        dev: &api::Device,
+        light_level: &api::LightLevel,
+        battery: Option<u8>,
+    ) -> Self {
+        let report = light_level.light.light_level_report.as_ref();
+        let level = report.map_or(light_level.light.light_level, |rep| rep.light_level);
+
+        let state = json!({
+            "lightlevel": level,
+            "dark": level < Self::LIGHTLEVEL_THOLD_DARK,
+            "daylight": level >= Self::LIGHTLEVEL_THOLD_DARK + Self::LIGHTLEVEL_THOLD_OFFSET,
+            "lastupdated": sensor_lastupdated(report.map(|rep| rep.changed)),
+        });
+
+        let config = json!({
+            "on": light_level.enabled,
+            "battery": battery,
+            "reachable": true,
+            "alert": "none",
+            "tholddark": Self::LIGHTLEVEL_THOLD_DARK,
+            "tholdoffset": Self::LIGHTLEVEL_THOLD_OFFSET,
+            "pending": [],
+        });
+
+        Self::from_device(
+            dev_id,
+            dev,
+            ApiSensorType::ZllLightLevel,
+            Self::CLUSTER_ILLUMINANCE,
+            config,
+            state,
+        )
+    }
+
+    #[allow(clippy::cast_possible_truncation)]
+    #[must_use]
+    pub fn from_dev_and_temperature(
+        dev_id: &Uuid,
+        dev: &api::Device,
+        temperature: &api::Temperature,
+        battery: Option<u8>,
+    ) -> Self {
+        let report = temperature.temperature.temperature_report.as_ref();
+        let celsius = report.map_or(temperature.temperature.temperature, |rep| rep.temperature);
+
+        let state = json!({
+            /* v1 temperatures are in 0.01 degrees celsius */
+            "temperature": (celsius * 100.0).round() as i32,
+            "lastupdated": sensor_lastupdated(report.map(|rep| rep.changed)),
+        });
+
+        let config = json!({
+            "on": temperature.enabled,
+            "battery": battery,
+            "reachable": true,
+            "alert": "none",
+            "pending": [],
+        });
+
+        Self::from_device(
+            dev_id,
+            dev,
+            ApiSensorType::ZllTemperature,
+            Self::CLUSTER_TEMPERATURE,
+            config,
+            state,
+        )
+    }
+
+    /// Make a virtual sensor, as requested by a client
     #[must_use]
-    pub fn builtin_daylight_sensor() -> Self {
+    pub fn new_clip(new: ApiSensorNew) -> Self {
+        let state = match new.sensor_type {
+            ApiSensorType::ClipGenericFlag => json!({"flag": false}),
+            _ => json!({"status": 0}),
+        };
+
+        let mut sensor = Self {
+            sensor_type: new.sensor_type,
+            config: json!({"on": true, "reachable": true}),
+            name: new.name,
+            state,
+            manufacturername: new.manufacturername,
+            modelid: new.modelid,
+            swversion: new.swversion,
+            swupdate: None,
+            uniqueid: Some(new.uniqueid),
+            diversityid: None,
+            productname: None,
+            recycle: Some(new.recycle),
+            capabilities: Value::Null,
+        };
+
+        /* initial values must pass the same checks as later updates */
+        if let Some(state) = new.state.as_object() {
+            let _ = sensor.update_state(state);
+        }
+        if let Some(config) = new.config.as_object() {
+            let _ = sensor.update_config(config);
+        }
+        sensor.state["lastupdated"] = json!("none");
+
+        sensor
+    }
+
+    /// Apply a state update from a client (or rule), and return the keys
+    /// that were accepted. Unknown keys are ignored.
+    pub fn update_state<'a>(&mut self, upd: &'a Map<String, Value>) -> Vec<(&'a str, Value)> {
+        let updated = Self::merge(&mut self.state, upd);
+        if !updated.is_empty() {
+            self.state["lastupdated"] = sensor_lastupdated(Some(Utc::now()));
+        }
+        updated
+    }
+
+    /// Apply a config update from a client (or rule), and return the keys
+    /// that were accepted. Unknown keys are ignored.
+    pub fn update_config<'a>(&mut self, upd: &'a Map<String, Value>) -> Vec<(&'a str, Value)> {
+        Self::merge(&mut self.config, upd)
+    }
+
+    fn merge<'a>(target: &mut Value, upd: &'a Map<String, Value>) -> Vec<(&'a str, Value)> {
+        let mut updated = vec![];
+
+        for (key, value) in upd {
+            if key == "lastupdated" {
+                continue;
+            }
+            if let Some(old) = target.get_mut(key) {
+                *old = value.clone();
+                updated.push((key.as_str(), value.clone()));
+            }
+        }
+
+        updated
+    }
+
+    /// The built-in daylight sensor. If the bridge location is known,
+    /// `daylight` tells whether the sun is currently up, and `lastupdated` is
+    /// the time of the last sunrise or sunset.
+    #[must_use]
+    pub fn builtin_daylight_sensor(
+        daylight: Option<bool>,
+        lastupdated: Option<DateTime<Utc>>,
+    ) -> Self {
         Self {
             config: json!({
-                "configured": false,
+                "configured": daylight.is_some(),
                 "on": true,
                 "sunriseoffset": 30,
                 "sunsetoffset": -30
@@ -750,11 +1625,11 @@ impl ApiSensor {
             modelid: "PHDL00".to_string(),
             name: "Daylight".to_string(),
             state: json!({
-                "daylight": Value::Null,
-                "lastupdated": "none",
+                "daylight": daylight,
+                "lastupdated": sensor_lastupdated(lastupdated),
             }),
             swversion: "1.0".to_string(),
-            sensor_type: "Daylight".to_string(),
+            sensor_type: ApiSensorType::Daylight,
             swupdate: None,
             uniqueid: None,
             diversityid: None,
@@ -765,6 +1640,28 @@ impl ApiSensor {
     }
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiSensorNew {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub sensor_type: ApiSensorType,
+    pub modelid: String,
+    pub manufacturername: String,
+    pub swversion: String,
+    pub uniqueid: String,
+    #[serde(default)]
+    pub recycle: bool,
+    #[serde(default)]
+    pub state: Value,
+    #[serde(default)]
+    pub config: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApiSensorUpdate {
+    pub name: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ApiUserConfig {
     pub config: ApiConfig,
@@ -927,4 +1824,194 @@ mod tests {
 
         assert_eq!(res, b"\"01:02:03:aa:bb:cc\"");
     }
+
+    #[test]
+    fn button_event_codes() {
+        use crate::api::ButtonEvent;
+        use crate::legacy_api::ApiSensor;
+
+        assert_eq!(ApiSensor::button_event(1, ButtonEvent::InitialPress), 1000);
+        assert_eq!(ApiSensor::button_event(1, ButtonEvent::ShortRelease), 1002);
+        assert_eq!(ApiSensor::button_event(2, ButtonEvent::Repeat), 2001);
+        assert_eq!(ApiSensor::button_event(4, ButtonEvent::LongRelease), 4003);
+    }
+
+    #[test]
+    fn temperature_sensor() {
+        use serde_json::json;
+        use uuid::Uuid;
+
+        use crate::api::{Device, RType, Temperature};
+        use crate::legacy_api::{ApiSensor, ApiSensorType};
+
+        let dev: Device = serde_json::from_value(json!({
+            "product_data": {
+                "model_id": "SML001",
+                "manufacturer_name": "Signify Netherlands B.V.",
+                "product_name": "Hue motion sensor",
+                "product_archetype": "unknown_archetype",
+                "certified": true,
+                "software_version": "1.1.27575",
+            },
+            "metadata": {"name": "Hallway sensor", "archetype": "unknown_archetype"},
+            "services": [],
+        }))
+        .unwrap();
+
+        let dev_id = Uuid::from_bytes([1; 16]);
+        let mut temp = Temperature::new(RType::Device.link_to(dev_id));
+
+        let sensor = ApiSensor::from_dev_and_temperature(&dev_id, &dev, &temp, Some(80));
+        assert_eq!(sensor.sensor_type, ApiSensorType::ZllTemperature);
+        assert_eq!(sensor.state["lastupdated"], "none");
+        assert_eq!(sensor.config["battery"], 80);
+        assert_eq!(
+            sensor.uniqueid.as_deref(),
+            Some("01010101010101010101010101010101-02-0402")
+        );
+
+        temp.report(21.456);
+        let sensor = ApiSensor::from_dev_and_temperature(&dev_id, &dev, &temp, None);
+        assert_eq!(sensor.state["temperature"], 2146);
+        assert_ne!(sensor.state["lastupdated"], "none");
+    }
+
+    #[test]
+    fn rule_condition_compare() {
+        use serde_json::json;
+
+        use crate::legacy_api::ApiRuleCondition;
+
+        let cond = |operator: &str, value: &str| -> ApiRuleCondition {
+            serde_json::from_value(json!({
+                "address": "/sensors/2/state/buttonevent",
+                "operator": operator,
+                "value": value,
+            }))
+            .unwrap()
+        };
+
+        assert!(cond("eq", "1002").compare(&json!(1002)));
+        assert!(!cond("eq", "1002").compare(&json!(2002)));
+        assert!(cond("eq", "true").compare(&json!(true)));
+        assert!(!cond("eq", "true").compare(&json!(false)));
+        assert!(cond("gt", "16000").compare(&json!(16001)));
+        assert!(!cond("gt", "16000").compare(&json!(16000)));
+        assert!(cond("lt", "1").compare(&json!(0)));
+        assert!(!cond("lt", "1").compare(&json!("0")));
+    }
+
+    #[test]
+    fn rule_condition_validate() {
+        use serde_json::json;
+
+        use crate::error::HueApiV1Error;
+        use crate::legacy_api::ApiRuleCondition;
+
+        let cond = |value: serde_json::Value| -> ApiRuleCondition {
+            serde_json::from_value(value).unwrap()
+        };
+
+        let address = "/sensors/2/state/presence";
+        assert!(
+            cond(json!({"address": address, "operator": "dx"}))
+                .validate()
+                .is_ok()
+        );
+        assert!(
+            cond(json!({"address": address, "operator": "stable", "value": "PT00:05:00"}))
+                .validate()
+                .is_ok()
+        );
+        assert_eq!(
+            cond(json!({"address": address, "operator": "eq"})).validate(),
+            Err(HueApiV1Error::MissingParametersInBody)
+        );
+        assert_eq!(
+            cond(json!({"address": "/config/localtime", "operator": "in", "value": "T25:00:00"}))
+                .validate(),
+            Err(HueApiV1Error::InvalidValueForParameter)
+        );
+    }
+
+    #[test]
+    fn scene_action_merge_lightstate() {
+        use serde_json::json;
+
+        use crate::api::{ColorTemperatureUpdate, DimmingUpdate, On, SceneAction};
+        use crate::legacy_api::ApiLightStateUpdate;
+
+        let upd = |value: serde_json::Value| -> ApiLightStateUpdate {
+            serde_json::from_value(value).unwrap()
+        };
+
+        let mut action = SceneAction::from(&upd(json!({"on": true, "bri": 254, "ct": 300})));
+        assert_eq!(action.on, Some(On::new(true)));
+        assert_eq!(action.dimming, Some(DimmingUpdate::new(254.0 / 2.54)));
+        assert_eq!(
+            action.color_temperature,
+            Some(ColorTemperatureUpdate::new(300))
+        );
+
+        // unset fields are kept, and color replaces color temperature
+        action += &upd(json!({"xy": [0.3, 0.4]}));
+        assert_eq!(action.on, Some(On::new(true)));
+        assert_eq!(action.dimming, Some(DimmingUpdate::new(254.0 / 2.54)));
+        assert_eq!(action.color_temperature, None);
+        assert!(action.color.is_some());
+
+        action += &upd(json!({"on": false, "ct": 250}));
+        assert_eq!(action.on, Some(On::new(false)));
+        assert_eq!(action.color, None);
+        assert_eq!(
+            action.color_temperature,
+            Some(ColorTemperatureUpdate::new(250))
+        );
+    }
+
+    #[test]
+    fn scene_action_from_hue_sat_lightstate() {
+        use serde_json::json;
+
+        use crate::api::{ColorUpdate, SceneAction};
+        use crate::hs::HS;
+        use crate::legacy_api::ApiLightStateUpdate;
+        use crate::xy::XY;
+
+        let upd: ApiLightStateUpdate =
+            serde_json::from_value(json!({"hue": 0xCCCC, "sat": 0xCC})).unwrap();
+
+        let (xy, _) = XY::from_hs(HS { hue: 0.8, sat: 0.8 });
+        let action = SceneAction::from(&upd);
+        assert_eq!(action.color, Some(ColorUpdate::new(xy)));
+
+        // hue/sat replaces color temperature, like xy does
+        let mut action = SceneAction::from(
+            &serde_json::from_value::<ApiLightStateUpdate>(json!({"ct": 300})).unwrap(),
+        );
+        action += &upd;
+        assert_eq!(action.color, Some(ColorUpdate::new(xy)));
+        assert_eq!(action.color_temperature, None);
+    }
+
+    #[test]
+    fn daylight_sensor_lastupdated() {
+        use chrono::{DateTime, Utc};
+        use serde_json::json;
+
+        use crate::legacy_api::ApiSensor;
+
+        let since: DateTime<Utc> = "2024-06-21T02:25:13Z".parse().unwrap();
+        let sensor = ApiSensor::builtin_daylight_sensor(Some(true), Some(since));
+        assert_eq!(
+            sensor.state,
+            json!({"daylight": true, "lastupdated": "2024-06-21T02:25:13"})
+        );
+
+        let sensor = ApiSensor::builtin_daylight_sensor(None, None);
+        assert_eq!(
+            sensor.state,
+            json!({"daylight": null, "lastupdated": "none"})
+        );
+    }
 }
@@ -0,0 +1,335 @@
+//! Time patterns used by the v1 api, in rules and schedules
+
+use std::str::FromStr;
+
+use chrono::{
+    DateTime, Datelike, Days, Local, NaiveDateTime, NaiveTime, TimeDelta, TimeZone, Utc, Weekday,
+};
+
+use crate::error::{HueError, HueResult};
+
+const TIME_FORMAT: &str = "%H:%M:%S";
+const DATETIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%S";
+
+fn parse_time(s: &str) -> HueResult<NaiveTime> {
+    NaiveTime::parse_from_str(s, TIME_FORMAT)
+        .map_err(|_| HueError::InvalidTimePattern(s.to_string()))
+}
+
+/// Parse a v1 duration on the form `PT01:30:00`
+pub fn parse_duration(s: &str) -> HueResult<TimeDelta> {
+    let err = || HueError::InvalidTimePattern(s.to_string());
+
+    let hms = s.strip_prefix("PT").ok_or_else(err)?;
+    let mut parts = hms.split(':').map(str::parse::<i64>);
+
+    match (parts.next(), parts.next(), parts.next(), parts.next()) {
+        (Some(Ok(h)), Some(Ok(m)), Some(Ok(s)), None) => {
+            Ok(TimeDelta::hours(h) + TimeDelta::minutes(m) + TimeDelta::seconds(s))
+        }
+        _ => Err(err()),
+    }
+}
+
+/// Set of weekdays, written as `W<bitmask>` where monday is 64 and sunday is 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApiWeekdays(pub u8);
+
+impl ApiWeekdays {
+    pub const ALL: Self = Self(0b0111_1111);
+
+    #[must_use]
+    pub const fn contains(self, weekday: Weekday) -> bool {
+        self.0 & (1 << (6 - weekday.num_days_from_monday())) != 0
+    }
+}
+
+impl FromStr for ApiWeekdays {
+    type Err = HueError;
+
+    fn from_str(s: &str) -> HueResult<Self> {
+        s.strip_prefix('W')
+            .and_then(|mask| mask.parse().ok())
+            .filter(|mask| *mask <= Self::ALL.0)
+            .map(Self)
+            .ok_or_else(|| HueError::InvalidTimePattern(s.to_string()))
+    }
+}
+
+/// A daily time interval, optionally limited to some weekdays, written as
+/// `T08:00:00/T22:00:00` or `W124/T08:00:00/T22:00:00`
+///
+/// Intervals where the end is before the start wrap around midnight. In that
+/// case, the weekdays refer to the day the interval starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApiTimeInterval {
+    pub weekdays: ApiWeekdays,
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl ApiTimeInterval {
+    #[must_use]
+    pub fn contains(&self, dt: &NaiveDateTime) -> bool {
+        let time = dt.time();
+        let today = self.weekdays.contains(dt.weekday());
+
+        if self.start <= self.end {
+            today && self.start <= time && time < self.end
+        } else {
+            let yesterday = self.weekdays.contains(dt.weekday().pred());
+            (today && time >= self.start) || (yesterday && time < self.end)
+        }
+    }
+}
+
+impl FromStr for ApiTimeInterval {
+    type Err = HueError;
+
+    fn from_str(s: &str) -> HueResult<Self> {
+        let err = || HueError::InvalidTimePattern(s.to_string());
+
+        let (weekdays, interval) = match s.split_once('/') {
+            Some((wd, rest)) if wd.starts_with('W') => (wd.parse()?, rest),
+            _ => (ApiWeekdays::ALL, s),
+        };
+
+        let (start, end) = interval.split_once('/').ok_or_else(err)?;
+
+        Ok(Self {
+            weekdays,
+            start: parse_time(start.strip_prefix('T').ok_or_else(err)?)?,
+            end: parse_time(end.strip_prefix('T').ok_or_else(err)?)?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiTimePattern {
+    /// A single point in (local) time: `2024-07-01T07:00:00`
+    Absolute(NaiveDateTime),
+    /// A time of day, on some weekdays: `W124/T07:00:00`
+    Recurring(ApiWeekdays, NaiveTime),
+    /// A delay, counting from the start time of the schedule: `PT00:10:00`
+    Timer(TimeDelta),
+}
+
+/// The `localtime` of a schedule
+///
+/// Every pattern can be randomized by appending `A` and a duration, e.g.
+/// `W127/T07:00:00A00:30:00`. Each trigger is then delayed by a random amount
+/// of time, up to that duration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApiScheduleTime {
+    pub pattern: ApiTimePattern,
+    pub random: Option<TimeDelta>,
+}
+
+impl ApiScheduleTime {
+    #[must_use]
+    pub const fn is_recurring(&self) -> bool {
+        matches!(self.pattern, ApiTimePattern::Recurring(..))
+    }
+
+    /// The next time the schedule is due, not counting randomization
+    ///
+    /// For recurring patterns, this is the first matching time after `now`.
+    /// Timers are due `start` plus the timer duration.
+    #[must_use]
+    pub fn next_after(&self, now: NaiveDateTime, start: NaiveDateTime) -> Option<NaiveDateTime> {
+        match self.pattern {
+            ApiTimePattern::Absolute(at) => Some(at),
+            ApiTimePattern::Timer(duration) => start.checked_add_signed(duration),
+            ApiTimePattern::Recurring(weekdays, time) => (0..=7)
+                .filter_map(|days| now.date().checked_add_days(Days::new(days)))
+                .filter(|date| weekdays.contains(date.weekday()))
+                .map(|date| date.and_time(time))
+                .find(|dt| *dt > now),
+        }
+    }
+}
+
+impl FromStr for ApiScheduleTime {
+    type Err = HueError;
+
+    fn from_str(s: &str) -> HueResult<Self> {
+        let err = || HueError::InvalidTimePattern(s.to_string());
+
+        let (pattern, random) = match s.split_once('A') {
+            Some((pattern, random)) => (pattern, Some(parse_duration(&format!("PT{random}"))?)),
+            None => (s, None),
+        };
+
+        let pattern = if pattern.starts_with("PT") {
+            ApiTimePattern::Timer(parse_duration(pattern)?)
+        } else if let Some((weekdays, time)) = pattern.split_once('/') {
+            ApiTimePattern::Recurring(
+                weekdays.parse()?,
+                parse_time(time.strip_prefix('T').ok_or_else(err)?)?,
+            )
+        } else {
+            ApiTimePattern::Absolute(
+                NaiveDateTime::parse_from_str(pattern, DATETIME_FORMAT).map_err(|_| err())?,
+            )
+        };
+
+        Ok(Self { pattern, random })
+    }
+}
+
+/// Apply `func` to the date and time of an absolute schedule time, keeping any
+/// randomization. Other patterns are returned unchanged.
+fn map_absolute(time: &str, func: impl Fn(NaiveDateTime) -> Option<NaiveDateTime>) -> String {
+    let (pattern, random) = match time.split_once('A') {
+        Some((pattern, random)) => (pattern, Some(random)),
+        None => (time, None),
+    };
+
+    let Some(dt) = NaiveDateTime::parse_from_str(pattern, DATETIME_FORMAT)
+        .ok()
+        .and_then(func)
+    else {
+        return time.to_string();
+    };
+
+    let dt = dt.format(DATETIME_FORMAT);
+    random.map_or_else(|| dt.to_string(), |random| format!("{dt}A{random}"))
+}
+
+/// Convert a schedule `localtime` to the (deprecated) utc `time` field
+#[must_use]
+pub fn absolute_to_utc(localtime: &str) -> String {
+    map_absolute(localtime, |dt| {
+        Local
+            .from_local_datetime(&dt)
+            .earliest()
+            .map(|dt| dt.naive_utc())
+    })
+}
+
+/// Convert a schedule `time` (in utc) to local time
+#[must_use]
+pub fn absolute_from_utc(time: &str) -> String {
+    map_absolute(time, |dt| {
+        Some(
+            DateTime::<Utc>::from_naive_utc_and_offset(dt, Utc)
+                .with_timezone(&Local)
+                .naive_local(),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{NaiveDate, NaiveDateTime, TimeDelta, Weekday};
+
+    use crate::legacy_time::{
+        ApiScheduleTime, ApiTimeInterval, ApiTimePattern, ApiWeekdays, absolute_from_utc,
+        absolute_to_utc, parse_duration,
+    };
+
+    fn at(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+        // 2024-07-01 is a monday
+        NaiveDate::from_ymd_opt(2024, 7, day)
+            .unwrap()
+            .and_hms_opt(hour, minute, 0)
+            .unwrap()
+    }
+
+    #[test]
+    fn duration() {
+        assert_eq!(
+            parse_duration("PT00:00:10").unwrap(),
+            TimeDelta::seconds(10)
+        );
+        assert_eq!(
+            parse_duration("PT01:30:00").unwrap(),
+            TimeDelta::minutes(90)
+        );
+        assert!(parse_duration("00:00:10").is_err());
+        assert!(parse_duration("PT00:10").is_err());
+    }
+
+    #[test]
+    fn weekdays() {
+        let wd: ApiWeekdays = "W124".parse().unwrap();
+        assert!(wd.contains(Weekday::Mon));
+        assert!(wd.contains(Weekday::Fri));
+        assert!(!wd.contains(Weekday::Sat));
+        assert!(!wd.contains(Weekday::Sun));
+
+        assert!("W128".parse::<ApiWeekdays>().is_err());
+        assert!("127".parse::<ApiWeekdays>().is_err());
+    }
+
+    #[test]
+    fn interval() {
+        let iv: ApiTimeInterval = "T08:00:00/T22:00:00".parse().unwrap();
+        assert!(!iv.contains(&at(1, 7, 59)));
+        assert!(iv.contains(&at(1, 8, 0)));
+        assert!(iv.contains(&at(6, 21, 59)));
+        assert!(!iv.contains(&at(6, 22, 0)));
+    }
+
+    #[test]
+    fn interval_weekdays_wrapping() {
+        // fridays only, from 22:00 until 02:00 the next morning
+        let iv: ApiTimeInterval = "W4/T22:00:00/T02:00:00".parse().unwrap();
+        assert!(!iv.contains(&at(4, 23, 0)));
+        assert!(iv.contains(&at(5, 23, 0)));
+        assert!(iv.contains(&at(6, 1, 0)));
+        assert!(!iv.contains(&at(6, 23, 0)));
+        assert!(!iv.contains(&at(5, 1, 0)));
+    }
+
+    #[test]
+    fn schedule_time_forms() {
+        let abs: ApiScheduleTime = "2024-07-01T07:00:00".parse().unwrap();
+        assert_eq!(abs.pattern, ApiTimePattern::Absolute(at(1, 7, 0)));
+        assert_eq!(abs.random, None);
+
+        let timer: ApiScheduleTime = "PT00:10:00A00:00:30".parse().unwrap();
+        assert_eq!(timer.pattern, ApiTimePattern::Timer(TimeDelta::minutes(10)));
+        assert_eq!(timer.random, Some(TimeDelta::seconds(30)));
+        assert_eq!(
+            timer.next_after(at(1, 12, 0), at(1, 8, 0)),
+            Some(at(1, 8, 10))
+        );
+
+        assert!("2024-07-01 07:00:00".parse::<ApiScheduleTime>().is_err());
+        assert!("W127/07:00:00".parse::<ApiScheduleTime>().is_err());
+        assert!("PT00:10:00A10".parse::<ApiScheduleTime>().is_err());
+    }
+
+    #[test]
+    fn schedule_time_recurring() {
+        // weekends only, at 09:00
+        let rec: ApiScheduleTime = "W3/T09:00:00".parse().unwrap();
+        assert!(rec.is_recurring());
+
+        assert_eq!(
+            rec.next_after(at(1, 12, 0), at(1, 12, 0)),
+            Some(at(6, 9, 0))
+        );
+        assert_eq!(rec.next_after(at(6, 8, 0), at(1, 12, 0)), Some(at(6, 9, 0)));
+        assert_eq!(rec.next_after(at(6, 9, 0), at(1, 12, 0)), Some(at(7, 9, 0)));
+        assert_eq!(
+            rec.next_after(at(7, 9, 0), at(1, 12, 0)),
+            Some(at(13, 9, 0))
+        );
+
+        let never: ApiScheduleTime = "W0/T09:00:00".parse().unwrap();
+        assert_eq!(never.next_after(at(1, 12, 0), at(1, 12, 0)), None);
+    }
+
+    #[test]
+    fn schedule_time_utc() {
+        let localtime = "2024-07-01T07:00:00A00:10:00";
+        let time = absolute_to_utc(localtime);
+        assert!(time.ends_with("A00:10:00"));
+        assert_eq!(absolute_from_utc(&time), localtime);
+
+        assert_eq!(absolute_to_utc("W127/T07:00:00"), "W127/T07:00:00");
+        assert_eq!(absolute_from_utc("PT00:10:00"), "PT00:10:00");
+    }
+}
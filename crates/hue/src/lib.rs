@@ -15,8 +15,10 @@ pub mod gamma;
 pub mod gradient;
 pub mod hs;
 pub mod legacy_api;
+pub mod legacy_time;
 pub mod scene_icons;
 pub mod stream;
+pub mod sun;
 pub mod update;
 pub mod version;
 pub mod xy;
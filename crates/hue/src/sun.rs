@@ -0,0 +1,294 @@
+use chrono::{DateTime, NaiveDate, TimeDelta, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Julian day number of the J2000.0 epoch (2000-01-01 12:00 UTC)
+const J2000: f64 = 2_451_545.0;
+
+/// Julian day number of the unix epoch (1970-01-01 00:00 UTC)
+const UNIX_EPOCH_JD: f64 = 2_440_587.5;
+
+/// Axial tilt of the earth, in degrees
+const EARTH_OBLIQUITY: f64 = 23.4397;
+
+/// Sun elevation at sunrise/sunset, in degrees. This is slightly below the
+/// horizon, to account for atmospheric refraction and the size of the solar disc.
+const SUN_ELEVATION: f64 = -0.833;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DayType {
+    Normal,
+    PolarDay,
+    PolarNight,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SunEvent {
+    Sunrise,
+    Sunset,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SunDay {
+    Normal {
+        sunrise: DateTime<Utc>,
+        sunset: DateTime<Utc>,
+    },
+    PolarDay,
+    PolarNight,
+}
+
+impl SunDay {
+    #[must_use]
+    pub const fn day_type(&self) -> DayType {
+        match self {
+            Self::Normal { .. } => DayType::Normal,
+            Self::PolarDay => DayType::PolarDay,
+            Self::PolarNight => DayType::PolarNight,
+        }
+    }
+
+    #[must_use]
+    pub const fn sunrise(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Normal { sunrise, .. } => Some(*sunrise),
+            Self::PolarDay | Self::PolarNight => None,
+        }
+    }
+
+    #[must_use]
+    pub const fn sunset(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Normal { sunset, .. } => Some(*sunset),
+            Self::PolarDay | Self::PolarNight => None,
+        }
+    }
+}
+
+/// Geographic location, used to calculate sunrise and sunset times
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sun {
+    pub latitude: f64,
+    pub longitude: f64,
+}
+
+impl Sun {
+    #[must_use]
+    pub const fn new(latitude: f64, longitude: f64) -> Self {
+        Self {
+            latitude,
+            longitude,
+        }
+    }
+
+    /// Calculate sunrise and sunset for the given date
+    ///
+    /// Uses the sunrise equation as described here:
+    ///
+    ///   <https://en.wikipedia.org/wiki/Sunrise_equation>
+    ///
+    /// The result is accurate to within a minute or two, which is plenty for
+    /// scheduling light changes.
+    #[must_use]
+    pub fn day(&self, date: NaiveDate) -> SunDay {
+        let epoch = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap_or_default();
+
+        #[allow(clippy::cast_precision_loss)]
+        let days = (date - epoch).num_days() as f64;
+
+        // mean solar time
+        let mean_time = days - self.longitude / 360.0;
+
+        // solar mean anomaly
+        let anomaly = 0.985_600_28f64.mul_add(mean_time, 357.5291) % 360.0;
+        let m = anomaly.to_radians();
+
+        // equation of the center
+        let center = 0.0003f64.mul_add(
+            (3.0 * m).sin(),
+            1.9148f64.mul_add(m.sin(), 0.0200 * (2.0 * m).sin()),
+        );
+
+        // ecliptic longitude
+        let lambda = ((anomaly + center + 180.0 + 102.9372) % 360.0).to_radians();
+
+        // solar transit (local solar noon)
+        let transit = 0.0069f64.mul_add(
+            -(2.0 * lambda).sin(),
+            0.0053f64.mul_add(m.sin(), J2000 + mean_time),
+        );
+
+        // declination of the sun
+        let sin_decl = lambda.sin() * EARTH_OBLIQUITY.to_radians().sin();
+        let cos_decl = sin_decl.asin().cos();
+
+        // hour angle
+        let lat = self.latitude.to_radians();
+        let cos_hour_angle = lat
+            .sin()
+            .mul_add(-sin_decl, SUN_ELEVATION.to_radians().sin())
+            / (lat.cos() * cos_decl);
+
+        if cos_hour_angle > 1.0 {
+            return SunDay::PolarNight;
+        }
+        if cos_hour_angle < -1.0 {
+            return SunDay::PolarDay;
+        }
+
+        let hour_angle = cos_hour_angle.acos().to_degrees() / 360.0;
+
+        match (
+            julian_to_utc(transit - hour_angle),
+            julian_to_utc(transit + hour_angle),
+        ) {
+            (Some(sunrise), Some(sunset)) => SunDay::Normal { sunrise, sunset },
+            _ => SunDay::PolarNight,
+        }
+    }
+
+    /// Whether the sun is up at `now`, and the time of the last sunrise or
+    /// sunset before that
+    ///
+    /// The time is `None` during polar day or night, when the last sunrise or
+    /// sunset is more than a day ago.
+    #[must_use]
+    pub fn daylight(&self, now: DateTime<Utc>) -> (bool, Option<DateTime<Utc>>) {
+        let today = now.date_naive();
+
+        let last = [today.pred_opt(), Some(today), today.succ_opt()]
+            .into_iter()
+            .flatten()
+            .map(|date| self.day(date))
+            .flat_map(|day| {
+                [
+                    day.sunrise().map(|time| (SunEvent::Sunrise, time)),
+                    day.sunset().map(|time| (SunEvent::Sunset, time)),
+                ]
+            })
+            .flatten()
+            .filter(|(_, time)| *time <= now)
+            .max_by_key(|(_, time)| *time);
+
+        match last {
+            Some((event, time)) => (event == SunEvent::Sunrise, Some(time)),
+            None => (self.day(today).day_type() == DayType::PolarDay, None),
+        }
+    }
+}
+
+/// Time of a sunrise or sunset (`event`) on the given date, in the timezone
+/// `tz`
+///
+/// Returns `None` if the location (`sun`) is unknown, or if the sun does not
+/// rise or set on that day.
+pub fn event_time<Tz: TimeZone>(
+    sun: Option<&Sun>,
+    event: SunEvent,
+    date: NaiveDate,
+    tz: &Tz,
+) -> Option<DateTime<Tz>> {
+    let day = sun?.day(date);
+    let time = match event {
+        SunEvent::Sunrise => day.sunrise(),
+        SunEvent::Sunset => day.sunset(),
+    }?;
+    Some(time.with_timezone(tz))
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn julian_to_utc(jd: f64) -> Option<DateTime<Utc>> {
+    let millis = ((jd - UNIX_EPOCH_JD) * 86_400_000.0).round() as i64;
+    DateTime::UNIX_EPOCH.checked_add_signed(TimeDelta::milliseconds(millis))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, NaiveDate, Utc};
+
+    use crate::sun::{DayType, Sun, SunDay};
+
+    fn time(value: &str) -> DateTime<Utc> {
+        value.parse().unwrap()
+    }
+
+    fn date(y: i32, m: u32, d: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(y, m, d).unwrap()
+    }
+
+    fn assert_near(value: Option<DateTime<Utc>>, expected: &str) {
+        let expected: DateTime<Utc> = expected.parse().unwrap();
+        let diff = (value.unwrap() - expected).num_seconds().abs();
+        assert!(diff < 120);
+    }
+
+    #[test]
+    fn copenhagen_summer() {
+        let day = Sun::new(55.6761, 12.5683).day(date(2024, 6, 21));
+        assert_eq!(day.day_type(), DayType::Normal);
+        assert_near(day.sunrise(), "2024-06-21T02:25:00Z");
+        assert_near(day.sunset(), "2024-06-21T19:57:00Z");
+    }
+
+    #[test]
+    fn copenhagen_winter() {
+        let day = Sun::new(55.6761, 12.5683).day(date(2024, 12, 21));
+        assert_near(day.sunrise(), "2024-12-21T07:37:00Z");
+        assert_near(day.sunset(), "2024-12-21T14:38:00Z");
+    }
+
+    #[test]
+    fn equator_equinox() {
+        let day = Sun::new(0.0, 0.0).day(date(2024, 3, 20));
+        assert_near(day.sunrise(), "2024-03-20T06:04:00Z");
+        assert_near(day.sunset(), "2024-03-20T18:10:00Z");
+    }
+
+    #[test]
+    fn western_longitude() {
+        // sunset in San Francisco is after midnight UTC
+        let day = Sun::new(37.7749, -122.4194).day(date(2024, 6, 21));
+        assert_near(day.sunrise(), "2024-06-21T12:48:00Z");
+        assert_near(day.sunset(), "2024-06-22T03:35:00Z");
+    }
+
+    #[test]
+    fn polar() {
+        let sun = Sun::new(69.6492, 18.9553);
+        assert_eq!(sun.day(date(2024, 6, 21)), SunDay::PolarDay);
+        assert_eq!(sun.day(date(2024, 12, 21)), SunDay::PolarNight);
+        assert_eq!(sun.day(date(2024, 12, 21)).sunset(), None);
+    }
+
+    #[test]
+    fn daylight_since_last_event() {
+        let sun = Sun::new(55.6761, 12.5683);
+
+        let (daylight, since) = sun.daylight(time("2024-06-21T12:00:00Z"));
+        assert!(daylight);
+        assert_near(since, "2024-06-21T02:25:00Z");
+
+        let (daylight, since) = sun.daylight(time("2024-06-21T23:00:00Z"));
+        assert!(!daylight);
+        assert_near(since, "2024-06-21T19:57:00Z");
+
+        // the last event does not change until the next sunrise
+        assert_eq!(sun.daylight(time("2024-06-22T01:00:00Z")), (false, since));
+    }
+
+    #[test]
+    fn daylight_across_utc_midnight() {
+        let sun = Sun::new(37.7749, -122.4194);
+
+        let (daylight, since) = sun.daylight(time("2024-06-22T02:00:00Z"));
+        assert!(daylight);
+        assert_near(since, "2024-06-21T12:48:00Z");
+    }
+
+    #[test]
+    fn daylight_polar() {
+        let sun = Sun::new(69.6492, 18.9553);
+        assert_eq!(sun.daylight(time("2024-06-21T12:00:00Z")), (true, None));
+        assert_eq!(sun.daylight(time("2024-12-21T12:00:00Z")), (false, None));
+    }
+}
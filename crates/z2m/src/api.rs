@@ -81,6 +81,9 @@ pub enum Message {
     #[serde(rename = "bridge/response/device/remove")]
     BridgeDeviceRemove(Response<DeviceRemoveResponse>),
 
+    #[serde(rename = "bridge/response/device/rename")]
+    BridgeDeviceRename(Response<DeviceRename>),
+
     #[serde(rename = "bridge/response/device/options")]
     BridgeDeviceOptions(Value),
 
@@ -90,6 +93,9 @@ pub enum Message {
     #[serde(rename = "bridge/response/device/ota_update/check")]
     BridgeDeviceOtaUpdateCheck(Value),
 
+    #[serde(rename = "bridge/response/device/ota_update/update")]
+    BridgeDeviceOtaUpdate(Response<DeviceOtaUpdate>),
+
     #[serde(rename = "bridge/health")]
     BridgeHealth(BridgeHealth),
 }
@@ -171,6 +177,11 @@ impl DeviceRead {
     }
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DeviceOtaUpdate {
+    pub id: String,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DeviceRemoveResponse {
     pub id: String,
@@ -193,10 +204,26 @@ pub enum Response<T> {
     },
 }
 
+impl<T> Response<T> {
+    /// The transaction id of the request this is a response to, if any
+    #[must_use]
+    pub fn transaction(&self) -> Option<&str> {
+        match self {
+            Self::Ok { transaction, .. } | Self::Error { transaction, .. } => {
+                transaction.as_ref().and_then(Value::as_str)
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GroupAdd {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub id: Option<u32>,
     pub friendly_name: String,
+    /// Returned by z2m in the response, to match it with the request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -217,6 +244,9 @@ pub struct GroupMemberAddRemove {
 pub struct GroupRename {
     pub from: String,
     pub to: String,
+    /// Returned by z2m in the response, to match it with the request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub transaction: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -233,13 +263,33 @@ pub struct DeviceRename {
     pub homeassistant_rename: bool,
 }
 
-#[derive(Serialize, Deserialize, Clone, Hash, Debug, Copy)]
+#[derive(Serialize, Deserialize, Clone, Hash, Debug, Copy, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum Availability {
     Online,
     Offline,
 }
 
+/// Payload of `<friendly_name>/availability`
+///
+/// This is a json object in z2m 2.x, but a plain string in z2m 1.x (unless
+/// `legacy_availability_payload` is disabled).
+#[derive(Serialize, Deserialize, Clone, Debug, Copy, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum AvailabilityPayload {
+    State { state: Availability },
+    Legacy(Availability),
+}
+
+impl AvailabilityPayload {
+    #[must_use]
+    pub const fn availability(&self) -> Availability {
+        match self {
+            Self::State { state } | Self::Legacy(state) => *state,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Hash)]
 #[serde(transparent)]
 pub struct IeeeAddress(#[serde(deserialize_with = "ieee_address")] u64);
@@ -612,6 +662,43 @@ impl Device {
         })
     }
 
+    #[must_use]
+    pub fn expose_named(&self, name: &str) -> Option<&Expose> {
+        self.exposes().iter().find(|exp| exp.name() == Some(name))
+    }
+
+    /// Name of the exposed illuminance property (in lux), if any.
+    ///
+    /// Before zigbee2mqtt 2.0, `illuminance` was the raw sensor value, and
+    /// `illuminance_lux` was the converted value. Newer versions only expose
+    /// `illuminance`, which is now in lux.
+    #[must_use]
+    pub fn expose_illuminance(&self) -> Option<&'static str> {
+        ["illuminance_lux", "illuminance"]
+            .into_iter()
+            .find(|name| self.expose_named(name).is_some())
+    }
+
+    #[must_use]
+    pub const fn battery_powered(&self) -> bool {
+        matches!(self.power_source, PowerSource::Battery)
+    }
+
+    #[must_use]
+    pub fn supports_ota(&self) -> bool {
+        self.definition.as_ref().is_some_and(|def| def.supports_ota)
+    }
+
+    /// True, if the device exposes any sensor supported as a hue sensor service
+    #[must_use]
+    pub fn expose_sensor(&self) -> bool {
+        self.expose_named("occupancy").is_some()
+            || self.expose_named("contact").is_some()
+            || self.expose_named("tamper").is_some()
+            || self.expose_named("temperature").is_some()
+            || self.expose_illuminance().is_some()
+    }
+
     #[must_use]
     pub fn expose_action(&self) -> bool {
         self.exposes().iter().any(|exp| {
@@ -622,6 +709,24 @@ impl Device {
             }
         })
     }
+
+    /// The possible values of the `action` expose, if any
+    #[must_use]
+    pub fn action_values(&self) -> Vec<&str> {
+        self.exposes()
+            .iter()
+            .filter_map(|exp| match exp {
+                Expose::Enum(ExposeEnum { base, values })
+                    if base.name.as_deref() == Some("action") =>
+                {
+                    Some(values)
+                }
+                _ => None,
+            })
+            .flatten()
+            .filter_map(Value::as_str)
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -833,7 +938,9 @@ pub struct DeviceEndpointClusters {
 
 #[cfg(test)]
 mod tests {
-    use super::IeeeAddress;
+    use serde_json::json;
+
+    use super::{Availability, AvailabilityPayload, GroupAdd, IeeeAddress, Response};
 
     #[test]
     fn ieee_adress_as_mac() {
@@ -841,4 +948,31 @@ mod tests {
 
         assert_eq!("13:4e:6d:22:9e:21:65:2a".to_string(), ieee_adress.as_mac());
     }
+
+    #[test]
+    fn availability_payload() {
+        let new: AvailabilityPayload = serde_json::from_value(json!({"state": "offline"})).unwrap();
+        let old: AvailabilityPayload = serde_json::from_value(json!("online")).unwrap();
+
+        assert_eq!(new.availability(), Availability::Offline);
+        assert_eq!(old.availability(), Availability::Online);
+    }
+
+    #[test]
+    fn response_transaction() {
+        let resp: Response<GroupAdd> = serde_json::from_value(json!({
+            "status": "error",
+            "error": "Group 'kitchen' already exists",
+            "transaction": "bifrost-3",
+        }))
+        .unwrap();
+        assert_eq!(resp.transaction(), Some("bifrost-3"));
+
+        let resp: Response<GroupAdd> = serde_json::from_value(json!({
+            "status": "ok",
+            "data": {"friendly_name": "kitchen", "id": 5},
+        }))
+        .unwrap();
+        assert_eq!(resp.transaction(), None);
+    }
 }
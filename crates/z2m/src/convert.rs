@@ -2,9 +2,10 @@ use std::collections::BTreeSet;
 use std::io::Cursor;
 
 use hue::api::{
-    ColorGamut, ColorTemperature, DeviceProductData, Dimming, DimmingDeltaAction, GamutType,
-    GroupedLightUpdate, LightColor, LightGradient, LightGradientMode, LightGradientPoint,
-    LightGradientUpdate, LightUpdate, MirekSchema,
+    ColorGamut, ColorTemperature, DeviceProductData, DeviceSoftwareUpdateState, Dimming,
+    DimmingDeltaAction, GamutType, GroupedLightUpdate, LightColor, LightGradient,
+    LightGradientMode, LightGradientPoint, LightGradientUpdate, LightUpdate, MirekSchema,
+    SceneAction, ZigbeeConnectivityStatus,
 };
 use hue::devicedb::product_data;
 use hue::error::HueError;
@@ -12,8 +13,8 @@ use hue::gradient::GradientProductData;
 use hue::xy::XY;
 use hue::zigbee::HueZigbeeUpdate;
 
-use crate::api::{Device, Expose, ExposeList, ExposeNumeric};
-use crate::update::{DeviceColorMode, DeviceUpdate};
+use crate::api::{Availability, Device, Expose, ExposeList, ExposeNumeric};
+use crate::update::{DeviceColorMode, DeviceOtaUpdateState, DeviceUpdate};
 
 pub trait ExtractExposeNumeric {
     fn extract_mirek_schema(&self) -> Option<MirekSchema>;
@@ -243,6 +244,42 @@ impl From<&DeviceUpdate> for LightUpdate {
     }
 }
 
+/// Light state for a z2m `scene_add` request. Gradients and effects can't be
+/// stored in a zigbee scene, so those are left out.
+impl From<&SceneAction> for DeviceUpdate {
+    fn from(action: &SceneAction) -> Self {
+        let upd = Self::default().with_state(action.on.map(|on| on.on));
+
+        if action.on.is_some_and(|on| !on.on) {
+            return upd;
+        }
+
+        upd.with_brightness(action.dimming.map(|dim| dim.brightness / 100.0 * 254.0))
+            .with_color_temp(action.color_temperature.and_then(|ct| ct.mirek))
+            .with_color_xy(action.color.map(|col| col.xy))
+    }
+}
+
+impl From<Availability> for ZigbeeConnectivityStatus {
+    fn from(value: Availability) -> Self {
+        match value {
+            Availability::Online => Self::Connected,
+            Availability::Offline => Self::ConnectivityIssue,
+        }
+    }
+}
+
+impl From<&DeviceOtaUpdateState> for DeviceSoftwareUpdateState {
+    fn from(value: &DeviceOtaUpdateState) -> Self {
+        match value {
+            DeviceOtaUpdateState::Idle | DeviceOtaUpdateState::Unknown(_) => Self::NoUpdate,
+            DeviceOtaUpdateState::Available => Self::ReadyToInstall,
+            DeviceOtaUpdateState::Scheduled => Self::UpdatePending,
+            DeviceOtaUpdateState::Updating => Self::Installing,
+        }
+    }
+}
+
 impl From<&GroupedLightUpdate> for DeviceUpdate {
     fn from(upd: &GroupedLightUpdate) -> Self {
         Self::default()
@@ -1,7 +1,10 @@
 use serde::Serialize;
 use serde_json::Value;
 
-use crate::api::{DeviceRead, DeviceRemove, GroupMemberChange, PermitJoin};
+use crate::api::{
+    DeviceOtaUpdate, DeviceRead, DeviceRemove, DeviceRename, GroupAdd, GroupMemberChange,
+    GroupRemove, GroupRename, PermitJoin,
+};
 use crate::update::DeviceUpdate;
 
 #[derive(Clone, Debug, Serialize)]
@@ -18,6 +21,15 @@ pub enum Z2mRequest<'a> {
         id: u32,
     },
 
+    SceneAdd {
+        name: &'a str,
+        #[serde(rename = "ID")]
+        id: u32,
+        group_id: u32,
+        #[serde(flatten)]
+        state: &'a DeviceUpdate,
+    },
+
     SceneRecall(u32),
 
     SceneRemove(u32),
@@ -33,6 +45,15 @@ pub enum Z2mRequest<'a> {
         payload: Z2mPayload,
     },
 
+    #[serde(untagged)]
+    GroupAdd(GroupAdd),
+
+    #[serde(untagged)]
+    GroupRemove(GroupRemove),
+
+    #[serde(untagged)]
+    GroupRename(GroupRename),
+
     #[serde(untagged)]
     GroupMemberAdd(GroupMemberChange),
 
@@ -45,6 +66,12 @@ pub enum Z2mRequest<'a> {
     #[serde(untagged)]
     DeviceRemove(DeviceRemove),
 
+    #[serde(untagged)]
+    DeviceRename(DeviceRename),
+
+    #[serde(untagged)]
+    DeviceOtaUpdate(DeviceOtaUpdate),
+
     #[serde(untagged)]
     Update(&'a DeviceUpdate),
 
@@ -58,3 +85,40 @@ pub enum Z2mRequest<'a> {
     #[serde(untagged)]
     Raw(Value),
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use crate::request::Z2mRequest;
+    use crate::update::DeviceUpdate;
+
+    #[test]
+    fn scene_add() {
+        let state = DeviceUpdate::default()
+            .with_state(Some(true))
+            .with_brightness(Some(127.0))
+            .with_color_temp(Some(300));
+
+        let req = Z2mRequest::SceneAdd {
+            name: "Reading",
+            id: 3,
+            group_id: 7,
+            state: &state,
+        };
+
+        assert_eq!(
+            serde_json::to_value(&req).unwrap(),
+            json!({
+                "scene_add": {
+                    "name": "Reading",
+                    "ID": 3,
+                    "group_id": 7,
+                    "state": "ON",
+                    "brightness": 127.0,
+                    "color_temp": 300,
+                }
+            })
+        );
+    }
+}
@@ -3,7 +3,7 @@ use std::collections::HashMap;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-use hue::api::{LightGradientUpdate, On};
+use hue::api::{DeviceSoftwareUpdateState, LightGradientUpdate, On};
 use hue::xy::XY;
 
 use crate::hexcolor::HexColor;
@@ -39,13 +39,24 @@ pub struct DeviceUpdate {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub power_on_behavior: Option<PowerOnBehavior>,
-    #[serde(skip_serializing_if = "HashMap::is_empty")]
-    #[serde(default)]
-    pub update: HashMap<String, Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub update: Option<DeviceOtaUpdate>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub update_available: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub battery: Option<Value>,
+    pub battery: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub occupancy: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub contact: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tamper: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub illuminance: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub illuminance_lux: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub transition: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -139,6 +150,23 @@ impl DeviceUpdate {
     pub fn with_transition(self, transition: Option<f64>) -> Self {
         Self { transition, ..self }
     }
+
+    /// Firmware update state, if reported. Older versions of z2m only report
+    /// the `update_available` flag.
+    #[must_use]
+    pub fn software_update_state(&self) -> Option<DeviceSoftwareUpdateState> {
+        if let Some(state) = self.update.as_ref().and_then(|ota| ota.state.as_ref()) {
+            return Some(state.into());
+        }
+
+        self.update_available.map(|available| {
+            if available {
+                DeviceSoftwareUpdateState::ReadyToInstall
+            } else {
+                DeviceSoftwareUpdateState::NoUpdate
+            }
+        })
+    }
 }
 
 #[derive(Copy, Debug, Serialize, Deserialize, Clone)]
@@ -270,3 +298,72 @@ pub enum DeviceEffect {
     #[serde(untagged)]
     Unknown(String),
 }
+
+/// OTA firmware update status of a device
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DeviceOtaUpdate {
+    /// Not reported (or `null`) for devices that have never been checked
+    #[serde(default)]
+    pub state: Option<DeviceOtaUpdateState>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub progress: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remaining: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeviceOtaUpdateState {
+    Idle,
+    Available,
+    Scheduled,
+    Updating,
+    #[serde(untagged)]
+    Unknown(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use hue::api::DeviceSoftwareUpdateState;
+
+    use super::{DeviceOtaUpdateState, DeviceUpdate};
+
+    #[test]
+    fn ota_update_state() {
+        let upd: DeviceUpdate = serde_json::from_value(json!({
+            "update": {"installed_version": 1, "latest_version": 2, "state": "available"},
+        }))
+        .unwrap();
+
+        assert_eq!(
+            upd.update.unwrap().state,
+            Some(DeviceOtaUpdateState::Available)
+        );
+    }
+
+    #[test]
+    fn ota_update_state_missing() {
+        let null: DeviceUpdate = serde_json::from_value(json!({
+            "update": {"installed_version": -1, "latest_version": -1, "state": null},
+        }))
+        .unwrap();
+
+        let omitted: DeviceUpdate = serde_json::from_value(json!({
+            "update": {"installed_version": -1, "latest_version": -1},
+            "update_available": false,
+        }))
+        .unwrap();
+
+        assert_eq!(null.update.as_ref().unwrap().state, None);
+        assert_eq!(null.software_update_state(), None);
+
+        // falls back to the legacy flag
+        assert_eq!(omitted.update.as_ref().unwrap().state, None);
+        assert_eq!(
+            omitted.software_update_state(),
+            Some(DeviceSoftwareUpdateState::NoUpdate)
+        );
+    }
+}
@@ -4,22 +4,25 @@ use std::sync::Arc;
 use hue::clamp::Clamp;
 use hue::effect_duration::EffectDuration;
 use hue::zigbee::{GradientParams, GradientStyle, HueZigbeeUpdate, LightRecordMode};
+use maplit::btreeset;
 use tokio::time::sleep;
 use uuid::Uuid;
 
 use bifrost_api::backend::BackendRequest;
 use hue::api::{
-    BridgeHome, ColorTemperatureUpdate, DimmingDeltaAction, Entertainment,
+    BridgeHome, ColorTemperatureUpdate, DeviceSoftwareUpdate, DimmingDeltaAction, Entertainment,
     EntertainmentConfiguration, GroupedLight, GroupedLightUpdate, Light, LightEffectsV2Update,
-    LightUpdate, RType, Resource, ResourceLink, Room, RoomUpdate, Scene, SceneActive, SceneStatus,
-    SceneStatusEnum, SceneUpdate, ZigbeeDeviceDiscoveryUpdate,
+    LightUpdate, RType, Resource, ResourceLink, Room, RoomUpdate, Scene, SceneAction, SceneActive,
+    SceneRecall, SceneStatus, SceneStatusEnum, SceneUpdate, ZigbeeDeviceDiscoveryUpdate, Zone,
+    ZoneUpdate,
 };
 use hue::error::HueError;
 use hue::stream::HueStreamLightsV2;
-use z2m::api::DeviceRead;
+use z2m::api::{DeviceRead, GroupRename};
 use z2m::update::{DeviceEffect, DeviceUpdate};
 
 use crate::backend::z2m::Z2mBackend;
+use crate::backend::z2m::dynamic_scene::DynamicScene;
 use crate::backend::z2m::entertainment::EntStream;
 use crate::backend::z2m::websocket::Z2mWebSocket;
 use crate::error::ApiResult;
@@ -89,6 +92,33 @@ impl Z2mBackend {
         Ok(hz)
     }
 
+    async fn backend_device_update(
+        &self,
+        z2mws: &mut Z2mWebSocket,
+        link: &ResourceLink,
+        upd: &hue::api::DeviceUpdate,
+    ) -> ApiResult<()> {
+        if !self.server.rename_devices.unwrap_or_default() {
+            return Ok(());
+        }
+
+        let Some(name) = upd.metadata.as_ref().and_then(|md| md.name.as_ref()) else {
+            return Ok(());
+        };
+
+        let Some(topic) = self.rmap.get(link) else {
+            return Ok(());
+        };
+
+        if topic == name {
+            return Ok(());
+        }
+
+        log::info!("[{}] Requesting z2m rename {topic} -> {name}", self.name);
+
+        z2mws.send_device_rename(topic, name).await
+    }
+
     async fn backend_light_update(
         &self,
         z2mws: &mut Z2mWebSocket,
@@ -210,8 +240,8 @@ impl Z2mBackend {
 
         lock.aux_set(link_scene, auxdata);
 
-        z2mws
-            .send_scene_store(topic, &scene.metadata.name, sid)
+        let group_id = lock.aux_get(&scene.group).ok().and_then(|aux| aux.index);
+        self.send_scene_program(z2mws, topic, group_id, scene, sid)
             .await?;
 
         lock.add(link_scene, Resource::Scene(scene.clone()))?;
@@ -236,35 +266,69 @@ impl Z2mBackend {
             .ok_or(HueError::NotFound(link.rid))?;
 
         if let Some(recall) = &upd.recall {
-            if recall.action == Some(SceneStatusEnum::Active) {
-                let scenes = lock.get_scenes_for_room(&scene.group.rid);
-                for rid in scenes {
-                    lock.update::<Scene>(&rid, |scn| {
-                        scn.status = Some(SceneStatus {
-                            active: if rid == link.rid {
-                                SceneActive::Static
-                            } else {
-                                SceneActive::Inactive
-                            },
-                            last_recall: None,
-                        });
-                    })?;
+            let active = match recall.action {
+                Some(SceneStatusEnum::Active | SceneStatusEnum::Static) => SceneActive::Static,
+                Some(SceneStatusEnum::DynamicPalette) => SceneActive::DynamicPalette,
+                None => {
+                    log::error!("Scene recall type not supported: {recall:?}");
+                    return Ok(());
                 }
+            };
 
-                let room = lock.get::<Scene>(link)?.group;
-                drop(lock);
+            let room = scene.group;
+            drop(lock);
 
-                if let Some(topic) = self.rmap.get(&room).cloned() {
-                    log::info!("[{}] Recall scene: {link:?}", self.name);
+            // recalling any scene takes over from dynamic playback in the same room
+            self.stop_dynamic_scenes(|ds| ds.group == room).await?;
 
-                    let mut lock = self.state.lock().await;
-                    self.learner.learn_scene_recall(link, &mut lock)?;
+            let mut lock = self.state.lock().await;
+            let scenes = lock.get_scenes_for_room(&room.rid);
+            for rid in scenes {
+                lock.update::<Scene>(&rid, |scn| {
+                    scn.status = Some(SceneStatus {
+                        active: if rid == link.rid {
+                            active
+                        } else {
+                            SceneActive::Inactive
+                        },
+                        last_recall: None,
+                    });
+                })?;
+            }
 
+            if let Some(topic) = self.rmap.get(&room).cloned() {
+                log::info!("[{}] Recall scene: {link:?}", self.name);
+
+                self.learner.learn_scene_recall(link, &mut lock)?;
+
+                let scene = lock.get::<Scene>(link)?;
+                if (recall.duration.is_some() || recall.dimming.is_some())
+                    && !scene.actions.is_empty()
+                {
+                    // z2m scene recalls take no parameters, so apply the
+                    // scene one light at a time instead
+                    self.send_scene_actions(z2mws, scene, recall).await?;
+                } else {
                     z2mws.send_scene_recall(&topic, index).await?;
                 }
-            } else {
-                log::error!("Scene recall type not supported: {recall:?}");
+
+                if active == SceneActive::DynamicPalette {
+                    let scene = lock.get::<Scene>(link)?;
+                    let lights = scene
+                        .dynamic_lights()
+                        .into_iter()
+                        .filter_map(|light| Some((light, self.rmap.get(&light)?.clone())))
+                        .collect();
+
+                    if let Some(ds) = DynamicScene::start(scene, lights, self.message_tx.clone()) {
+                        log::info!("[{}] Starting dynamic playback of {link:?}", self.name);
+                        self.dynamic_scenes.insert(*link, ds);
+                    } else {
+                        log::warn!("[{}] Scene {link:?} has no palette to play", self.name);
+                    }
+                }
             }
+            drop(lock);
         } else {
             // We're not recalling the scene, so we are updating the scene
             let room = lock.get::<Scene>(link)?.group;
@@ -272,16 +336,16 @@ impl Z2mBackend {
             if let Some(topic) = self.rmap.get(&room).cloned() {
                 log::info!("[{}] Store scene: {link:?}", self.name);
 
-                let scene = lock.get::<Scene>(link)?;
-                z2mws
-                    .send_scene_store(&topic, &scene.metadata.name, index)
+                let mut scene = lock.get::<Scene>(link)?.clone();
+                scene += upd;
+
+                let group_id = lock.aux_get(&room).ok().and_then(|aux| aux.index);
+                self.send_scene_program(z2mws, &topic, group_id, &scene, index)
                     .await?;
 
                 // We have requested z2m to update the scene, so update
                 // the state database accordingly
-                lock.update::<Scene>(&link.rid, |scene| {
-                    *scene += upd;
-                })?;
+                lock.update::<Scene>(&link.rid, |scn| *scn = scene)?;
 
                 drop(lock);
             }
@@ -290,6 +354,118 @@ impl Z2mBackend {
         Ok(())
     }
 
+    /// Program a scene in z2m from its actions, by adding the scene to each
+    /// target light with the state from the matching action. Unlike
+    /// `scene_store`, this does not depend on what the lights are doing right
+    /// now, so lights that are off can still be part of the scene.
+    ///
+    /// Scenes without actions (or groups without a known z2m group id) fall
+    /// back to storing the current state of the lights.
+    async fn send_scene_program(
+        &self,
+        z2mws: &mut Z2mWebSocket,
+        topic: &str,
+        group_id: Option<u32>,
+        scene: &Scene,
+        index: u32,
+    ) -> ApiResult<()> {
+        let name = &scene.metadata.name;
+
+        let Some(group_id) = group_id.filter(|_| !scene.actions.is_empty()) else {
+            return z2mws.send_scene_store(topic, name, index).await;
+        };
+
+        // start from a clean slate, so lights that are no longer part of
+        // the scene are removed from it
+        z2mws.send_scene_remove(topic, index).await?;
+
+        for sae in &scene.actions {
+            let Some(light_topic) = self.rmap.get(&sae.target) else {
+                log::warn!(
+                    "[{}] Scene target {:?} is not known by z2m, skipping",
+                    self.name,
+                    sae.target
+                );
+                continue;
+            };
+
+            let state = DeviceUpdate::from(&sae.action);
+            z2mws
+                .send_scene_add(light_topic, name, index, group_id, &state)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Update for a single scene action, using the transition `duration` and
+    /// brightness override from `recall`
+    fn make_scene_action_update(action: &SceneAction, recall: &SceneRecall) -> DeviceUpdate {
+        let transition = recall.duration.map(|ms| f64::from(ms) / 1000.0);
+        let brightness = recall.dimming.map(|dim| dim.brightness / 100.0 * 254.0);
+
+        let upd = DeviceUpdate::from(action).with_transition(transition);
+        if action.on.is_none_or(|on| on.on) && brightness.is_some() {
+            upd.with_brightness(brightness)
+        } else {
+            upd
+        }
+    }
+
+    /// Apply the actions of a scene directly to each light
+    async fn send_scene_actions(
+        &self,
+        z2mws: &mut Z2mWebSocket,
+        scene: &Scene,
+        recall: &SceneRecall,
+    ) -> ApiResult<()> {
+        for sae in &scene.actions {
+            let Some(topic) = self.rmap.get(&sae.target) else {
+                continue;
+            };
+
+            let upd = Self::make_scene_action_update(&sae.action, recall);
+            z2mws.send_update(topic, &upd).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Stop dynamic playback of all scenes matching `pred`, and mark them inactive
+    async fn stop_dynamic_scenes(&mut self, pred: impl Fn(&DynamicScene) -> bool) -> ApiResult<()> {
+        let stopped: Vec<ResourceLink> = self
+            .dynamic_scenes
+            .iter()
+            .filter(|(_, ds)| pred(ds))
+            .map(|(link, _)| *link)
+            .collect();
+
+        if stopped.is_empty() {
+            return Ok(());
+        }
+
+        let mut lock = self.state.lock().await;
+        for link in stopped {
+            log::info!("[{}] Stopping dynamic playback of {link:?}", self.name);
+            self.dynamic_scenes.remove(&link);
+
+            lock.update::<Scene>(&link.rid, |scn| {
+                if scn
+                    .status
+                    .is_some_and(|st| st.active == SceneActive::DynamicPalette)
+                {
+                    scn.status = Some(SceneStatus {
+                        active: SceneActive::Inactive,
+                        last_recall: None,
+                    });
+                }
+            })?;
+        }
+        drop(lock);
+
+        Ok(())
+    }
+
     async fn backend_grouped_light_update(
         &self,
         z2mws: &mut Z2mWebSocket,
@@ -331,46 +507,284 @@ impl Z2mBackend {
         Ok(())
     }
 
-    async fn backend_room_update(
+    /// Add and remove z2m group members, to go from the `existing` to the `new`
+    /// set of children. Children unknown to this backend are ignored.
+    async fn sync_group_members(
+        &self,
+        z2mws: &mut Z2mWebSocket,
+        topic: &str,
+        existing: &BTreeSet<ResourceLink>,
+        new: &BTreeSet<ResourceLink>,
+    ) -> ApiResult<()> {
+        let known_existing: BTreeSet<_> = existing
+            .iter()
+            .filter(|link| self.rmap.contains_key(link))
+            .collect();
+
+        let known_new: BTreeSet<_> = new
+            .iter()
+            .filter(|link| self.rmap.contains_key(link))
+            .collect();
+
+        for add in known_new.difference(&known_existing) {
+            let friendly_name = &self.rmap[add];
+            z2mws.send_group_member_add(topic, friendly_name).await?;
+        }
+
+        for remove in known_existing.difference(&known_new) {
+            let friendly_name = &self.rmap[remove];
+            z2mws.send_group_member_remove(topic, friendly_name).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Remove `children` from any room other than `link`, since a device
+    /// can only be in one room at a time. The rooms are updated when z2m
+    /// confirms the group membership changes.
+    async fn leave_other_rooms(
         &self,
         z2mws: &mut Z2mWebSocket,
         link: &ResourceLink,
-        upd: &RoomUpdate,
+        children: &BTreeSet<ResourceLink>,
     ) -> ApiResult<()> {
         let lock = self.state.lock().await;
+        let rooms: Vec<(ResourceLink, BTreeSet<ResourceLink>)> = lock
+            .get_resource_ids_by_type(RType::Room)
+            .into_iter()
+            .map(|rid| RType::Room.link_to(rid))
+            .filter(|room| room != link)
+            .filter_map(|room| Some((room, lock.get::<Room>(&room).ok()?.children.clone())))
+            .collect();
+        drop(lock);
 
-        if let Some(children) = &upd.children {
-            if let Some(topic) = self.rmap.get(link) {
-                let room = lock.get::<Room>(link)?.clone();
-                drop(lock);
+        for (room, existing) in rooms {
+            let Some(topic) = self.rmap.get(&room) else {
+                continue;
+            };
 
-                let known_existing: BTreeSet<_> = room
-                    .children
-                    .iter()
-                    .filter(|device| self.rmap.contains_key(device))
-                    .collect();
+            if existing.is_disjoint(children) {
+                continue;
+            }
 
-                let known_new: BTreeSet<_> = children
-                    .iter()
-                    .filter(|device| self.rmap.contains_key(device))
-                    .collect();
+            let remaining = existing.difference(children).copied().collect();
+            self.sync_group_members(z2mws, topic, &existing, &remaining)
+                .await?;
+        }
 
-                for add in known_new.difference(&known_existing) {
-                    let friendly_name = &self.rmap[add];
-                    z2mws.send_group_member_add(topic, friendly_name).await?;
-                }
+        Ok(())
+    }
 
-                for remove in known_existing.difference(&known_new) {
-                    let friendly_name = &self.rmap[remove];
-                    z2mws.send_group_member_remove(topic, friendly_name).await?;
-                }
-            }
+    async fn backend_room_update(
+        &mut self,
+        z2mws: &mut Z2mWebSocket,
+        link: &ResourceLink,
+        upd: &RoomUpdate,
+    ) -> ApiResult<()> {
+        if let (Some(children), Some(topic)) = (&upd.children, self.rmap.get(link)) {
+            self.leave_other_rooms(z2mws, link, children).await?;
+
+            let room = self.state.lock().await.get::<Room>(link)?.clone();
+
+            self.sync_group_members(z2mws, topic, &room.children, children)
+                .await?;
+        }
+
+        if let Some(name) = upd.metadata.as_ref().and_then(|md| md.name.as_ref()) {
+            self.backend_group_rename(z2mws, link, name).await?;
         }
 
         Ok(())
     }
 
-    async fn backend_delete(&self, z2mws: &mut Z2mWebSocket, link: &ResourceLink) -> ApiResult<()> {
+    /// Pick the z2m group name for a new room or zone, if it should be created
+    /// by this backend.
+    ///
+    /// With multiple z2m servers, the group is created by the backend that
+    /// knows the first child of the room or zone.
+    fn new_group_topic(&self, name: &str, children: &BTreeSet<ResourceLink>) -> Option<String> {
+        let Some(first) = children.first() else {
+            log::warn!("[{}] Refusing to create empty group {name:?}", self.name);
+            return None;
+        };
+
+        if !self.rmap.contains_key(first) {
+            return None;
+        }
+
+        let prefix = self.server.group_prefix.as_deref().unwrap_or_default();
+        Some(format!("{prefix}{name}"))
+    }
+
+    /// Request a new z2m group, and add the room or zone (`obj`) for it to the
+    /// state. The group is confirmed (or rolled back) when z2m responds.
+    async fn backend_group_create(
+        &mut self,
+        z2mws: &mut Z2mWebSocket,
+        link: &ResourceLink,
+        link_glight: ResourceLink,
+        topic: &str,
+        children: &BTreeSet<ResourceLink>,
+        obj: Resource,
+    ) -> ApiResult<()> {
+        log::info!("[{}] Requesting z2m group {topic} for {link:?}", self.name);
+
+        let transaction = self.next_transaction();
+        z2mws.send_group_add(topic, &transaction).await?;
+        self.pending_group_add
+            .insert(transaction, (topic.to_string(), *link));
+
+        self.map.insert(topic.to_string(), link_glight);
+        self.rmap.insert(link_glight, topic.to_string());
+        self.rmap.insert(*link, topic.to_string());
+
+        // Children are added when z2m confirms the group membership changes
+        self.sync_group_members(z2mws, topic, &BTreeSet::new(), children)
+            .await?;
+
+        let mut lock = self.state.lock().await;
+        lock.aux_set(link, AuxData::new().with_topic(topic).with_created());
+        lock.add(link, obj)?;
+        lock.add(
+            &link_glight,
+            Resource::GroupedLight(GroupedLight::new(*link)),
+        )?;
+        drop(lock);
+
+        Ok(())
+    }
+
+    /// Request renaming the z2m group of a room or zone. The new name is
+    /// recorded right away, so the group is recognized when z2m publishes
+    /// the updated group list.
+    async fn backend_group_rename(
+        &mut self,
+        z2mws: &mut Z2mWebSocket,
+        link: &ResourceLink,
+        name: &str,
+    ) -> ApiResult<()> {
+        let Some(from) = self.rmap.get(link).cloned() else {
+            return Ok(());
+        };
+
+        let prefix = self.server.group_prefix.as_deref().unwrap_or_default();
+        let to = format!("{prefix}{name}");
+
+        if from == to {
+            return Ok(());
+        }
+
+        log::info!("[{}] Requesting z2m group rename {from} -> {to}", self.name);
+
+        let transaction = self.next_transaction();
+        z2mws.send_group_rename(&from, &to, &transaction).await?;
+
+        let mut lock = self.state.lock().await;
+        let aux = lock.aux_get(link).cloned().unwrap_or_default();
+        lock.aux_set(link, aux.with_topic(&to));
+        drop(lock);
+
+        self.pending_group_rename.insert(
+            transaction.clone(),
+            (
+                *link,
+                GroupRename {
+                    from,
+                    to,
+                    transaction: Some(transaction),
+                },
+            ),
+        );
+
+        Ok(())
+    }
+
+    async fn backend_room_create(
+        &mut self,
+        z2mws: &mut Z2mWebSocket,
+        link_room: &ResourceLink,
+        room: &Room,
+    ) -> ApiResult<()> {
+        let Some(topic) = self.new_group_topic(&room.metadata.name, &room.children) else {
+            return Ok(());
+        };
+
+        self.leave_other_rooms(z2mws, link_room, &room.children)
+            .await?;
+
+        let link_glight = RType::GroupedLight.deterministic(link_room.rid);
+
+        let new_room = Room {
+            children: BTreeSet::new(),
+            metadata: room.metadata.clone(),
+            services: btreeset![link_glight],
+        };
+
+        self.backend_group_create(
+            z2mws,
+            link_room,
+            link_glight,
+            &topic,
+            &room.children,
+            Resource::Room(new_room),
+        )
+        .await
+    }
+
+    async fn backend_zone_create(
+        &mut self,
+        z2mws: &mut Z2mWebSocket,
+        link_zone: &ResourceLink,
+        zone: &Zone,
+    ) -> ApiResult<()> {
+        let Some(topic) = self.new_group_topic(&zone.metadata.name, &zone.children) else {
+            return Ok(());
+        };
+
+        let link_glight = RType::GroupedLight.deterministic(link_zone.rid);
+
+        let new_zone = Zone {
+            children: BTreeSet::new(),
+            metadata: zone.metadata.clone(),
+            services: btreeset![link_glight],
+        };
+
+        self.backend_group_create(
+            z2mws,
+            link_zone,
+            link_glight,
+            &topic,
+            &zone.children,
+            Resource::Zone(new_zone),
+        )
+        .await
+    }
+
+    async fn backend_zone_update(
+        &mut self,
+        z2mws: &mut Z2mWebSocket,
+        link: &ResourceLink,
+        upd: &ZoneUpdate,
+    ) -> ApiResult<()> {
+        if let (Some(children), Some(topic)) = (&upd.children, self.rmap.get(link)) {
+            let zone = self.state.lock().await.get::<Zone>(link)?.clone();
+
+            self.sync_group_members(z2mws, topic, &zone.children, children)
+                .await?;
+        }
+
+        if let Some(name) = upd.metadata.as_ref().and_then(|md| md.name.as_ref()) {
+            self.backend_group_rename(z2mws, link, name).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn backend_delete(
+        &mut self,
+        z2mws: &mut Z2mWebSocket,
+        link: &ResourceLink,
+    ) -> ApiResult<()> {
         match link.rtype {
             RType::Scene => {
                 let lock = self.state.lock().await;
@@ -386,6 +800,29 @@ impl Z2mBackend {
                 }
             }
 
+            RType::Room | RType::Zone => {
+                if let Some(topic) = self.rmap.get(link).cloned() {
+                    let mut lock = self.state.lock().await;
+                    let created = lock.aux_get(link).is_ok_and(|aux| aux.created);
+
+                    if created {
+                        log::info!("[{}] Requesting z2m removal of group {topic}", self.name);
+                        z2mws.send_group_remove(&topic).await?;
+                    } else {
+                        // groups imported from z2m are only unlinked, and
+                        // not imported again until the next restart
+                        log::info!("[{}] Unlinking z2m group {topic}", self.name);
+                        self.ignore.insert(topic.clone());
+                    }
+
+                    self.map.remove(&topic);
+                    self.rmap.retain(|_, v| *v != topic);
+
+                    lock.delete(link)?;
+                    drop(lock);
+                }
+            }
+
             RType::Device => {
                 if let Some(dev) = self
                     .rmap
@@ -532,6 +969,35 @@ impl Z2mBackend {
         Ok(())
     }
 
+    async fn backend_device_software_update(
+        &self,
+        z2mws: &mut Z2mWebSocket,
+        link: &ResourceLink,
+    ) -> ApiResult<()> {
+        let owner = self
+            .state
+            .lock()
+            .await
+            .get::<DeviceSoftwareUpdate>(link)?
+            .owner;
+
+        let Some(dev) = self
+            .network
+            .values()
+            .find(|dev| RType::Device.deterministic(&dev.ieee_address) == owner)
+        else {
+            return Ok(());
+        };
+
+        log::info!(
+            "[{}] Requesting z2m firmware update of {}",
+            self.name,
+            dev.friendly_name
+        );
+
+        z2mws.send_device_ota_update(&dev.friendly_name).await
+    }
+
     async fn backend_zigbee_device_discovery(
         &self,
         z2mws: &mut Z2mWebSocket,
@@ -549,7 +1015,18 @@ impl Z2mBackend {
         self.learner.cleanup();
 
         match &*req {
+            BackendRequest::DeviceUpdate(link, upd) => {
+                self.backend_device_update(z2mws, link, upd).await
+            }
+
+            BackendRequest::DeviceSoftwareUpdate(link, _upd) => {
+                self.backend_device_software_update(z2mws, link).await
+            }
+
             BackendRequest::LightUpdate(link, upd) => {
+                // manual light changes take over from dynamic scene playback
+                self.stop_dynamic_scenes(|ds| ds.lights.contains(link))
+                    .await?;
                 self.backend_light_update(z2mws, link, upd).await
             }
 
@@ -562,6 +1039,11 @@ impl Z2mBackend {
             }
 
             BackendRequest::GroupedLightUpdate(link, upd) => {
+                let owner = self.state.lock().await.get::<GroupedLight>(link)?.owner;
+                self.stop_dynamic_scenes(|ds| {
+                    owner.rtype == RType::BridgeHome || ds.group == owner
+                })
+                .await?;
                 self.backend_grouped_light_update(z2mws, link, upd).await
             }
 
@@ -569,7 +1051,22 @@ impl Z2mBackend {
                 self.backend_room_update(z2mws, link, upd).await
             }
 
-            BackendRequest::Delete(link) => self.backend_delete(z2mws, link).await,
+            BackendRequest::RoomCreate(link, room) => {
+                self.backend_room_create(z2mws, link, room).await
+            }
+
+            BackendRequest::ZoneCreate(link, zone) => {
+                self.backend_zone_create(z2mws, link, zone).await
+            }
+
+            BackendRequest::ZoneUpdate(link, upd) => {
+                self.backend_zone_update(z2mws, link, upd).await
+            }
+
+            BackendRequest::Delete(link) => {
+                self.dynamic_scenes.remove(link);
+                self.backend_delete(z2mws, link).await
+            }
 
             BackendRequest::EntertainmentStart(ent_id) => {
                 self.backend_entertainment_start(z2mws, ent_id).await
@@ -588,3 +1085,43 @@ impl Z2mBackend {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use hue::api::{SceneAction, SceneRecall};
+
+    use crate::backend::z2m::Z2mBackend;
+
+    fn update(action: serde_json::Value, recall: serde_json::Value) -> (Option<f64>, Option<f64>) {
+        let action: SceneAction = serde_json::from_value(action).unwrap();
+        let recall: SceneRecall = serde_json::from_value(recall).unwrap();
+        let upd = Z2mBackend::make_scene_action_update(&action, &recall);
+        (upd.brightness, upd.transition)
+    }
+
+    #[test]
+    fn scene_action_update_plain() {
+        let action = json!({"on": {"on": true}, "dimming": {"brightness": 50.0}});
+        assert_eq!(update(action, json!({})), (Some(127.0), None));
+    }
+
+    #[test]
+    fn scene_action_update_brightness_override() {
+        let action = json!({"on": {"on": true}, "dimming": {"brightness": 50.0}});
+        let recall = json!({"dimming": {"brightness": 100.0}});
+        assert_eq!(update(action, recall.clone()), (Some(254.0), None));
+
+        // lights turned off by the scene stay off
+        let action = json!({"on": {"on": false}});
+        assert_eq!(update(action, recall), (None, None));
+    }
+
+    #[test]
+    fn scene_action_update_duration() {
+        let action = json!({"on": {"on": true}});
+        let recall = json!({"duration": 2500});
+        assert_eq!(update(action, recall), (None, Some(2.5)));
+    }
+}
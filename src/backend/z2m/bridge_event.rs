@@ -7,15 +7,18 @@ use tokio_tungstenite::tungstenite;
 use uuid::Uuid;
 
 use hue::api::{
-    Device, DimmingUpdate, GroupedLight, Light, LightUpdate, RType, Resource, ResourceLink, Room,
+    Contact, Device, DevicePower, DeviceSoftwareUpdate, DeviceSoftwareUpdateState, DimmingUpdate,
+    GroupedLight, Light, LightLevel, LightUpdate, Motion, RType, RelativeRotary, Resource,
+    ResourceLink, Room, Tamper, Temperature, ZigbeeConnectivity, ZigbeeConnectivityStatus, Zone,
 };
 use z2m::api::{
-    BridgeDevices, DeviceRemoveResponse, GroupMemberChange, Message, RawMessage, Response,
+    Availability, AvailabilityPayload, BridgeDevices, DeviceRemoveResponse, DeviceRename, GroupAdd,
+    GroupMemberChange, GroupRename, Message, RawMessage, Response,
 };
 use z2m::update::DeviceUpdate;
 
 use crate::backend::z2m::Z2mBackend;
-use crate::backend::z2m::button::Z2mButtonHandler;
+use crate::backend::z2m::button::{Z2mButtonData, Z2mButtonHandler};
 use crate::error::{ApiError, ApiResult};
 
 impl Z2mBackend {
@@ -47,6 +50,83 @@ impl Z2mBackend {
         })
     }
 
+    async fn handle_update_device(&self, uuid: &Uuid, upd: &DeviceUpdate) -> ApiResult<()> {
+        let mut res = self.state.lock().await;
+        let services = res.get_id::<Device>(*uuid)?.services.clone();
+
+        for link in &services {
+            match link.rtype {
+                RType::Motion => {
+                    if let Some(occupancy) = upd.occupancy {
+                        res.update(&link.rid, |motion: &mut Motion| motion.report(occupancy))?;
+                    }
+                }
+                RType::Contact => {
+                    if let Some(contact) = upd.contact {
+                        res.update(&link.rid, |c: &mut Contact| c.report(contact))?;
+                    }
+                }
+                RType::Tamper => {
+                    if let Some(tamper) = upd.tamper {
+                        res.update(&link.rid, |t: &mut Tamper| t.report(tamper))?;
+                    }
+                }
+                RType::LightLevel => {
+                    if let Some(lux) = upd.illuminance_lux.or(upd.illuminance) {
+                        res.update(&link.rid, |ll: &mut LightLevel| ll.report(lux))?;
+                    }
+                }
+                RType::Temperature => {
+                    if let Some(temp) = upd.temperature {
+                        res.update(&link.rid, |t: &mut Temperature| t.report(temp))?;
+                    }
+                }
+                RType::DevicePower => {
+                    if let Some(battery) = upd.battery {
+                        res.update(&link.rid, |dp: &mut DevicePower| dp.report_battery(battery))?;
+                    }
+                }
+                RType::RelativeRotary => {
+                    let rotation = self
+                        .rotaries
+                        .get(&RType::Device.link_to(*uuid))
+                        .and_then(|rotary| rotary.rotation(upd));
+
+                    if let Some(rotation) = rotation {
+                        res.update(&link.rid, |rr: &mut RelativeRotary| rr.report(rotation))?;
+                    }
+                }
+                _ => {}
+            }
+        }
+        drop(res);
+
+        Ok(())
+    }
+
+    async fn handle_update_software(
+        &self,
+        device: &ResourceLink,
+        state: DeviceSoftwareUpdateState,
+    ) -> ApiResult<()> {
+        let mut res = self.state.lock().await;
+
+        let Some(link) = res
+            .get::<Device>(device)?
+            .service(RType::DeviceSoftwareUpdate)
+            .copied()
+        else {
+            return Ok(());
+        };
+
+        res.update(&link.rid, |dsu: &mut DeviceSoftwareUpdate| {
+            dsu.state = state;
+        })?;
+        drop(res);
+
+        Ok(())
+    }
+
     async fn handle_update(&mut self, rid: &Uuid, payload: &Value) -> ApiResult<()> {
         if let Value::String(string) = payload {
             if string.is_empty() {
@@ -59,6 +139,21 @@ impl Z2mBackend {
         log::trace!("Device update {upd:#?}");
 
         let obj = self.state.lock().await.get_resource_by_id(rid)?.obj;
+
+        if let Some(state) = upd.software_update_state() {
+            let device = match &obj {
+                Resource::Light(light) => Some(light.owner),
+                Resource::Device(_) => Some(RType::Device.link_to(*rid)),
+                _ => None,
+            };
+
+            if let Some(device) = device
+                && let Err(e) = self.handle_update_software(&device, state).await
+            {
+                log::error!("FAIL: {e:?} in {upd:?}");
+            }
+        }
+
         match obj {
             Resource::Light(_) => {
                 if let Err(e) = self.handle_update_light(rid, &upd).await {
@@ -70,6 +165,11 @@ impl Z2mBackend {
                     log::error!("FAIL: {e:?} in {upd:?}");
                 }
             }
+            Resource::Device(_) => {
+                if let Err(e) = self.handle_update_device(rid, &upd).await {
+                    log::error!("FAIL: {e:?} in {upd:?}");
+                }
+            }
             _ => {}
         }
 
@@ -77,8 +177,32 @@ impl Z2mBackend {
     }
 
     async fn handle_device_message(&mut self, msg: RawMessage) -> ApiResult<()> {
-        if msg.topic.ends_with("/availability") {
+        if let Some(device_topic) = msg.topic.strip_suffix("/availability") {
             // availability: https://www.zigbee2mqtt.io/guide/usage/mqtt_topics_and_messages.html#zigbee2mqtt-friendly-name-availability
+            let Some(link) = self.map.get(device_topic).copied() else {
+                return Ok(());
+            };
+
+            let payload = match AvailabilityPayload::deserialize(&msg.payload) {
+                Ok(payload) => payload,
+                Err(err) => {
+                    log::error!(
+                        "Cannot parse availability: {err}\n{}",
+                        serde_json::to_string_pretty(&msg.payload)?
+                    );
+                    return Ok(());
+                }
+            };
+
+            if let Err(err) = self
+                .handle_availability(&link, payload.availability())
+                .await
+            {
+                log::error!(
+                    "[{}] Cannot update availability of {link:?}: {err}",
+                    self.name
+                );
+            }
             return Ok(());
         }
 
@@ -128,30 +252,69 @@ impl Z2mBackend {
         Ok(())
     }
 
+    /// Reflect device availability in the `status` of its zigbee
+    /// connectivity. Hue clients show lights as unreachable from this.
+    async fn handle_availability(
+        &self,
+        link: &ResourceLink,
+        availability: Availability,
+    ) -> ApiResult<()> {
+        let status: ZigbeeConnectivityStatus = availability.into();
+
+        let mut lock = self.state.lock().await;
+
+        let device_link = match link.rtype {
+            RType::Light => lock.get::<Light>(link)?.owner,
+            RType::Device => *link,
+            // groups do not have a zigbee connectivity
+            _ => return Ok(()),
+        };
+
+        let Some(zbc) = lock
+            .get::<Device>(&device_link)?
+            .service(RType::ZigbeeConnectivity)
+            .copied()
+        else {
+            return Ok(());
+        };
+
+        log::debug!(
+            "[{}] Availability of {device_link:?}: {status:?}",
+            self.name
+        );
+        lock.update::<ZigbeeConnectivity>(&zbc.rid, |zigcon| zigcon.status = status)?;
+        drop(lock);
+
+        Ok(())
+    }
+
     async fn handle_action(
         &mut self,
         link: &ResourceLink,
         payload: &Value,
     ) -> Result<(), ApiError> {
-        let lock = self.state.lock().await;
-
-        let device = lock.get_id::<Device>(link.rid.clone())?.clone();
-        let model_id: String = if let Ok(aux) = lock.aux_get(&link) {
-            aux.model_id
-                .as_ref()
-                .unwrap_or(&device.product_data.model_id)
-                .clone()
-        } else {
-            device.product_data.model_id.clone()
-        };
-        drop(lock);
+        let device = self.state.lock().await.get_id::<Device>(link.rid)?.clone();
+
         let Some(action) = payload.as_str() else {
             log::warn!("[{}] Unable to parse action payload {}", self.name, payload);
             return Ok(());
         };
 
-        let Some(z2m_button_device) = self.get_button_handler(link, &model_id) else {
-            log::info!("Ignored unsupported button device {model_id} with action {action}");
+        // rotation is reported from the device state instead, since the
+        // step size and direction are not part of the action topic
+        if self
+            .rotaries
+            .get(link)
+            .is_some_and(|rotary| rotary.handles(action))
+        {
+            return Ok(());
+        }
+
+        let Some(z2m_button_device) = self.get_button_handler(link) else {
+            log::info!(
+                "Ignored unsupported button device {} with action {action}",
+                device.metadata.name
+            );
             return Ok(());
         };
         z2m_button_device
@@ -166,13 +329,17 @@ impl Z2mBackend {
     fn get_button_handler(
         &mut self,
         resource_link: &ResourceLink,
-        model_id: &str,
     ) -> Option<Arc<Mutex<Z2mButtonHandler>>> {
         let handler = self.button_handlers.get(resource_link);
         match handler {
             Some(handler) => Some(handler.clone()),
             None => {
-                let handler = Z2mButtonHandler::from_model_id(self.state.clone(), model_id)?;
+                let apidev = self
+                    .rmap
+                    .get(resource_link)
+                    .and_then(|topic| self.network.get(topic))?;
+                let data = Z2mButtonData::from_device(apidev, &self.config)?;
+                let handler = Z2mButtonHandler::new(self.state.clone(), data);
                 let handler = Arc::new(Mutex::new(handler));
                 self.button_handlers
                     .insert(resource_link.clone(), handler.clone());
@@ -209,6 +376,15 @@ impl Z2mBackend {
                     );
                     self.ignore.insert(dev.friendly_name.to_string());
                 }
+            } else if dev.expose_sensor() {
+                log::info!(
+                    "[{}] Adding sensor {:?}: [{}] ({})",
+                    self.name,
+                    dev.ieee_address,
+                    dev.friendly_name,
+                    dev.model_id.as_deref().unwrap_or("<unknown model>")
+                );
+                self.add_sensor(dev).await?;
             } else {
                 log::debug!(
                     "[{}] Ignoring unsupported device {}",
@@ -244,6 +420,134 @@ impl Z2mBackend {
         Ok(())
     }
 
+    async fn bridge_group_add(&mut self, resp: &Response<GroupAdd>) -> ApiResult<()> {
+        // responses to requests from other clients are ignored
+        let Some((topic, link)) = resp
+            .transaction()
+            .and_then(|transaction| self.pending_group_add.remove(transaction))
+        else {
+            if let Response::Error { error, .. } = resp {
+                log::warn!("[{}] Error reported from z2m: {error}", self.name);
+            }
+            return Ok(());
+        };
+
+        match resp {
+            Response::Ok { data, .. } => {
+                log::info!(
+                    "[{}] Created z2m group {topic} (id {:?}) for {link:?}",
+                    self.name,
+                    data.id
+                );
+            }
+            Response::Error { error, .. } => {
+                log::error!(
+                    "[{}] Failed to create z2m group {topic}: {error}",
+                    self.name
+                );
+
+                self.map.remove(&topic);
+                self.rmap.retain(|_, v| *v != topic);
+                self.state.lock().await.delete(&link)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn bridge_group_rename(&mut self, resp: &Response<GroupRename>) -> ApiResult<()> {
+        let pending = resp
+            .transaction()
+            .and_then(|transaction| self.pending_group_rename.remove(transaction));
+
+        match resp {
+            Response::Ok { data, .. } => {
+                log::info!(
+                    "[{}] Renamed z2m group {} -> {}",
+                    self.name,
+                    data.from,
+                    data.to
+                );
+
+                if let Some(link) = self.map.remove(&data.from) {
+                    self.map.insert(data.to.clone(), link);
+                }
+
+                for topic in self.rmap.values_mut() {
+                    if *topic == data.from {
+                        topic.clone_from(&data.to);
+                    }
+                }
+            }
+            Response::Error { error, .. } => {
+                let Some((link, rename)) = pending else {
+                    log::warn!("[{}] Error reported from z2m: {error}", self.name);
+                    return Ok(());
+                };
+
+                log::error!(
+                    "[{}] Failed to rename z2m group {} -> {}: {error}",
+                    self.name,
+                    rename.from,
+                    rename.to
+                );
+
+                // the group keeps its old name
+                let prefix = self.server.group_prefix.as_deref().unwrap_or_default();
+                let name = rename
+                    .from
+                    .strip_prefix(prefix)
+                    .unwrap_or(&rename.from)
+                    .to_string();
+
+                let mut lock = self.state.lock().await;
+                let aux = lock.aux_get(&link).cloned().unwrap_or_default();
+                lock.aux_set(&link, aux.with_topic(&rename.from));
+                match link.rtype {
+                    RType::Room => lock.update(&link.rid, |room: &mut Room| {
+                        room.metadata.name = name;
+                    })?,
+                    RType::Zone => lock.update(&link.rid, |zone: &mut Zone| {
+                        zone.metadata.name = name;
+                    })?,
+                    _ => {}
+                }
+                drop(lock);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Re-key the topic mappings for a device renamed in z2m
+    fn bridge_device_rename(&mut self, data: &DeviceRename) {
+        log::info!(
+            "[{}] Device renamed in z2m: {} -> {}",
+            self.name,
+            data.from,
+            data.to
+        );
+
+        if let Some(link) = self.map.remove(&data.from) {
+            self.map.insert(data.to.clone(), link);
+        }
+
+        for topic in self.rmap.values_mut() {
+            if *topic == data.from {
+                topic.clone_from(&data.to);
+            }
+        }
+
+        if let Some(mut dev) = self.network.remove(&data.from) {
+            dev.friendly_name.clone_from(&data.to);
+            self.network.insert(data.to.clone(), dev);
+        }
+
+        if self.ignore.remove(&data.from) {
+            self.ignore.insert(data.to.clone());
+        }
+    }
+
     #[allow(clippy::collapsible_else_if)]
     async fn bridge_group_member_change(
         &self,
@@ -257,6 +561,19 @@ impl Z2mBackend {
             let device_link = device.owner;
             if let Some(room) = self.map.get(&change.group) {
                 let room_link = lock.get::<GroupedLight>(room)?.owner;
+
+                // zones have light services as children, not devices
+                if room_link.rtype == RType::Zone {
+                    lock.update(&room_link.rid, |zone: &mut Zone| {
+                        if added {
+                            zone.children.insert(*light);
+                        } else {
+                            zone.children.remove(light);
+                        }
+                    })?;
+                    return Ok(());
+                }
+
                 let exists = lock
                     .get::<Room>(&room_link)?
                     .children
@@ -299,15 +616,21 @@ impl Z2mBackend {
             Message::BridgeDeviceOtaUpdateCheck(obj) => {}
             Message::BridgeDeviceConfigureReporting(obj) => {}
             Message::BridgeConfig(obj) => {}
-            Message::BridgeResponseGroupAdd(obj) => {}
             Message::BridgeResponseGroupRemove(obj) => {}
-            Message::BridgeResponseGroupRename(obj) => {}
             Message::BridgeResponseGroupOptions(obj) => {}
 
             Message::BridgeDevices(obj) => {
                 self.bridge_devices(obj).await?;
             }
 
+            Message::BridgeResponseGroupAdd(resp) => {
+                self.bridge_group_add(resp).await?;
+            }
+
+            Message::BridgeResponseGroupRename(resp) => {
+                self.bridge_group_rename(resp).await?;
+            }
+
             Message::BridgeGroups(obj) => {
                 /* println!("{obj:#?}"); */
                 for grp in obj {
@@ -334,6 +657,20 @@ impl Z2mBackend {
                 self.bridge_device_remove(data).await?;
             }
 
+            Message::BridgeDeviceRename(obj) => {
+                let Response::Ok { data, .. } = obj else {
+                    log::warn!("[{}] Error reported from z2m: {obj:?}", self.name);
+                    return Ok(());
+                };
+
+                self.bridge_device_rename(data);
+            }
+
+            Message::BridgeDeviceOtaUpdate(obj) => {
+                if let Response::Error { error, .. } = obj {
+                    log::error!("[{}] Firmware update failed: {error}", self.name);
+                }
+            }
             Message::BridgeHealth(_) => {}
         }
         Ok(())
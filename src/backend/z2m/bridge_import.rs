@@ -1,19 +1,20 @@
 use std::collections::HashSet;
 
 use maplit::btreeset;
-use serde_json::json;
 use uuid::Uuid;
 
 use hue::api::{
-    BridgeHome, Button, ContentConfiguration, ContentConfigurationOrder,
-    ContentConfigurationOrientation, ContentConfigurationStatusType, DeviceArchetype,
-    DeviceProductData, Entertainment, EntertainmentSegment, EntertainmentSegments, GroupedLight,
-    Light, LightEffects, LightEffectsV2, LightMetadata, Metadata, OrderType, OrientationType,
-    RType, Resource, ResourceLink, Room, RoomArchetype, RoomMetadata, Scene, SceneActive,
-    SceneMetadata, SceneRecall, SceneStatus, Stub, Taurus, ZigbeeConnectivity,
-    ZigbeeConnectivityStatus,
+    BridgeHome, Button, Contact, ContentConfiguration, ContentConfigurationOrder,
+    ContentConfigurationOrientation, ContentConfigurationStatusType, DeviceArchetype, DevicePower,
+    DeviceProductData, DeviceSoftwareUpdate, Entertainment, EntertainmentSegment,
+    EntertainmentSegments, GroupedLight, Light, LightEffects, LightEffectsV2, LightLevel,
+    LightMetadata, Metadata, Motion, OrderType, OrientationType, RType, RelativeRotary, Resource,
+    ResourceLink, Room, RoomArchetype, RoomMetadata, Scene, SceneActive, SceneMetadata,
+    ScenePalette, SceneRecall, SceneStatus, Stub, Tamper, Taurus, Temperature, ZigbeeConnectivity,
+    ZigbeeConnectivityStatus, Zone,
 };
 use hue::devicedb::gradient_product_data;
+use hue::error::HueError;
 use hue::scene_icons;
 use z2m::api::ExposeLight;
 use z2m::convert::{
@@ -23,10 +24,13 @@ use z2m::convert::{
 
 use crate::backend::z2m::Z2mBackend;
 use crate::backend::z2m::button::Z2mButtonData;
+use crate::backend::z2m::rotary::Z2mRotaryData;
 use crate::error::ApiResult;
 use crate::model::state::AuxData;
+use crate::resource::Resources;
 
 impl Z2mBackend {
+    #[allow(clippy::too_many_lines)]
     pub async fn add_light(
         &mut self,
         apidev: &z2m::api::Device,
@@ -48,10 +52,15 @@ impl Z2mBackend {
         let gradient = apidev.expose_gradient();
         let gradient_product_data = gradient_product_data(&product_data.model_id);
 
+        let dsu = Self::make_device_software_update(apidev, link_device);
+
+        let mut services = btreeset![link_zigcon, link_light, link_enttm, link_taurus];
+        services.extend(dsu.as_ref().map(|(link, _)| *link));
+
         let dev = hue::api::Device {
             product_data,
             metadata: metadata.clone().into(),
-            services: btreeset![link_zigcon, link_light, link_enttm, link_taurus],
+            services,
             identify: Some(Stub),
             usertest: None,
         };
@@ -158,11 +167,42 @@ impl Z2mBackend {
         res.add(&link_enttm, Resource::Entertainment(enttm))?;
         res.add(&link_taurus, Resource::Taurus(taurus))?;
         res.add(&link_zigcon, Resource::ZigbeeConnectivity(zigcon))?;
+        if let Some((link_dsu, dsu)) = dsu {
+            res.add_device_service(&link_device, &link_dsu, Resource::DeviceSoftwareUpdate(dsu))?;
+        }
         drop(res);
 
         Ok(())
     }
 
+    /// Battery-powered devices get a `device_power` service, updated from the
+    /// `battery` property in state messages
+    fn make_device_power(
+        apidev: &z2m::api::Device,
+        link_device: ResourceLink,
+    ) -> Option<(ResourceLink, DevicePower)> {
+        apidev.battery_powered().then(|| {
+            (
+                RType::DevicePower.deterministic(&apidev.ieee_address),
+                DevicePower::new(link_device),
+            )
+        })
+    }
+
+    /// Devices that support OTA updates in z2m get a `device_software_update`
+    /// service, updated from the `update` property in state messages
+    fn make_device_software_update(
+        apidev: &z2m::api::Device,
+        link_device: ResourceLink,
+    ) -> Option<(ResourceLink, DeviceSoftwareUpdate)> {
+        apidev.supports_ota().then(|| {
+            (
+                RType::DeviceSoftwareUpdate.deterministic(&apidev.ieee_address),
+                DeviceSoftwareUpdate::new(link_device),
+            )
+        })
+    }
+
     pub async fn add_switch(&mut self, apidev: &z2m::api::Device) -> ApiResult<Option<()>> {
         let name = &apidev.friendly_name;
 
@@ -173,22 +213,36 @@ impl Z2mBackend {
 
         let link_zbc = RType::ZigbeeConnectivity.deterministic(&apidev.ieee_address);
 
-        // missing services:
-        // device_power
-        // device_software_update
-
         let mut services = btreeset![link_zbc];
         let mut buttons = vec![];
 
-        let Some(model_id) = &apidev.model_id else {
-            return Ok(None);
-        };
+        let power = Self::make_device_power(apidev, link_device);
+        services.extend(power.as_ref().map(|(link, _)| *link));
 
-        let Some(button_device) = Z2mButtonData::from_model_id(&model_id) else {
+        let dsu = Self::make_device_software_update(apidev, link_device);
+        services.extend(dsu.as_ref().map(|(link, _)| *link));
+
+        let button_device = Z2mButtonData::from_device(apidev, &self.config);
+        let rotary_data = Z2mRotaryData::from_device(apidev);
+        let rotary = rotary_data.as_ref().map(|_| {
+            let link_rotary = RType::RelativeRotary.deterministic(&apidev.ieee_address);
+            (link_rotary, RelativeRotary::new(link_device))
+        });
+
+        // kept for handling updates, instead of deriving it for every message
+        if let Some(rotary_data) = rotary_data {
+            self.rotaries.insert(link_device, rotary_data);
+        } else {
+            self.rotaries.remove(&link_device);
+        }
+
+        if button_device.is_none() && rotary.is_none() {
             return Ok(None);
-        };
+        }
+
+        services.extend(rotary.as_ref().map(|(link, _)| *link));
 
-        for button in &button_device.buttons {
+        for button in button_device.iter().flat_map(|bd| &bd.buttons) {
             let link_button = RType::Button.deterministic((&apidev.ieee_address, &button.name));
             let button = Button {
                 owner: link_device,
@@ -217,22 +271,173 @@ impl Z2mBackend {
             status: ZigbeeConnectivityStatus::Connected,
         };
 
-        if let Some(model_id) = &apidev.model_id {
-            // needed to look up button mappings when handling actions
-            res.aux_set(&link_device, AuxData::new().with_model_id(&model_id));
-        }
         res.add(&link_device, Resource::Device(dev))?;
         for (link_button, button) in buttons {
             res.add(&link_button, Resource::Button(button))?;
         }
+        if let Some((link_rotary, rotary)) = rotary {
+            res.add_device_service(&link_device, &link_rotary, Resource::RelativeRotary(rotary))?;
+        }
+        if let Some((link_power, power)) = power {
+            res.add_device_service(&link_device, &link_power, Resource::DevicePower(power))?;
+        }
         res.add(&link_zbc, Resource::ZigbeeConnectivity(zigcon))?;
+        if let Some((link_dsu, dsu)) = dsu {
+            res.add_device_service(&link_device, &link_dsu, Resource::DeviceSoftwareUpdate(dsu))?;
+        }
         drop(res);
 
         Ok(Some(()))
     }
 
+    pub async fn add_sensor(&mut self, apidev: &z2m::api::Device) -> ApiResult<()> {
+        let name = &apidev.friendly_name;
+
+        let link_device = RType::Device.deterministic(&apidev.ieee_address);
+        let link_zbc = RType::ZigbeeConnectivity.deterministic(&apidev.ieee_address);
+
+        let mut sensors = vec![];
+
+        if apidev.expose_named("occupancy").is_some() {
+            let link_motion = RType::Motion.deterministic(&apidev.ieee_address);
+            let motion = Motion::new(link_device);
+            sensors.push((link_motion, Resource::Motion(motion)));
+        }
+
+        if apidev.expose_named("contact").is_some() {
+            let link_contact = RType::Contact.deterministic(&apidev.ieee_address);
+            let contact = Contact::new(link_device);
+            sensors.push((link_contact, Resource::Contact(contact)));
+        }
+
+        if apidev.expose_named("tamper").is_some() {
+            let link_tamper = RType::Tamper.deterministic(&apidev.ieee_address);
+            let tamper = Tamper::new(link_device);
+            sensors.push((link_tamper, Resource::Tamper(tamper)));
+        }
+
+        if apidev.expose_illuminance().is_some() {
+            let link_light_level = RType::LightLevel.deterministic(&apidev.ieee_address);
+            let light_level = LightLevel::new(link_device);
+            sensors.push((link_light_level, Resource::LightLevel(light_level)));
+        }
+
+        if apidev.expose_named("temperature").is_some() {
+            let link_temperature = RType::Temperature.deterministic(&apidev.ieee_address);
+            let temperature = Temperature::new(link_device);
+            sensors.push((link_temperature, Resource::Temperature(temperature)));
+        }
+
+        self.map.insert(name.to_owned(), link_device);
+        self.rmap.insert(link_device, name.to_owned());
+
+        let power = Self::make_device_power(apidev, link_device);
+        let dsu = Self::make_device_software_update(apidev, link_device);
+
+        let mut services = btreeset![link_zbc];
+        services.extend(sensors.iter().map(|(link, _)| *link));
+        services.extend(power.as_ref().map(|(link, _)| *link));
+        services.extend(dsu.as_ref().map(|(link, _)| *link));
+
+        let dev = hue::api::Device {
+            product_data: DeviceProductData::guess_from_device(apidev),
+            metadata: Metadata::new(DeviceArchetype::UnknownArchetype, name),
+            services,
+            identify: None,
+            usertest: None,
+        };
+
+        let zigcon = ZigbeeConnectivity {
+            channel: None,
+            extended_pan_id: None,
+            mac_address: apidev.ieee_address.as_mac(),
+            owner: link_device,
+            status: ZigbeeConnectivityStatus::Connected,
+        };
+
+        let mut res = self.state.lock().await;
+        res.add(&link_device, Resource::Device(dev))?;
+        for (link_sensor, sensor) in sensors {
+            res.add_device_service(&link_device, &link_sensor, sensor)?;
+        }
+        if let Some((link_power, power)) = power {
+            res.add_device_service(&link_device, &link_power, Resource::DevicePower(power))?;
+        }
+        res.add(&link_zbc, Resource::ZigbeeConnectivity(zigcon))?;
+        if let Some((link_dsu, dsu)) = dsu {
+            res.add_device_service(&link_device, &link_dsu, Resource::DeviceSoftwareUpdate(dsu))?;
+        }
+        drop(res);
+
+        Ok(())
+    }
+
+    /// Find the room or zone (`rtype`) linked to the z2m group with the given name
+    fn find_group(res: &Resources, rtype: RType, topic: &str) -> Option<ResourceLink> {
+        res.get_resource_ids_by_type(rtype)
+            .into_iter()
+            .map(|id| rtype.link_to(id))
+            .find(|link| {
+                res.aux_get(link)
+                    .is_ok_and(|aux| aux.topic.as_deref() == Some(topic))
+            })
+    }
+
+    pub async fn add_zone(
+        &mut self,
+        grp: &z2m::api::Group,
+        link_zone: ResourceLink,
+    ) -> ApiResult<()> {
+        let topic = grp.friendly_name.clone();
+
+        let mut res = self.state.lock().await;
+
+        let zone = res.get::<Zone>(&link_zone)?;
+        let link_glight = *zone
+            .grouped_light_service()
+            .ok_or(HueError::NotFound(link_zone.rid))?;
+
+        log::info!(
+            "[{}] {link_zone:?} ({}) known, updating..",
+            self.name,
+            zone.metadata.name
+        );
+
+        let children = grp
+            .members
+            .iter()
+            .map(|f| RType::Light.deterministic(&f.ieee_address))
+            .filter(|link| res.get::<Light>(link).is_ok())
+            .collect();
+
+        res.update(&link_zone.rid, |zone: &mut Zone| zone.children = children)?;
+        let aux = res.aux_get(&link_zone).cloned().unwrap_or_default();
+        res.aux_set(&link_zone, aux.with_topic(&topic).with_index(grp.id));
+        drop(res);
+
+        self.map.insert(topic.clone(), link_glight);
+        self.rmap.insert(link_glight, topic.clone());
+        self.rmap.insert(link_zone, topic);
+
+        Ok(())
+    }
+
     #[allow(clippy::too_many_lines)]
     pub async fn add_group(&mut self, grp: &z2m::api::Group) -> ApiResult<()> {
+        if self.ignore.contains(&grp.friendly_name) {
+            return Ok(());
+        }
+
+        let res = self.state.lock().await;
+        let link_zone = Self::find_group(&res, RType::Zone, &grp.friendly_name);
+        let link_room = Self::find_group(&res, RType::Room, &grp.friendly_name)
+            .unwrap_or_else(|| RType::Room.deterministic(&grp.friendly_name));
+        drop(res);
+
+        if let Some(link_zone) = link_zone {
+            return self.add_zone(grp, link_zone).await;
+        }
+
         let room_name;
 
         if let Some(ref prefix) = self.server.group_prefix {
@@ -250,9 +455,6 @@ impl Z2mBackend {
             room_name = &grp.friendly_name;
         }
 
-        let link_room = RType::Room.deterministic(&grp.friendly_name);
-        let link_glight = RType::GroupedLight.deterministic((link_room.rid, grp.id));
-
         let children = grp
             .members
             .iter()
@@ -275,12 +477,7 @@ impl Z2mBackend {
                     image: guess_scene_icon(&scn.name),
                     name: scn.name.clone(),
                 },
-                palette: json!({
-                    "color": [],
-                    "dimming": [],
-                    "color_temperature": [],
-                    "effects": [],
-                }),
+                palette: ScenePalette::default(),
                 speed: 0.5,
                 recall: SceneRecall {
                     action: None,
@@ -304,6 +501,9 @@ impl Z2mBackend {
             res.add(&link_scene, Resource::Scene(scene))?;
         }
 
+        let mut archetype = RoomArchetype::Home;
+        let mut link_glight = RType::GroupedLight.deterministic((link_room.rid, grp.id));
+
         if let Ok(room) = res.get::<Room>(&link_room) {
             log::info!(
                 "[{}] {link_room:?} ({}) known, updating..",
@@ -311,6 +511,13 @@ impl Z2mBackend {
                 room.metadata.name
             );
 
+            // keep the archetype (icon) and grouped light of known rooms,
+            // since these might have been chosen when created from the app
+            archetype = room.metadata.archetype;
+            if let Some(glight) = room.grouped_light_service() {
+                link_glight = *glight;
+            }
+
             let scenes_old: HashSet<Uuid> =
                 HashSet::from_iter(res.get_scenes_for_room(&link_room.rid));
 
@@ -333,7 +540,7 @@ impl Z2mBackend {
             );
         }
 
-        let mut metadata = RoomMetadata::new(RoomArchetype::Home, room_name);
+        let mut metadata = RoomMetadata::new(archetype, room_name);
         if let Some(room_conf) = self.config.rooms.get(&topic) {
             if let Some(name) = &room_conf.name {
                 metadata.name.clone_from(name);
@@ -359,6 +566,8 @@ impl Z2mBackend {
             })?;
         }
 
+        let aux = res.aux_get(&link_room).cloned().unwrap_or_default();
+        res.aux_set(&link_room, aux.with_topic(&topic).with_index(grp.id));
         res.add(&link_room, Resource::Room(room))?;
 
         let glight = GroupedLight::new(link_room);
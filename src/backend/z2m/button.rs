@@ -7,7 +7,52 @@ use hue::api::{
 };
 use tokio::{sync::Mutex, task::JoinHandle, time::sleep};
 
-use crate::{error::ApiResult, resource::Resources};
+use crate::backend::z2m::rotary::Z2mRotaryData;
+use crate::{config::AppConfig, error::ApiResult, resource::Resources};
+
+/// Suffixes of z2m `action` values, and the button events they map to (the
+/// rest of the value is the button name). Longer suffixes must come before
+/// shorter suffixes they end with.
+const ACTION_SUFFIXES: &[(&str, ButtonEvent)] = &[
+    ("_press_release", ButtonEvent::ShortRelease),
+    ("_short_release", ButtonEvent::ShortRelease),
+    ("_hold_release", ButtonEvent::LongRelease),
+    ("_long_release", ButtonEvent::LongRelease),
+    ("_double_press", ButtonEvent::DoubleShortRelease),
+    ("_long_press", ButtonEvent::LongPress),
+    ("_double", ButtonEvent::DoubleShortRelease),
+    // hue has no triple press, so report it as a multi-press too
+    ("_triple", ButtonEvent::DoubleShortRelease),
+    ("_press", ButtonEvent::InitialPress),
+    ("_hold", ButtonEvent::LongPress),
+    ("_release", ButtonEvent::LongRelease),
+    ("_single", ButtonEvent::ShortRelease),
+    ("_click", ButtonEvent::ShortRelease),
+];
+
+/// Prefixes of z2m `action` values (like `single_left`), and the button
+/// events they map to
+const ACTION_PREFIXES: &[(&str, ButtonEvent)] = &[
+    ("single_", ButtonEvent::ShortRelease),
+    ("double_", ButtonEvent::DoubleShortRelease),
+    ("hold_", ButtonEvent::LongPress),
+    ("release_", ButtonEvent::LongRelease),
+];
+
+/// Level control `action` values of remotes like the IKEA STYRBAR and RODRET,
+/// which report holding the on and off buttons as dimming commands. These are
+/// mapped to holding the named button, instead of creating extra buttons.
+const LEVEL_ACTIONS: &[(&str, &str)] = &[
+    ("brightness_move_up", "on"),
+    ("brightness_move_down", "off"),
+];
+
+/// Level control `action` value for releasing the button held through one of
+/// the [`LEVEL_ACTIONS`]
+const LEVEL_STOP: &str = "brightness_stop";
+
+/// Name of the button for devices with a single, unnamed button
+const DEFAULT_BUTTON: &str = "button";
 
 pub struct Z2mButtonHandler {
     data: Z2mButtonData,
@@ -17,14 +62,13 @@ pub struct Z2mButtonHandler {
 }
 
 impl Z2mButtonHandler {
-    pub fn from_model_id(res: Arc<Mutex<Resources>>, model_id: &str) -> Option<Self> {
-        let button_data = Z2mButtonData::from_model_id(model_id);
-        button_data.map(|data| Self {
+    pub fn new(res: Arc<Mutex<Resources>>, data: Z2mButtonData) -> Self {
+        Self {
             data,
             res,
             prev_button_press: Arc::new(Mutex::new(None)),
             button_repeat_task: None,
-        })
+        }
     }
 
     pub async fn handle_action(&mut self, device: &Device, action: &str) -> ApiResult<()> {
@@ -64,7 +108,7 @@ impl Z2mButtonHandler {
             return Ok(());
         };
         log::trace!(
-            "Recevied button action {} {} {:?}",
+            "Received button action {} {} {:?}",
             button_controller.metadata.control_id,
             device.metadata.name,
             button_event
@@ -184,8 +228,10 @@ impl Z2mButtonHandler {
 #[derive(Debug)]
 pub struct Z2mButtonData {
     pub buttons: Vec<Z2mButton>,
-    mappings: HashMap<&'static str, Z2mButtonMapping>,
+    mappings: HashMap<String, Z2mButtonMapping>,
     required_longpress_workaround: bool,
+    /// Control id of the button held through one of the [`LEVEL_ACTIONS`]
+    held: Option<u32>,
 }
 
 #[derive(Debug)]
@@ -210,12 +256,193 @@ impl Z2mButtonData {
         }
     }
 
+    /// Button data for a z2m device, from the known models, or otherwise
+    /// derived from the values of its `action` expose (except the values of a
+    /// rotary). Mappings from the config are applied on top.
+    ///
+    /// Returns `None` if the device has no buttons.
+    pub fn from_device(apidev: &z2m::api::Device, config: &AppConfig) -> Option<Self> {
+        let model_id = apidev.model_id.as_deref().unwrap_or_default();
+
+        let mut data = Self::from_model_id(model_id).unwrap_or_else(|| {
+            let rotary = Z2mRotaryData::from_device(apidev);
+            Self::from_action_values(&apidev.action_values(), rotary.as_ref())
+        });
+
+        for (action, mapping) in config.buttons.get(model_id).into_iter().flatten() {
+            data.add_mapping(action, &mapping.button, mapping.event);
+        }
+
+        (!data.buttons.is_empty()).then_some(data)
+    }
+
+    fn empty() -> Self {
+        Self {
+            buttons: vec![],
+            mappings: HashMap::new(),
+            required_longpress_workaround: false,
+            held: None,
+        }
+    }
+
+    /// Button data derived from the values of an `action` expose, except the
+    /// values handled by the `rotary`
+    fn from_action_values(values: &[&str], rotary: Option<&Z2mRotaryData>) -> Self {
+        let values: Vec<&str> = values
+            .iter()
+            .copied()
+            .filter(|action| !rotary.is_some_and(|rotary| rotary.handles(action)))
+            .collect();
+
+        let mut data = Self::empty();
+
+        for action in &values {
+            if *action == LEVEL_STOP || LEVEL_ACTIONS.iter().any(|(level, _)| level == action) {
+                continue;
+            }
+            if let Some((button, event)) = Self::parse_action(action) {
+                data.add_mapping(action, button, event);
+            }
+        }
+
+        data.add_level_mappings(&values);
+        data.fixup_release_events();
+        data
+    }
+
+    /// Split an `action` value into button name and event
+    fn parse_action(action: &str) -> Option<(&str, ButtonEvent)> {
+        if action.is_empty() {
+            return None;
+        }
+
+        for (suffix, event) in ACTION_SUFFIXES {
+            // values like "press" or "hold" are events of an unnamed button
+            if Some(action) == suffix.strip_prefix('_') {
+                return Some((DEFAULT_BUTTON, *event));
+            }
+            if let Some(button) = action.strip_suffix(suffix) {
+                return Some((button, *event));
+            }
+        }
+
+        for (prefix, event) in ACTION_PREFIXES {
+            if let Some(button) = action.strip_prefix(prefix) {
+                return Some((button, *event));
+            }
+        }
+
+        // anything else (like "on" or "toggle") is a button on its own,
+        // which only reports a single event per press
+        Some((action, ButtonEvent::ShortRelease))
+    }
+
+    fn add_mapping(&mut self, action: &str, button: &str, event: ButtonEvent) {
+        let control_id = if let Some(btn) = self.buttons.iter().find(|btn| btn.name == button) {
+            btn.metadata.control_id
+        } else {
+            let control_id = u32::try_from(self.buttons.len() + 1).unwrap_or(u32::MAX);
+            self.buttons.push(Z2mButton {
+                name: button.to_string(),
+                data: ButtonData {
+                    button_report: None,
+                    last_event: None,
+                    repeat_interval: None,
+                    event_values: Some(vec![]),
+                },
+                metadata: ButtonMetadata { control_id },
+            });
+            control_id
+        };
+
+        self.add_event_value(button, event);
+
+        self.mappings.insert(
+            action.to_string(),
+            Z2mButtonMapping {
+                control_id,
+                action: event,
+            },
+        );
+    }
+
+    fn add_event_value(&mut self, button: &str, event: ButtonEvent) {
+        if let Some(btn) = self.buttons.iter_mut().find(|btn| btn.name == button) {
+            let events = btn.data.event_values.get_or_insert_default();
+            if !events.contains(&event) {
+                events.push(event);
+            }
+            if event == ButtonEvent::LongPress {
+                btn.data.repeat_interval = Some(800);
+            }
+        }
+    }
+
+    /// Map the [`LEVEL_ACTIONS`] to holding their button, if the device has
+    /// that button and can report releasing it
+    fn add_level_mappings(&mut self, values: &[&str]) {
+        if !values.contains(&LEVEL_STOP) {
+            return;
+        }
+
+        for (action, button) in LEVEL_ACTIONS {
+            if !values.contains(action) || !self.buttons.iter().any(|btn| btn.name == *button) {
+                continue;
+            }
+
+            self.add_mapping(action, button, ButtonEvent::LongPress);
+            self.add_event_value(button, ButtonEvent::LongRelease);
+        }
+    }
+
+    /// Make sure every derived button reports a short release, since that is
+    /// what hue clients act on
+    fn fixup_release_events(&mut self) {
+        for btn in &mut self.buttons {
+            let events = btn.data.event_values.get_or_insert_default();
+
+            if events.contains(&ButtonEvent::ShortRelease) {
+                continue;
+            }
+
+            if events.contains(&ButtonEvent::LongRelease) {
+                // a plain release is a short release, unless the button was held
+                events.push(ButtonEvent::ShortRelease);
+            } else {
+                // without any release event, a press is all we get
+                let control_id = btn.metadata.control_id;
+                events.retain(|evt| *evt != ButtonEvent::InitialPress);
+                events.push(ButtonEvent::ShortRelease);
+                for mapping in self.mappings.values_mut() {
+                    if mapping.control_id == control_id
+                        && mapping.action == ButtonEvent::InitialPress
+                    {
+                        mapping.action = ButtonEvent::ShortRelease;
+                    }
+                }
+            }
+        }
+    }
+
     pub fn get_controller_id(&self, action: &str) -> Option<u32> {
-        self.mappings.get(&action).map(|m| m.control_id)
+        if action == LEVEL_STOP {
+            return self.held;
+        }
+        self.mappings.get(action).map(|m| m.control_id)
     }
 
     fn next_button_event(&mut self, button_data: &ButtonData, action: &str) -> Option<ButtonEvent> {
-        let mapped_button_event = self.mappings.get(&action).cloned()?.action;
+        if action == LEVEL_STOP {
+            self.held.take()?;
+            return Some(ButtonEvent::LongRelease);
+        }
+
+        let mapping = self.mappings.get(action).cloned()?;
+        if LEVEL_ACTIONS.iter().any(|(level, _)| *level == action) {
+            self.held = Some(mapping.control_id);
+        }
+
+        let mapped_button_event = mapping.action;
         let Some(current_button_report) = &button_data.button_report else {
             return Some(mapped_button_event);
         };
@@ -224,6 +451,11 @@ impl Z2mButtonData {
                 ButtonEvent::LongPress | ButtonEvent::Repeat => ButtonEvent::Repeat,
                 _ => mapped_button_event,
             },
+            // a release after a short press is a short release
+            ButtonEvent::LongRelease => match current_button_report.event {
+                ButtonEvent::InitialPress => ButtonEvent::ShortRelease,
+                _ => mapped_button_event,
+            },
             _ => mapped_button_event,
         })
     }
@@ -233,6 +465,7 @@ fn friends_of_hue_switch() -> Z2mButtonData {
     let events = vec![ButtonEvent::InitialPress, ButtonEvent::ShortRelease];
     Z2mButtonData {
         required_longpress_workaround: true,
+        held: None,
         buttons: vec![
             Z2mButton {
                 name: "1".to_string(),
@@ -275,7 +508,7 @@ fn friends_of_hue_switch() -> Z2mButtonData {
                 metadata: ButtonMetadata { control_id: 4 },
             },
         ],
-        mappings: maplit::hashmap! {
+        mappings: owned_keys(maplit::hashmap! {
             "press_1" => Z2mButtonMapping { control_id: 1, action: ButtonEvent::InitialPress},
             "release_1" => Z2mButtonMapping { control_id: 1, action: ButtonEvent::ShortRelease},
 
@@ -287,7 +520,7 @@ fn friends_of_hue_switch() -> Z2mButtonData {
 
             "press_4" => Z2mButtonMapping { control_id: 4, action: ButtonEvent::InitialPress},
             "release_4" => Z2mButtonMapping { control_id: 4, action: ButtonEvent::ShortRelease},
-        },
+        }),
     }
 }
 
@@ -301,6 +534,7 @@ fn hue_dimmer_switch() -> Z2mButtonData {
     ];
     Z2mButtonData {
         required_longpress_workaround: false,
+        held: None,
         buttons: vec![
             Z2mButton {
                 name: "on".to_string(),
@@ -343,7 +577,7 @@ fn hue_dimmer_switch() -> Z2mButtonData {
                 metadata: ButtonMetadata { control_id: 4 },
             },
         ],
-        mappings: maplit::hashmap! {
+        mappings: owned_keys(maplit::hashmap! {
             "on_press" => Z2mButtonMapping { control_id: 1, action: ButtonEvent::InitialPress},
             "on_hold" => Z2mButtonMapping { control_id: 1, action: ButtonEvent::LongPress},
             "on_press_release" => Z2mButtonMapping { control_id: 1, action: ButtonEvent::ShortRelease},
@@ -363,6 +597,182 @@ fn hue_dimmer_switch() -> Z2mButtonData {
             "off_hold" => Z2mButtonMapping { control_id: 4, action: ButtonEvent::LongPress},
             "off_press_release" => Z2mButtonMapping { control_id: 4, action: ButtonEvent::ShortRelease},
             "off_hold_release" => Z2mButtonMapping { control_id: 4, action: ButtonEvent::LongRelease},
-        },
+        }),
+    }
+}
+
+fn owned_keys(mappings: HashMap<&str, Z2mButtonMapping>) -> HashMap<String, Z2mButtonMapping> {
+    mappings
+        .into_iter()
+        .map(|(action, mapping)| (action.to_string(), mapping))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use hue::api::ButtonEvent;
+
+    use crate::backend::z2m::button::Z2mButtonData;
+
+    #[test]
+    fn parse_action() {
+        let cases = [
+            (
+                "button_1_press_release",
+                "button_1",
+                ButtonEvent::ShortRelease,
+            ),
+            ("button_1_hold", "button_1", ButtonEvent::LongPress),
+            ("arrow_left_click", "arrow_left", ButtonEvent::ShortRelease),
+            ("arrow_left_release", "arrow_left", ButtonEvent::LongRelease),
+            (
+                "button_2_double",
+                "button_2",
+                ButtonEvent::DoubleShortRelease,
+            ),
+            (
+                "button_3_triple",
+                "button_3",
+                ButtonEvent::DoubleShortRelease,
+            ),
+            ("single_left", "left", ButtonEvent::ShortRelease),
+            ("hold", "button", ButtonEvent::LongPress),
+            ("on", "on", ButtonEvent::ShortRelease),
+        ];
+
+        for (action, button, event) in cases {
+            assert_eq!(Z2mButtonData::parse_action(action), Some((button, event)));
+        }
+    }
+
+    fn button_names(data: &Z2mButtonData) -> Vec<&str> {
+        data.buttons.iter().map(|btn| btn.name.as_str()).collect()
+    }
+
+    fn event_values(data: &Z2mButtonData, button: &str) -> Vec<ButtonEvent> {
+        let btn = data.buttons.iter().find(|btn| btn.name == button).unwrap();
+        btn.data.event_values.clone().unwrap_or_default()
+    }
+
+    #[test]
+    fn ikea_styrbar() {
+        let data = Z2mButtonData::from_action_values(
+            &[
+                "on",
+                "off",
+                "brightness_move_up",
+                "brightness_move_down",
+                "brightness_stop",
+                "arrow_left_click",
+                "arrow_left_hold",
+                "arrow_left_release",
+                "arrow_right_click",
+                "arrow_right_hold",
+                "arrow_right_release",
+            ],
+            None,
+        );
+
+        assert_eq!(
+            button_names(&data),
+            ["on", "off", "arrow_left", "arrow_right"]
+        );
+        assert_eq!(
+            event_values(&data, "on"),
+            [
+                ButtonEvent::ShortRelease,
+                ButtonEvent::LongPress,
+                ButtonEvent::LongRelease
+            ]
+        );
+        assert_eq!(data.get_controller_id("brightness_move_down"), Some(2));
+        assert_eq!(data.get_controller_id("arrow_right_hold"), Some(4));
+    }
+
+    #[test]
+    fn ikea_rodret() {
+        let mut data = Z2mButtonData::from_action_values(
+            &[
+                "on",
+                "off",
+                "brightness_move_up",
+                "brightness_move_down",
+                "brightness_stop",
+            ],
+            None,
+        );
+
+        assert_eq!(button_names(&data), ["on", "off"]);
+
+        // nothing is held yet, so there is nothing to release
+        assert_eq!(data.get_controller_id("brightness_stop"), None);
+
+        let idle = data.buttons[0].data.clone();
+        assert_eq!(
+            data.next_button_event(&idle, "brightness_move_up"),
+            Some(ButtonEvent::LongPress)
+        );
+        assert_eq!(data.get_controller_id("brightness_stop"), Some(1));
+        assert_eq!(
+            data.next_button_event(&idle, "brightness_stop"),
+            Some(ButtonEvent::LongRelease)
+        );
+        assert_eq!(data.get_controller_id("brightness_stop"), None);
+    }
+
+    #[test]
+    fn level_actions_without_buttons() {
+        // without on/off buttons, level control actions do not make buttons
+        let data = Z2mButtonData::from_action_values(
+            &["toggle", "brightness_move_up", "brightness_stop"],
+            None,
+        );
+
+        assert_eq!(button_names(&data), ["toggle"]);
+        assert_eq!(data.get_controller_id("brightness_move_up"), None);
+    }
+
+    #[test]
+    fn aqara_opple() {
+        let actions: Vec<String> = (1..=6)
+            .flat_map(|n| {
+                ["hold", "release", "single", "double", "triple"]
+                    .map(|event| format!("button_{n}_{event}"))
+            })
+            .collect();
+        let actions: Vec<&str> = actions.iter().map(String::as_str).collect();
+
+        let data = Z2mButtonData::from_action_values(&actions, None);
+
+        assert_eq!(
+            button_names(&data),
+            [
+                "button_1", "button_2", "button_3", "button_4", "button_5", "button_6"
+            ]
+        );
+        assert_eq!(data.get_controller_id("button_6_triple"), Some(6));
+        assert_eq!(
+            event_values(&data, "button_1"),
+            [
+                ButtonEvent::LongPress,
+                ButtonEvent::LongRelease,
+                ButtonEvent::ShortRelease,
+                ButtonEvent::DoubleShortRelease
+            ]
+        );
+    }
+
+    #[test]
+    fn press_without_release() {
+        let mut data = Z2mButtonData::empty();
+        data.add_mapping("on_press", "on", ButtonEvent::InitialPress);
+        data.add_mapping("off_press", "off", ButtonEvent::InitialPress);
+        data.add_mapping("off_press_release", "off", ButtonEvent::ShortRelease);
+        data.fixup_release_events();
+
+        assert_eq!(data.buttons.len(), 2);
+        assert_eq!(data.get_controller_id("off_press_release"), Some(2));
+        assert_eq!(data.mappings["on_press"].action, ButtonEvent::ShortRelease);
+        assert_eq!(data.mappings["off_press"].action, ButtonEvent::InitialPress);
     }
 }
@@ -0,0 +1,80 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+
+use hue::api::{LightUpdate, ResourceLink, Scene};
+use z2m::update::DeviceUpdate;
+
+/// Playback of a scene recalled as `dynamic_palette`
+///
+/// Every light in the scene slowly cycles through the palette of the scene,
+/// each light starting at a different palette entry. The playback task runs
+/// until this object is dropped.
+pub struct DynamicScene {
+    pub group: ResourceLink,
+    pub lights: HashSet<ResourceLink>,
+    task: JoinHandle<()>,
+}
+
+impl DynamicScene {
+    /// Start playback of `scene` on the given lights (and their z2m topics)
+    ///
+    /// Returns `None` if there is nothing to play back.
+    pub fn start(
+        scene: &Scene,
+        lights: Vec<(ResourceLink, String)>,
+        tx: mpsc::UnboundedSender<(String, DeviceUpdate)>,
+    ) -> Option<Self> {
+        let step = scene.dynamic_step_duration();
+
+        let palette: Vec<DeviceUpdate> = scene
+            .dynamic_palette()
+            .iter()
+            .map(|upd| Self::device_update(upd, step))
+            .collect();
+
+        if palette.is_empty() || lights.is_empty() {
+            return None;
+        }
+
+        let (lights, topics): (HashSet<_>, Vec<_>) = lights.into_iter().unzip();
+
+        let task = tokio::spawn(async move {
+            for offset in 0.. {
+                // the scene has just been recalled, so start by waiting a full step
+                sleep(step).await;
+
+                for (index, topic) in topics.iter().enumerate() {
+                    let upd = palette[(index + offset) % palette.len()].clone();
+                    if tx.send((topic.clone(), upd)).is_err() {
+                        return;
+                    }
+                }
+            }
+        });
+
+        Some(Self {
+            group: scene.group,
+            lights,
+            task,
+        })
+    }
+
+    fn device_update(upd: &LightUpdate, transition: Duration) -> DeviceUpdate {
+        DeviceUpdate::default()
+            .with_state(Some(true))
+            .with_brightness(upd.dimming.map(|dim| dim.brightness / 100.0 * 254.0))
+            .with_color_temp(upd.color_temperature.and_then(|ct| ct.mirek))
+            .with_color_xy(upd.color.map(|col| col.xy))
+            .with_transition(Some(transition.as_secs_f64()))
+    }
+}
+
+impl Drop for DynamicScene {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
@@ -2,8 +2,11 @@ mod backend_event;
 mod bridge_event;
 mod bridge_import;
 mod button;
+mod dynamic_scene;
 pub mod entertainment;
 pub mod learn;
+pub mod mqtt;
+mod rotary;
 pub mod websocket;
 pub mod zclcommand;
 
@@ -28,12 +31,16 @@ use tokio_tungstenite::{
 
 use bifrost_api::backend::BackendRequest;
 use hue::api::ResourceLink;
+use z2m::api::GroupRename;
 use z2m::update::DeviceUpdate;
 
 use crate::backend::z2m::button::Z2mButtonHandler;
+use crate::backend::z2m::dynamic_scene::DynamicScene;
 use crate::backend::z2m::entertainment::EntStream;
 use crate::backend::z2m::learn::SceneLearn;
-use crate::backend::z2m::websocket::Z2mWebSocket;
+use crate::backend::z2m::mqtt::Z2mMqtt;
+use crate::backend::z2m::rotary::Z2mRotaryData;
+use crate::backend::z2m::websocket::{Z2mTransport, Z2mWebSocket};
 use crate::config::{AppConfig, Z2mServer};
 use crate::error::{ApiError, ApiResult};
 use crate::model::throttle::Throttle;
@@ -82,10 +89,20 @@ pub struct Z2mBackend {
     network: HashMap<String, z2m::api::Device>,
     entstream: Option<EntStream>,
     counter: u32,
+    transaction: u64,
     fps: u32,
     throttle: Throttle,
     socket: Option<WebSocketStream<MaybeTlsStream<TcpStream>>>,
     button_handlers: HashMap<ResourceLink, Arc<Mutex<Z2mButtonHandler>>>,
+    rotaries: HashMap<ResourceLink, Z2mRotaryData>,
+    dynamic_scenes: HashMap<ResourceLink, DynamicScene>,
+
+    // group requests sent to z2m, waiting for a response
+    //
+    // group creation is matched on transaction id, since the z2m frontend
+    // also forwards responses to requests from other clients
+    pending_group_add: HashMap<String, (String, ResourceLink)>,
+    pending_group_rename: HashMap<String, (ResourceLink, GroupRename)>,
 
     // for sending delayed messages over the websocket
     message_rx: mpsc::UnboundedReceiver<(String, DeviceUpdate)>,
@@ -128,11 +145,65 @@ impl Z2mBackend {
             message_rx,
             message_tx,
             button_handlers,
+            rotaries: HashMap::new(),
+            dynamic_scenes: HashMap::new(),
+            pending_group_add: HashMap::new(),
+            pending_group_rename: HashMap::new(),
             socket: None,
             counter: 0,
+            transaction: 0,
         })
     }
 
+    /// Transaction id for the next request to z2m, to recognize the response
+    fn next_transaction(&mut self) -> String {
+        self.transaction += 1;
+        format!("bifrost-{}", self.transaction)
+    }
+
+    async fn connect_websocket(&self) -> ApiResult<Z2mTransport> {
+        let sanitized_url = self.server.get_sanitized_url();
+        let url = self.server.get_url();
+        // if tls verification is disabled, build a TlsConnector that explicitly
+        // does not check certificate validity. This is obviously neither safe
+        // nor recommended.
+        let connector = if self.server.disable_tls_verify.unwrap_or_default() {
+            log::warn!(
+                "[{}] TLS verification disabled; will accept any certificate!",
+                self.name
+            );
+            Some(Connector::NativeTls(
+                TlsConnector::builder()
+                    .danger_accept_invalid_certs(true)
+                    .build()?,
+            ))
+        } else {
+            None
+        };
+
+        log::info!("[{}] Connecting to {}", self.name, &sanitized_url);
+        match connect_async_tls_with_config(url.as_str(), None, false, connector.clone()).await {
+            Ok((socket, _)) => Ok(Z2mTransport::WebSocket(Box::new(socket))),
+            Err(err) => {
+                log::error!("[{}] Connect failed: {err:?}", self.name);
+                Err(err.into())
+            }
+        }
+    }
+
+    async fn connect_mqtt(&self) -> ApiResult<Z2mTransport> {
+        log::info!(
+            "[{}] Connecting to {}",
+            self.name,
+            self.server.get_sanitized_url()
+        );
+
+        let client_id = format!("bifrost-{}-{}", self.config.bridge.mac, self.name);
+        let mqtt = Z2mMqtt::connect(&self.server, client_id).await?;
+
+        Ok(Z2mTransport::Mqtt(mqtt))
+    }
+
     pub async fn event_loop(
         &mut self,
         chan: &mut Receiver<Arc<BackendRequest>>,
@@ -170,7 +241,13 @@ impl Service for Z2mBackend {
         let sanitized_url = self.server.get_sanitized_url();
         let url = self.server.get_url();
 
-        if url != self.server.url {
+        if self.server.is_mqtt() {
+            log::info!(
+                "[{}] Using mqtt broker, with base topic {:?}",
+                self.name,
+                self.server.get_base_topic()
+            );
+        } else if url != self.server.url {
             log::info!(
                 "[{}] Rewrote url for compatibility with z2m 2.x.",
                 self.name
@@ -185,36 +262,12 @@ impl Service for Z2mBackend {
     }
 
     async fn run(&mut self) -> ApiResult<()> {
-        let sanitized_url = self.server.get_sanitized_url();
-        let url = self.server.get_url();
-        // if tls verification is disabled, build a TlsConnector that explicitly
-        // does not check certificate validity. This is obviously neither safe
-        // nor recommended.
-        let connector = if self.server.disable_tls_verify.unwrap_or_default() {
-            log::warn!(
-                "[{}] TLS verification disabled; will accept any certificate!",
-                self.name
-            );
-            Some(Connector::NativeTls(
-                TlsConnector::builder()
-                    .danger_accept_invalid_certs(true)
-                    .build()?,
-            ))
+        let socket = if self.server.is_mqtt() {
+            self.connect_mqtt().await?
         } else {
-            None
+            self.connect_websocket().await?
         };
 
-        log::info!("[{}] Connecting to {}", self.name, &sanitized_url);
-        let socket =
-            match connect_async_tls_with_config(url.as_str(), None, false, connector.clone()).await
-            {
-                Ok((socket, _)) => socket,
-                Err(err) => {
-                    log::error!("[{}] Connect failed: {err:?}", self.name);
-                    return Err(err.into());
-                }
-            };
-
         let z2m_socket = Z2mWebSocket::new(self.name.clone(), socket);
         let mut chan = self.state.lock().await.backend_event_stream();
         self.event_loop(&mut chan, z2m_socket).await
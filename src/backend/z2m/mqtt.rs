@@ -0,0 +1,209 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use futures::Stream;
+use rumqttc::{AsyncClient, ConnectionError, Event, MqttOptions, Packet, Publish, QoS};
+use serde_json::Value;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use bifrost_api::config::Z2mServer;
+use z2m::api::RawMessage;
+
+use crate::error::{ApiError, ApiResult};
+
+/// Connection to the mqtt broker that zigbee2mqtt publishes to
+///
+/// Messages are translated to and from the same `{topic, payload}` form that
+/// the z2m frontend websocket uses, with topics relative to the base topic.
+pub struct Z2mMqtt {
+    client: AsyncClient,
+    base_topic: String,
+    rx: mpsc::UnboundedReceiver<Result<Publish, ConnectionError>>,
+    poller: JoinHandle<()>,
+}
+
+impl Z2mMqtt {
+    /// Maximum size of incoming messages. The device list in
+    /// `bridge/devices` easily exceeds the default limit of rumqttc.
+    const MAX_PACKET_SIZE: usize = 16 * 1024 * 1024;
+
+    /// Number of outgoing requests that can be queued for the broker
+    const CAPACITY: usize = 64;
+
+    pub async fn connect(server: &Z2mServer, client_id: String) -> ApiResult<Self> {
+        let url = &server.url;
+        let host = url
+            .host_str()
+            .ok_or_else(|| ApiError::InvalidMqttUrl(url.clone()))?;
+        let port = url.port().unwrap_or(Z2mServer::DEFAULT_MQTT_PORT);
+
+        let mut opts = MqttOptions::new(client_id, host, port);
+        opts.set_keep_alive(Duration::from_secs(30));
+        opts.set_max_packet_size(Self::MAX_PACKET_SIZE, Self::MAX_PACKET_SIZE);
+
+        if !url.username().is_empty() {
+            opts.set_credentials(url.username(), url.password().unwrap_or_default());
+        }
+
+        let (client, mut eventloop) = AsyncClient::new(opts, Self::CAPACITY);
+
+        let base_topic = server.get_base_topic().to_string();
+
+        // sent as soon as the connection is up. retained messages (like
+        // `bridge/devices`) are delivered right after subscribing.
+        client
+            .subscribe(format!("{base_topic}/#"), QoS::AtMostOnce)
+            .await?;
+
+        // the event loop has to be polled continuously, to keep the
+        // connection alive while messages are being sent
+        let (tx, rx) = mpsc::unbounded_channel();
+        let poller = tokio::spawn(async move {
+            loop {
+                match eventloop.poll().await {
+                    Ok(Event::Incoming(Packet::Publish(publish))) => {
+                        if tx.send(Ok(publish)).is_err() {
+                            return;
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(err) => {
+                        let _ = tx.send(Err(err));
+                        return;
+                    }
+                }
+            }
+        });
+
+        Ok(Self {
+            client,
+            base_topic,
+            rx,
+            poller,
+        })
+    }
+
+    pub async fn publish(&self, msg: &RawMessage) -> ApiResult<()> {
+        let topic = format!("{}/{}", self.base_topic, msg.topic);
+        let payload = serde_json::to_vec(&msg.payload)?;
+
+        Ok(self
+            .client
+            .publish(topic, QoS::AtMostOnce, false, payload)
+            .await?)
+    }
+
+    /// Returns true for topics that carry requests *to* zigbee2mqtt. Since we
+    /// subscribe to everything under the base topic, we also receive our own
+    /// (and others') requests, which the websocket would never forward.
+    fn is_request(topic: &str) -> bool {
+        topic.starts_with("bridge/request/")
+            || topic.ends_with("/set")
+            || topic.ends_with("/get")
+            || topic.contains("/set/")
+            || topic.contains("/get/")
+    }
+
+    fn parse(base_topic: &str, publish: &Publish) -> Option<RawMessage> {
+        let topic = publish.topic.strip_prefix(base_topic)?.strip_prefix('/')?;
+
+        if Self::is_request(topic) {
+            return None;
+        }
+
+        // like the z2m frontend, pass on payloads that are not valid json
+        // (such as `online` in z2m 1.x) as strings
+        let payload = serde_json::from_slice(&publish.payload).unwrap_or_else(|_| {
+            Value::String(String::from_utf8_lossy(&publish.payload).into_owned())
+        });
+
+        Some(RawMessage {
+            topic: topic.to_string(),
+            payload,
+        })
+    }
+}
+
+impl Stream for Z2mMqtt {
+    type Item = ApiResult<RawMessage>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        loop {
+            match self.rx.poll_recv(cx) {
+                Poll::Ready(Some(Ok(publish))) => {
+                    if let Some(msg) = Self::parse(&self.base_topic, &publish) {
+                        return Poll::Ready(Some(Ok(msg)));
+                    }
+                }
+                Poll::Ready(Some(Err(err))) => return Poll::Ready(Some(Err(err.into()))),
+                Poll::Ready(None) => return Poll::Ready(None),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+impl Drop for Z2mMqtt {
+    fn drop(&mut self) {
+        self.poller.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rumqttc::{Publish, QoS};
+    use serde_json::json;
+
+    use crate::backend::z2m::mqtt::Z2mMqtt;
+
+    fn parse(base_topic: &str, topic: &str, payload: &[u8]) -> Option<(String, serde_json::Value)> {
+        let publish = Publish::new(topic, QoS::AtMostOnce, payload.to_vec());
+        Z2mMqtt::parse(base_topic, &publish).map(|msg| (msg.topic, msg.payload))
+    }
+
+    #[test]
+    fn strip_base_topic() {
+        assert_eq!(
+            parse("home/z2m", "home/z2m/kitchen/lamp", br#"{"state":"ON"}"#),
+            Some(("kitchen/lamp".to_string(), json!({"state": "ON"})))
+        );
+
+        // other base topics (including ones sharing a prefix) are ignored
+        assert_eq!(parse("home/z2m", "zigbee2mqtt/lamp", b"{}"), None);
+        assert_eq!(parse("home/z2m", "home/z2mx/lamp", b"{}"), None);
+        assert_eq!(parse("home/z2m", "home/z2m", b"{}"), None);
+    }
+
+    #[test]
+    fn skip_requests() {
+        for topic in [
+            "zigbee2mqtt/bridge/request/group/add",
+            "zigbee2mqtt/lamp/set",
+            "zigbee2mqtt/lamp/get",
+            "zigbee2mqtt/lamp/set/state",
+            "zigbee2mqtt/lamp/get/state",
+        ] {
+            assert_eq!(parse("zigbee2mqtt", topic, b"{}"), None);
+        }
+
+        assert!(
+            parse(
+                "zigbee2mqtt",
+                "zigbee2mqtt/bridge/response/group/add",
+                b"{}"
+            )
+            .is_some()
+        );
+        assert!(parse("zigbee2mqtt", "zigbee2mqtt/settings", b"{}").is_some());
+    }
+
+    #[test]
+    fn non_json_payload() {
+        assert_eq!(
+            parse("zigbee2mqtt", "zigbee2mqtt/lamp/availability", b"online"),
+            Some(("lamp/availability".to_string(), json!("online")))
+        );
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use hue::api::{RelativeRotaryDirection, RelativeRotaryRotation};
+use z2m::update::DeviceUpdate;
+
+/// Models that report rotation through the level control `action` values
+/// (`brightness_move_up` / `brightness_move_down`) instead of dedicated
+/// rotation values
+const LEVEL_ROTARY_MODELS: &[&str] = &["SYMFONISK Sound Controller"];
+
+/// Steps reported for a rotation, if z2m does not report a step size
+const DEFAULT_STEPS: u32 = 30;
+
+/// Duration reported for a rotation, if z2m does not report a transition time
+const DEFAULT_DURATION_MS: u32 = 400;
+
+/// How an `action` value of a rotary is handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RotaryAction {
+    Rotate(RelativeRotaryDirection),
+    /// Belongs to the rotary, but does not produce a report (like `rotate_stop`)
+    Ignore,
+}
+
+/// Rotary support for a z2m device, derived from the values of its `action`
+/// expose
+#[derive(Debug)]
+pub struct Z2mRotaryData {
+    mappings: HashMap<String, RotaryAction>,
+}
+
+impl Z2mRotaryData {
+    /// Returns `None` if the device has no rotary
+    pub fn from_device(apidev: &z2m::api::Device) -> Option<Self> {
+        let model_id = apidev.model_id.as_deref().unwrap_or_default();
+        let values = apidev.action_values();
+
+        let mut mappings: HashMap<String, RotaryAction> = values
+            .iter()
+            .filter_map(|action| Some((action.to_string(), Self::parse_action(action)?)))
+            .collect();
+
+        if LEVEL_ROTARY_MODELS.contains(&model_id) {
+            for action in &values {
+                let mapping = match *action {
+                    "brightness_move_up" => {
+                        RotaryAction::Rotate(RelativeRotaryDirection::ClockWise)
+                    }
+                    "brightness_move_down" => {
+                        RotaryAction::Rotate(RelativeRotaryDirection::CounterClockWise)
+                    }
+                    "brightness_stop" => RotaryAction::Ignore,
+                    _ => continue,
+                };
+                mappings.insert(action.to_string(), mapping);
+            }
+        } else if !mappings.is_empty() {
+            // devices with dedicated rotation values (like the Hue Tap Dial)
+            // report the same rotation as level control commands too
+            for action in values.iter().filter(|v| v.starts_with("brightness_")) {
+                mappings
+                    .entry(action.to_string())
+                    .or_insert(RotaryAction::Ignore);
+            }
+        }
+
+        (!mappings.is_empty()).then_some(Self { mappings })
+    }
+
+    /// Parse dedicated rotation values, like `rotate_left` (IKEA SYMFONISK)
+    /// or `dial_rotate_right_fast` (Hue Tap Dial)
+    fn parse_action(action: &str) -> Option<RotaryAction> {
+        let rotation = action.strip_prefix("dial_").unwrap_or(action);
+        let direction = rotation.strip_prefix("rotate_")?;
+
+        match direction.split('_').next()? {
+            "right" => Some(RotaryAction::Rotate(RelativeRotaryDirection::ClockWise)),
+            "left" => Some(RotaryAction::Rotate(
+                RelativeRotaryDirection::CounterClockWise,
+            )),
+            "stop" => Some(RotaryAction::Ignore),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the `action` value belongs to the rotary (and should
+    /// not be handled as a button)
+    pub fn handles(&self, action: &str) -> bool {
+        self.mappings.contains_key(action)
+    }
+
+    /// The rotation reported by a device update, if any
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    pub fn rotation(&self, upd: &DeviceUpdate) -> Option<RelativeRotaryRotation> {
+        let action = upd.__.get("action").and_then(Value::as_str)?;
+        let RotaryAction::Rotate(mapped) = *self.mappings.get(action)? else {
+            return None;
+        };
+
+        let direction = match upd.__.get("action_direction").and_then(Value::as_str) {
+            Some("right" | "up") => RelativeRotaryDirection::ClockWise,
+            Some("left" | "down") => RelativeRotaryDirection::CounterClockWise,
+            _ => mapped,
+        };
+
+        let steps = Self::number(upd, "action_step_size").map_or(DEFAULT_STEPS, |steps| {
+            steps.round().clamp(1.0, 255.0) as u32
+        });
+
+        let duration = Self::number(upd, "action_transition_time")
+            .map_or(DEFAULT_DURATION_MS, |secs| {
+                (secs * 1000.0).round().clamp(0.0, f64::from(u16::MAX)) as u32
+            });
+
+        Some(RelativeRotaryRotation {
+            direction,
+            steps,
+            duration,
+        })
+    }
+
+    fn number(upd: &DeviceUpdate, key: &str) -> Option<f64> {
+        upd.__.get(key).and_then(Value::as_f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use hue::api::{RelativeRotaryDirection, RelativeRotaryRotation};
+    use z2m::update::DeviceUpdate;
+
+    use crate::backend::z2m::rotary::{RotaryAction, Z2mRotaryData};
+
+    fn tap_dial() -> Z2mRotaryData {
+        let mappings = [
+            "dial_rotate_left_step",
+            "dial_rotate_right_fast",
+            "brightness_step_up",
+        ]
+        .into_iter()
+        .map(|action| {
+            let mapping = Z2mRotaryData::parse_action(action).unwrap_or(RotaryAction::Ignore);
+            (action.to_string(), mapping)
+        })
+        .collect();
+
+        Z2mRotaryData { mappings }
+    }
+
+    #[test]
+    fn parse_action() {
+        assert_eq!(
+            Z2mRotaryData::parse_action("dial_rotate_left_step"),
+            Some(RotaryAction::Rotate(
+                RelativeRotaryDirection::CounterClockWise
+            ))
+        );
+        assert_eq!(
+            Z2mRotaryData::parse_action("rotate_right"),
+            Some(RotaryAction::Rotate(RelativeRotaryDirection::ClockWise))
+        );
+        assert_eq!(
+            Z2mRotaryData::parse_action("rotate_stop"),
+            Some(RotaryAction::Ignore)
+        );
+        assert_eq!(Z2mRotaryData::parse_action("button_1_press"), None);
+    }
+
+    #[test]
+    fn rotation() {
+        let rotary = tap_dial();
+
+        let upd: DeviceUpdate = serde_json::from_value(json!({
+            "action": "dial_rotate_right_fast",
+            "action_direction": "right",
+            "action_step_size": 71,
+        }))
+        .unwrap();
+
+        assert_eq!(
+            rotary.rotation(&upd),
+            Some(RelativeRotaryRotation {
+                direction: RelativeRotaryDirection::ClockWise,
+                steps: 71,
+                duration: 400,
+            })
+        );
+
+        // level control duplicates are claimed, but not reported
+        let upd: DeviceUpdate = serde_json::from_value(json!({
+            "action": "brightness_step_up",
+            "action_step_size": 71,
+        }))
+        .unwrap();
+
+        assert!(rotary.handles("brightness_step_up"));
+        assert_eq!(rotary.rotation(&upd), None);
+    }
+}
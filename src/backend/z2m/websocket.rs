@@ -4,23 +4,36 @@ use std::task::{Context, Poll};
 use futures::{SinkExt, Stream};
 use hue::zigbee::{HueZigbeeUpdate, ZigbeeMessage};
 use tokio::net::TcpStream;
-use tokio_tungstenite::tungstenite::{self, Message};
+use tokio_tungstenite::tungstenite::Message;
 use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
-use z2m::api::{DeviceRead, DeviceRemove, GroupMemberChange, PermitJoin};
+use z2m::api::{
+    DeviceOtaUpdate, DeviceRead, DeviceRemove, DeviceRename, GroupAdd, GroupMemberChange,
+    GroupRemove, GroupRename, PermitJoin,
+};
 use z2m::request::Z2mPayload;
 use z2m::update::DeviceUpdate;
 use z2m::{api::RawMessage, request::Z2mRequest};
 
+use crate::backend::z2m::mqtt::Z2mMqtt;
 use crate::backend::z2m::zclcommand::hue_zclcommand;
-use crate::error::ApiResult;
+use crate::error::{ApiError, ApiResult};
+
+/// The connection used to reach a z2m server
+pub enum Z2mTransport {
+    /// The websocket of the z2m frontend
+    WebSocket(Box<WebSocketStream<MaybeTlsStream<TcpStream>>>),
+    /// The mqtt broker z2m publishes to
+    Mqtt(Z2mMqtt),
+}
 
 pub struct Z2mWebSocket {
     pub name: String,
-    pub socket: WebSocketStream<MaybeTlsStream<TcpStream>>,
+    pub socket: Z2mTransport,
 }
 
 impl Z2mWebSocket {
-    pub const fn new(name: String, socket: WebSocketStream<MaybeTlsStream<TcpStream>>) -> Self {
+    #[must_use]
+    pub const fn new(name: String, socket: Z2mTransport) -> Self {
         Self { name, socket }
     }
 
@@ -39,6 +52,18 @@ impl Z2mWebSocket {
         /* ); */
 
         let api_req = match &payload {
+            Z2mRequest::GroupAdd(value) => RawMessage {
+                topic: "bridge/request/group/add".into(),
+                payload: serde_json::to_value(value)?,
+            },
+            Z2mRequest::GroupRemove(value) => RawMessage {
+                topic: "bridge/request/group/remove".into(),
+                payload: serde_json::to_value(value)?,
+            },
+            Z2mRequest::GroupRename(value) => RawMessage {
+                topic: "bridge/request/group/rename".into(),
+                payload: serde_json::to_value(value)?,
+            },
             Z2mRequest::GroupMemberAdd(value) => RawMessage {
                 topic: "bridge/request/group/members/add".into(),
                 payload: serde_json::to_value(value)?,
@@ -55,6 +80,14 @@ impl Z2mWebSocket {
                 topic: "bridge/request/device/remove".into(),
                 payload: serde_json::to_value(dev)?,
             },
+            Z2mRequest::DeviceRename(dev) => RawMessage {
+                topic: "bridge/request/device/rename".into(),
+                payload: serde_json::to_value(dev)?,
+            },
+            Z2mRequest::DeviceOtaUpdate(dev) => RawMessage {
+                topic: "bridge/request/device/ota_update/update".into(),
+                payload: serde_json::to_value(dev)?,
+            },
             Z2mRequest::DeviceRead(read) => RawMessage {
                 topic: format!("{topic}/get"),
                 payload: serde_json::to_value(read)?,
@@ -72,8 +105,10 @@ impl Z2mWebSocket {
             log::debug!("[{}] Sending {json}", self.name);
         }
 
-        let msg = Message::text(json);
-        Ok(self.socket.send(msg).await?)
+        match &mut self.socket {
+            Z2mTransport::WebSocket(socket) => Ok(socket.send(Message::text(json)).await?),
+            Z2mTransport::Mqtt(mqtt) => mqtt.publish(&api_req).await,
+        }
     }
 
     pub async fn send_scene_store(&mut self, topic: &str, name: &str, id: u32) -> ApiResult<()> {
@@ -82,6 +117,24 @@ impl Z2mWebSocket {
         self.send(topic, &z2mreq).await
     }
 
+    pub async fn send_scene_add(
+        &mut self,
+        topic: &str,
+        name: &str,
+        id: u32,
+        group_id: u32,
+        state: &DeviceUpdate,
+    ) -> ApiResult<()> {
+        let z2mreq = Z2mRequest::SceneAdd {
+            name,
+            id,
+            group_id,
+            state,
+        };
+
+        self.send(topic, &z2mreq).await
+    }
+
     pub async fn send_scene_recall(&mut self, topic: &str, index: u32) -> ApiResult<()> {
         let z2mreq = Z2mRequest::SceneRecall(index);
 
@@ -106,6 +159,44 @@ impl Z2mWebSocket {
         self.send(topic, &z2mreq).await
     }
 
+    pub async fn send_group_add(
+        &mut self,
+        friendly_name: &str,
+        transaction: &str,
+    ) -> ApiResult<()> {
+        let z2mreq = Z2mRequest::GroupAdd(GroupAdd {
+            id: None,
+            friendly_name: friendly_name.to_string(),
+            transaction: Some(transaction.to_string()),
+        });
+
+        self.send("", &z2mreq).await
+    }
+
+    pub async fn send_group_remove(&mut self, friendly_name: &str) -> ApiResult<()> {
+        let z2mreq = Z2mRequest::GroupRemove(GroupRemove {
+            id: friendly_name.to_string(),
+            force: false,
+        });
+
+        self.send("", &z2mreq).await
+    }
+
+    pub async fn send_group_rename(
+        &mut self,
+        from: &str,
+        to: &str,
+        transaction: &str,
+    ) -> ApiResult<()> {
+        let z2mreq = Z2mRequest::GroupRename(GroupRename {
+            from: from.to_string(),
+            to: to.to_string(),
+            transaction: Some(transaction.to_string()),
+        });
+
+        self.send("", &z2mreq).await
+    }
+
     pub async fn send_group_member_add(
         &mut self,
         topic: &str,
@@ -176,15 +267,40 @@ impl Z2mWebSocket {
 
         self.send("", &z2mreq).await
     }
+
+    pub async fn send_device_rename(&mut self, from: &str, to: &str) -> ApiResult<()> {
+        let z2mreq = Z2mRequest::DeviceRename(DeviceRename {
+            from: from.to_string(),
+            to: to.to_string(),
+            homeassistant_rename: false,
+        });
+
+        self.send("", &z2mreq).await
+    }
+
+    pub async fn send_device_ota_update(&mut self, id: &str) -> ApiResult<()> {
+        let z2mreq = Z2mRequest::DeviceOtaUpdate(DeviceOtaUpdate { id: id.to_string() });
+
+        self.send("", &z2mreq).await
+    }
 }
 
 impl Stream for Z2mWebSocket
 where
     Self: Unpin,
 {
-    type Item = Result<Message, tungstenite::Error>;
+    type Item = ApiResult<Message>;
 
     fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        WebSocketStream::poll_next(Pin::new(&mut self.socket), cx)
+        match &mut self.socket {
+            Z2mTransport::WebSocket(socket) => {
+                Pin::new(socket).poll_next(cx).map_err(ApiError::from)
+            }
+            // present mqtt messages exactly like the websocket does, so both
+            // are handled by the same code
+            Z2mTransport::Mqtt(mqtt) => Pin::new(mqtt)
+                .poll_next(cx)
+                .map(|item| item.map(|msg| Ok(Message::text(serde_json::to_string(&msg?)?)))),
+        }
     }
 }
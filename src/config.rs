@@ -1,17 +1,100 @@
 use camino::Utf8Path;
-use config::{Config, ConfigError};
+use config::{Config, ConfigError, Source};
 
 pub use bifrost_api::config::*;
 
+/// Url schemes supported for z2m servers
+const Z2M_URL_SCHEMES: [&str; 3] = ["ws", "wss", "mqtt"];
+
 pub fn parse(filename: &Utf8Path) -> Result<AppConfig, ConfigError> {
+    load(config::File::with_name(filename.as_str()))
+}
+
+fn load(source: impl Source + Send + Sync + 'static) -> Result<AppConfig, ConfigError> {
     let settings = Config::builder()
         .set_default("bifrost.state_file", "state.yaml")?
         .set_default("bifrost.cert_file", "cert.pem")?
         .set_default("bridge.http_port", 80)?
         .set_default("bridge.https_port", 443)?
         .set_default("bridge.entm_port", 2100)?
-        .add_source(config::File::with_name(filename.as_str()))
+        .add_source(source)
         .build()?;
 
-    settings.try_deserialize()
+    let config: AppConfig = settings.try_deserialize()?;
+
+    check_url_schemes(&config)?;
+
+    Ok(config)
+}
+
+/// Reject urls we cannot connect to, instead of failing at connection time
+/// (or worse, treating an mqtt url as a websocket)
+fn check_url_schemes(config: &AppConfig) -> Result<(), ConfigError> {
+    for (name, server) in &config.z2m.servers {
+        let scheme = server.url.scheme();
+        if !Z2M_URL_SCHEMES.contains(&scheme) {
+            return Err(ConfigError::Message(format!(
+                "z2m server {name:?}: unsupported url scheme {scheme:?} (expected ws://, wss:// or mqtt://. mqtts:// is not supported)"
+            )));
+        }
+    }
+
+    if let Some(hass) = &config.homeassistant {
+        let scheme = hass.url.scheme();
+        if scheme != "mqtt" {
+            return Err(ConfigError::Message(format!(
+                "homeassistant: unsupported url scheme {scheme:?} (expected mqtt://. mqtts:// is not supported)"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use config::{ConfigError, File, FileFormat};
+
+    use crate::config::{AppConfig, load};
+
+    const BASE: &str = "
+bridge:
+  name: Bifrost
+  mac: 00:11:22:33:44:55
+  ipaddress: 10.12.0.20
+  netmask: 255.255.255.0
+  gateway: 10.12.0.1
+  timezone: Europe/Copenhagen
+z2m:
+  server1:
+    url: ws://10.0.0.100:8080
+";
+
+    fn load_yaml(extra: &str) -> Result<AppConfig, ConfigError> {
+        load(File::from_str(&format!("{BASE}{extra}"), FileFormat::Yaml))
+    }
+
+    #[test]
+    fn z2m_url_schemes() {
+        for url in ["ws://10.0.0.100:8080", "wss://z2m.lan", "mqtt://10.0.0.100"] {
+            let config = load_yaml(&format!("  server2:\n    url: {url}\n")).unwrap();
+            assert!(config.z2m.servers["server2"].url.as_str().starts_with(url));
+        }
+
+        for url in ["mqtts://10.0.0.100", "http://10.0.0.100:8080"] {
+            let res = load_yaml(&format!("  server2:\n    url: {url}\n"));
+            assert!(matches!(res, Err(ConfigError::Message(_))));
+        }
+    }
+
+    #[test]
+    fn homeassistant_url_schemes() {
+        let config = load_yaml("homeassistant:\n  url: mqtt://10.0.0.100\n").unwrap();
+        assert!(config.homeassistant.is_some());
+
+        for url in ["mqtts://10.0.0.100", "http://10.0.0.100:8123"] {
+            let res = load_yaml(&format!("homeassistant:\n  url: {url}\n"));
+            assert!(matches!(res, Err(ConfigError::Message(_))));
+        }
+    }
 }
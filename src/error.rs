@@ -3,8 +3,8 @@ use std::num::{ParseIntError, TryFromIntError};
 use std::sync::Arc;
 
 use camino::Utf8PathBuf;
-use chrono::{DateTime, Local, NaiveTime, Weekday};
-use hue::api::RType;
+use chrono::Weekday;
+use hue::api::{RType, ResourceLink, configuration::TimePoint};
 use thiserror::Error;
 use tokio::task::JoinError;
 
@@ -75,6 +75,12 @@ pub enum ApiError {
     #[error(transparent)]
     TungsteniteError(#[from] tokio_tungstenite::tungstenite::Error),
 
+    #[error(transparent)]
+    MqttClientError(#[from] rumqttc::ClientError),
+
+    #[error(transparent)]
+    MqttConnectionError(#[from] rumqttc::ConnectionError),
+
     #[error(transparent)]
     X509DerError(#[from] der::Error),
 
@@ -121,6 +127,9 @@ pub enum ApiError {
     #[error("Unexpected eof on z2m socket")]
     UnexpectedZ2mEof,
 
+    #[error("Invalid mqtt url: {0}")]
+    InvalidMqttUrl(url::Url),
+
     #[error("Unexpected z2m message: {0:?}")]
     UnexpectedZ2mReply(tokio_tungstenite::tungstenite::Message),
 
@@ -128,6 +137,9 @@ pub enum ApiError {
     #[error("Failed to get firmware version reply from update server")]
     NoUpdateInformation,
 
+    #[error("Unauthorized user")]
+    Unauthorized,
+
     /* bifrost errors: routes */
     #[error("Creating object of type {0:?} is not yet supported by Bifrost")]
     CreateNotYetSupported(RType),
@@ -147,6 +159,12 @@ pub enum ApiError {
     #[error("Deleting object of type {0:?} is not allowed by hue protocol")]
     DeleteNotAllowed(RType),
 
+    #[error("Cannot create {0:?} without children")]
+    EmptyGroup(RType),
+
+    #[error("Unknown child {0:?}")]
+    UnknownChild(ResourceLink),
+
     /* bifrost errors */
     #[error("Missing auxiliary data resource {0:?}")]
     AuxNotFound(uuid::Uuid),
@@ -178,14 +196,8 @@ pub enum ApiError {
     #[error("Invalid zigbee message")]
     ZigbeeMessageError,
 
-    #[error("Invalid date time conversion {0:?}")]
-    InvalidDateTimeConversion(DateTime<Local>),
-
-    #[error("Invalid naive time")]
-    InvalidNaiveTime,
-
-    #[error("No next weekday occurence {0:?} {0:?}")]
-    NoNextWeekdayOccurence(NaiveTime, HashSet<Weekday>),
+    #[error("No next weekday occurence {0:?} {1:?}")]
+    NoNextWeekdayOccurence(TimePoint, HashSet<Weekday>),
 }
 
 impl From<SvcError> for ApiError {
@@ -127,6 +127,21 @@ async fn build_tasks(appstate: &AppState) -> ApiResult<()> {
     let svc = server::behavior_instance::BehaviorInstanceService::new(appstate.res.clone());
     mgr.register_service("behavior-instance", svc).await?;
 
+    let svc = server::smart_scene::SmartSceneService::new(appstate.res.clone());
+    mgr.register_service("smart-scene", svc).await?;
+
+    let svc = server::rules::RulesService::new(appstate.clone());
+    mgr.register_service("rules", svc).await?;
+
+    let svc = server::schedules::ScheduleService::new(appstate.clone());
+    mgr.register_service("schedules", svc).await?;
+
+    // register home assistant mqtt export, if configured
+    if let Some(conf) = &appstate.config().homeassistant {
+        let svc = server::homeassistant::HomeAssistantService::new(appstate.clone(), conf.clone());
+        mgr.register_service("homeassistant", svc).await?;
+    }
+
     // register all z2m backends as services
     let template = backend::z2m::Z2mServiceTemplate::new(appstate.clone());
     mgr.register_template("z2m", template).await?;
@@ -1,12 +1,17 @@
 use std::collections::BTreeMap;
 use std::io::Read;
 
+use chrono::{DateTime, Utc};
+use rand::Rng;
+use rand::distr::Alphanumeric;
 use serde::{Deserialize, Serialize};
 use serde_yml::Value;
 use uuid::Uuid;
 
-use hue::api::{DeviceArchetype, Resource};
+use hue::api::{DeviceArchetype, HueStreamKey, Resource};
 use hue::error::{HueError, HueResult};
+use hue::legacy_api::{ApiResourceLink, ApiRule, ApiSchedule, ApiSensor};
+use hue::sun::Sun;
 use hue::version::SwVersion;
 
 use crate::error::{ApiError, ApiResult};
@@ -17,6 +22,16 @@ pub struct AuxData {
     pub index: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub model_id: Option<String>,
+    /// The z2m group was created by bifrost (and not imported from z2m), so
+    /// it is removed from z2m when the room or zone is deleted
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub created: bool,
+    /// Location of the bridge (for the geolocation resource). This is never
+    /// reported back to clients, so it is kept here instead of in the resource.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub latitude: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub longitude: Option<f64>,
 }
 
 impl AuxData {
@@ -48,6 +63,31 @@ impl AuxData {
             ..self
         }
     }
+
+    #[must_use]
+    pub fn with_created(self) -> Self {
+        Self {
+            created: true,
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn with_location(self, latitude: Option<f64>, longitude: Option<f64>) -> Self {
+        Self {
+            latitude,
+            longitude,
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub const fn sun(&self) -> Option<Sun> {
+        match (self.latitude, self.longitude) {
+            (Some(lat), Some(lon)) => Some(Sun::new(lat, lon)),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize)]
@@ -84,6 +124,21 @@ impl IdMap {
         id
     }
 
+    /// Map `uuid` to the fixed `id`, unless that id already belongs to
+    /// something else. In that case, `uuid` keeps (or gets) a regular id.
+    pub fn add_fixed(&mut self, uuid: Uuid, id: u32) -> u32 {
+        match self.reverse.get(&id) {
+            Some(other) if *other == uuid => id,
+            Some(_) => self.add(uuid),
+            None => {
+                self.remove(&uuid);
+                self.forward.insert(uuid, id);
+                self.reverse.insert(id, uuid);
+                id
+            }
+        }
+    }
+
     #[must_use]
     pub fn id(&self, uuid: &Uuid) -> Option<u32> {
         self.forward.get(uuid).copied()
@@ -101,6 +156,51 @@ impl IdMap {
     }
 }
 
+/// An application key ("username") registered through `POST /api`
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ApiUser {
+    pub name: String,
+    pub app_id: Uuid,
+    pub created: DateTime<Utc>,
+    pub last_used: DateTime<Utc>,
+    /// Pre-shared key for entertainment (DTLS) streaming, if requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key: Option<HueStreamKey>,
+}
+
+impl ApiUser {
+    const USERNAME_LENGTH: usize = 40;
+
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        let now = Utc::now();
+        Self {
+            name: name.to_string(),
+            app_id: Uuid::new_v4(),
+            created: now,
+            last_used: now,
+            client_key: None,
+        }
+    }
+
+    #[must_use]
+    pub fn with_client_key(self) -> Self {
+        Self {
+            client_key: Some(HueStreamKey::new(rand::random())),
+            ..self
+        }
+    }
+
+    #[must_use]
+    pub fn generate_username() -> String {
+        rand::rng()
+            .sample_iter(Alphanumeric)
+            .take(Self::USERNAME_LENGTH)
+            .map(char::from)
+            .collect()
+    }
+}
+
 #[derive(Clone, Default, Debug, Serialize, Deserialize)]
 pub enum StateVersion {
     /// Version 0: (`res`, `aux`) tuple, no version field in state
@@ -119,6 +219,18 @@ pub struct State {
     version: StateVersion,
     aux: BTreeMap<Uuid, AuxData>,
     id_v1: IdMap,
+    #[serde(default)]
+    users: BTreeMap<String, ApiUser>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    rules: BTreeMap<u32, ApiRule>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    schedules: BTreeMap<u32, ApiSchedule>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    resourcelinks: BTreeMap<u32, ApiResourceLink>,
+    /// Virtual (CLIP) sensors created through the v1 api. These are numbered
+    /// through `id_v1`, to avoid clashing with sensors made from resources.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    clip_sensors: BTreeMap<Uuid, ApiSensor>,
     pub res: BTreeMap<Uuid, Resource>,
 }
 
@@ -201,6 +313,11 @@ impl State {
             version: StateVersion::V1,
             aux,
             id_v1,
+            users: BTreeMap::new(),
+            rules: BTreeMap::new(),
+            schedules: BTreeMap::new(),
+            resourcelinks: BTreeMap::new(),
+            clip_sensors: BTreeMap::new(),
             res,
         })
     }
@@ -279,6 +396,132 @@ impl State {
         Ok(())
     }
 
+    #[must_use]
+    pub fn user(&self, username: &str) -> Option<&ApiUser> {
+        self.users.get(username)
+    }
+
+    pub fn user_mut(&mut self, username: &str) -> Option<&mut ApiUser> {
+        self.users.get_mut(username)
+    }
+
+    #[must_use]
+    pub const fn users(&self) -> &BTreeMap<String, ApiUser> {
+        &self.users
+    }
+
+    pub fn add_user(&mut self, username: String, user: ApiUser) {
+        self.users.insert(username, user);
+    }
+
+    #[must_use]
+    pub const fn rules(&self) -> &BTreeMap<u32, ApiRule> {
+        &self.rules
+    }
+
+    pub fn rule_mut(&mut self, id: u32) -> HueResult<&mut ApiRule> {
+        self.rules.get_mut(&id).ok_or(HueError::V1NotFound(id))
+    }
+
+    #[must_use]
+    pub fn next_rule_id(&self) -> u32 {
+        (1..=u32::MAX)
+            .find(|id| !self.rules.contains_key(id))
+            .unwrap_or_default()
+    }
+
+    pub fn add_rule(&mut self, id: u32, rule: ApiRule) {
+        self.rules.insert(id, rule);
+    }
+
+    pub fn remove_rule(&mut self, id: u32) -> HueResult<ApiRule> {
+        self.rules.remove(&id).ok_or(HueError::V1NotFound(id))
+    }
+
+    #[must_use]
+    pub const fn schedules(&self) -> &BTreeMap<u32, ApiSchedule> {
+        &self.schedules
+    }
+
+    pub fn schedule_mut(&mut self, id: u32) -> HueResult<&mut ApiSchedule> {
+        self.schedules.get_mut(&id).ok_or(HueError::V1NotFound(id))
+    }
+
+    #[must_use]
+    pub fn next_schedule_id(&self) -> u32 {
+        (1..=u32::MAX)
+            .find(|id| !self.schedules.contains_key(id))
+            .unwrap_or_default()
+    }
+
+    pub fn add_schedule(&mut self, id: u32, schedule: ApiSchedule) {
+        self.schedules.insert(id, schedule);
+    }
+
+    pub fn remove_schedule(&mut self, id: u32) -> HueResult<ApiSchedule> {
+        self.schedules.remove(&id).ok_or(HueError::V1NotFound(id))
+    }
+
+    #[must_use]
+    pub const fn resourcelinks(&self) -> &BTreeMap<u32, ApiResourceLink> {
+        &self.resourcelinks
+    }
+
+    pub fn resourcelink_mut(&mut self, id: u32) -> HueResult<&mut ApiResourceLink> {
+        self.resourcelinks
+            .get_mut(&id)
+            .ok_or(HueError::V1NotFound(id))
+    }
+
+    #[must_use]
+    pub fn next_resourcelink_id(&self) -> u32 {
+        (1..=u32::MAX)
+            .find(|id| !self.resourcelinks.contains_key(id))
+            .unwrap_or_default()
+    }
+
+    pub fn add_resourcelink(&mut self, id: u32, link: ApiResourceLink) {
+        self.resourcelinks.insert(id, link);
+    }
+
+    pub fn remove_resourcelink(&mut self, id: u32) -> HueResult<ApiResourceLink> {
+        self.resourcelinks
+            .remove(&id)
+            .ok_or(HueError::V1NotFound(id))
+    }
+
+    #[must_use]
+    pub const fn clip_sensors(&self) -> &BTreeMap<Uuid, ApiSensor> {
+        &self.clip_sensors
+    }
+
+    pub fn clip_sensor_mut(&mut self, id: &Uuid) -> HueResult<&mut ApiSensor> {
+        self.clip_sensors.get_mut(id).ok_or(HueError::NotFound(*id))
+    }
+
+    pub fn add_clip_sensor(&mut self, sensor: ApiSensor) -> u32 {
+        let uuid = Uuid::new_v4();
+        self.clip_sensors.insert(uuid, sensor);
+        self.id_v1.add(uuid)
+    }
+
+    pub fn remove_clip_sensor(&mut self, id: &Uuid) -> HueResult<ApiSensor> {
+        let sensor = self
+            .clip_sensors
+            .remove(id)
+            .ok_or(HueError::NotFound(*id))?;
+        self.id_v1.remove(id);
+        Ok(sensor)
+    }
+
+    pub fn add_id_v1(&mut self, uuid: Uuid) -> u32 {
+        self.id_v1.add(uuid)
+    }
+
+    pub fn add_id_v1_fixed(&mut self, uuid: Uuid, id: u32) -> u32 {
+        self.id_v1.add_fixed(uuid, id)
+    }
+
     #[must_use]
     pub fn id_v1(&self, uuid: &Uuid) -> Option<u32> {
         self.id_v1.id(uuid)
@@ -300,3 +543,33 @@ fn migrate_mac_address(zbc_mac_address: &str) -> Result<String, ApiError> {
         .join(":");
     Ok(mac_address)
 }
+
+#[cfg(test)]
+mod tests {
+    use uuid::Uuid;
+
+    use crate::model::state::IdMap;
+
+    #[test]
+    fn idmap_add_fixed() {
+        let fixed = Uuid::new_v4();
+        let other = Uuid::new_v4();
+
+        // a fixed id is skipped by regular allocation
+        let mut map = IdMap::new();
+        assert_eq!(map.add_fixed(fixed, 1), 1);
+        assert_eq!(map.add(other), 0);
+        assert_eq!(map.add(Uuid::new_v4()), 2);
+
+        // an existing mapping is moved to the fixed id, if it is free
+        let mut map = IdMap::new();
+        assert_eq!(map.add(other), 0);
+        assert_eq!(map.add(fixed), 1);
+        assert_eq!(map.add_fixed(fixed, 5), 5);
+        assert_eq!(map.uuid(&1), None);
+
+        // but never taken from another resource
+        assert_eq!(map.add_fixed(fixed, 0), 5);
+        assert_eq!(map.uuid(&0), Some(other));
+    }
+}
@@ -1,29 +1,35 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
 use std::io::{Read, Write};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
+use chrono::Utc;
 use itertools::Itertools;
 use maplit::btreeset;
 use serde::Serialize;
 use serde_json::json;
 use tokio::sync::Notify;
 use tokio::sync::broadcast::{Receiver, Sender};
-use uuid::Uuid;
+use uuid::{Uuid, uuid};
 
 use bifrost_api::backend::BackendRequest;
 use hue::api::{
     BehaviorScript, Bridge, BridgeHome, Device, DeviceArchetype, DeviceProductData, DimmingUpdate,
-    Entertainment, EntertainmentConfiguration, GroupedLight, Light, Metadata, On, RType, Resource,
-    ResourceLink, ResourceRecord, Room, Stub, TimeZone, ZigbeeConnectivity,
-    ZigbeeConnectivityStatus, ZigbeeDeviceDiscovery, ZigbeeDeviceDiscoveryAction,
-    ZigbeeDeviceDiscoveryStatus, Zone,
+    Entertainment, EntertainmentConfiguration, Geolocation, GroupedLight, HueStreamKey, Light,
+    Metadata, On, RType, Resource, ResourceLink, ResourceRecord, Room, Stub, TimeZone,
+    ZigbeeConnectivity, ZigbeeConnectivityStatus, ZigbeeDeviceDiscovery,
+    ZigbeeDeviceDiscoveryAction, ZigbeeDeviceDiscoveryStatus, Zone,
 };
 use hue::error::{HueError, HueResult};
 use hue::event::EventBlock;
+use hue::legacy_api::{
+    ApiResourceLink, ApiResourceLinkNew, ApiRule, ApiRuleNew, ApiSchedule, ApiSensor,
+};
+use hue::sun::Sun;
 use hue::version::SwVersion;
 
-use crate::error::ApiResult;
-use crate::model::state::{AuxData, State};
+use crate::error::{ApiError, ApiResult};
+use crate::model::state::{ApiUser, AuxData, State};
 use crate::server::hueevents::HueEventStream;
 
 #[derive(Clone, Debug)]
@@ -33,12 +39,24 @@ pub struct Resources {
     state_updates: Arc<Notify>,
     backend_updates: Sender<Arc<BackendRequest>>,
     hue_event_stream: HueEventStream,
+    /// Time of the last press of the (virtual) link button
+    link_button: Option<Instant>,
 }
 
 impl Resources {
     const MAX_SCENE_ID: u32 = 100;
     const HUE_EVENTS_BUFFER_SIZE: usize = 128;
 
+    /// Pairing window opened by pressing the link button
+    const LINK_BUTTON_TIMEOUT: Duration = Duration::from_secs(30);
+
+    /// The built-in v1 daylight sensor has no resource, so it is numbered
+    /// through this fixed uuid instead
+    pub const DAYLIGHT_SENSOR_ID: Uuid = uuid!("a7a5a8a1-1d1b-4f5e-9e35-9b0d3c2a5f10");
+
+    /// The daylight sensor is always `/sensors/1` on a real bridge
+    const DAYLIGHT_SENSOR_ID_V1: u32 = 1;
+
     #[allow(clippy::new_without_default)]
     #[must_use]
     pub fn new(version: SwVersion, state: State) -> Self {
@@ -48,6 +66,7 @@ impl Resources {
             state_updates: Arc::new(Notify::new()),
             backend_updates: Sender::new(32),
             hue_event_stream: HueEventStream::new(Self::HUE_EVENTS_BUFFER_SIZE),
+            link_button: None,
         }
     }
 
@@ -91,6 +110,8 @@ impl Resources {
     }
 
     pub fn init(&mut self, bridge_id: &str) -> ApiResult<()> {
+        self.state
+            .add_id_v1_fixed(Self::DAYLIGHT_SENSOR_ID, Self::DAYLIGHT_SENSOR_ID_V1);
         self.add_bridge(bridge_id.to_owned())?;
         self.add_behavior_scripts()?;
         Ok(())
@@ -208,6 +229,22 @@ impl Resources {
         Ok(())
     }
 
+    /// Add a service resource, and link it to the given device.
+    ///
+    /// Devices imported before a service was supported are already known (and
+    /// are not replaced by [`Self::add`]), so the link is added here as well.
+    pub fn add_device_service(
+        &mut self,
+        device: &ResourceLink,
+        link: &ResourceLink,
+        obj: Resource,
+    ) -> ApiResult<()> {
+        self.add(link, obj)?;
+        self.update(&device.rid, |dev: &mut Device| {
+            dev.services.insert(*link);
+        })
+    }
+
     pub fn delete(&mut self, link: &ResourceLink) -> ApiResult<()> {
         log::info!("Deleting {link:?}..");
 
@@ -283,6 +320,7 @@ impl Resources {
         let link_zbdd = RType::ZigbeeDeviceDiscovery.deterministic(link_bridge.rid);
         let link_zbc = RType::ZigbeeConnectivity.deterministic(link_bridge.rid);
         let link_bhome_glight = RType::GroupedLight.deterministic(link_bridge_home.rid);
+        let link_geoloc = RType::Geolocation.deterministic(link_bridge.rid);
 
         let bridge_dev = Device {
             product_data: DeviceProductData::hue_bridge_v2(&self.version),
@@ -373,6 +411,7 @@ impl Resources {
         self.add(&link_zbc, Resource::ZigbeeConnectivity(zbc))?;
         self.add(&link_bridge_ent, Resource::Entertainment(brent))?;
         self.add(&link_bhome_glight, Resource::GroupedLight(bhome_glight))?;
+        self.add(&link_geoloc, Resource::Geolocation(Geolocation::default()))?;
 
         Ok(())
     }
@@ -481,7 +520,10 @@ impl Resources {
                 }
             }
 
-            Resource::Button(_) => Some(format!("/sensors/{id}")),
+            Resource::Button(_)
+            | Resource::LightLevel(_)
+            | Resource::Motion(_)
+            | Resource::Temperature(_) => Some(format!("/sensors/{id}")),
 
             Resource::EntertainmentConfiguration(_dev) => Some(format!("/groups/{id}")),
 
@@ -509,10 +551,8 @@ impl Resources {
             | Resource::GroupedLightLevel(_)
             | Resource::GroupedMotion(_)
             | Resource::Homekit(_)
-            | Resource::LightLevel(_)
             | Resource::Matter(_)
             | Resource::MatterFabric(_)
-            | Resource::Motion(_)
             | Resource::PrivateGroup(_)
             | Resource::PublicImage(_)
             | Resource::RelativeRotary(_)
@@ -520,7 +560,6 @@ impl Resources {
             | Resource::SmartScene(_)
             | Resource::Tamper(_)
             | Resource::Taurus(_)
-            | Resource::Temperature(_)
             | Resource::ZgpConnectivity(_)
             | Resource::ZigbeeConnectivity(_)
             | Resource::ZigbeeDeviceDiscovery(_)
@@ -532,6 +571,27 @@ impl Resources {
         ResourceRecord::new(*id, self.id_v1_scope(id, res), res.clone())
     }
 
+    /// Check that a new room or zone (`rtype`) has children, which are all
+    /// known. Otherwise, no backend would ever create it.
+    pub fn check_group_children(
+        &self,
+        rtype: RType,
+        children: &BTreeSet<ResourceLink>,
+    ) -> ApiResult<()> {
+        if children.is_empty() {
+            return Err(ApiError::EmptyGroup(rtype));
+        }
+
+        if let Some(child) = children
+            .iter()
+            .find(|child| self.get_resource(child).is_err())
+        {
+            return Err(ApiError::UnknownChild(*child));
+        }
+
+        Ok(())
+    }
+
     pub fn get_resource(&self, rlink: &ResourceLink) -> HueResult<ResourceRecord> {
         self.state
             .res
@@ -598,6 +658,301 @@ impl Resources {
         self.state.from_id_v1(&id).ok_or(HueError::V1NotFound(id))
     }
 
+    /// Assign a v1 id to a resource that the backend has been asked to
+    /// create, so it can be returned to v1 clients right away
+    pub fn reserve_id_v1(&mut self, uuid: Uuid) -> u32 {
+        let id = self.state.add_id_v1(uuid);
+        self.state_updates.notify_waiters();
+        id
+    }
+
+    /// Link to the device representing the bridge itself
+    pub fn get_bridge_device_link(&self) -> HueResult<ResourceLink> {
+        let id = self
+            .get_resource_ids_by_type(RType::Bridge)
+            .into_iter()
+            .next()
+            .ok_or(HueError::NotFound(Uuid::nil()))?;
+
+        Ok(self.get_id::<Bridge>(id)?.owner)
+    }
+
+    pub fn press_link_button(&mut self) {
+        self.link_button = Some(Instant::now());
+    }
+
+    #[must_use]
+    pub fn link_button_active(&self) -> bool {
+        self.link_button
+            .is_some_and(|pressed| pressed.elapsed() < Self::LINK_BUTTON_TIMEOUT)
+    }
+
+    /// Location of the bridge, if it has been configured (using the geolocation resource)
+    #[must_use]
+    pub fn sun(&self) -> Option<Sun> {
+        self.get_resource_ids_by_type(RType::Geolocation)
+            .into_iter()
+            .filter_map(|id| self.state.aux_get(&id).ok())
+            .find_map(AuxData::sun)
+    }
+
+    pub fn add_user(&mut self, name: &str, generate_client_key: bool) -> (String, &ApiUser) {
+        let username = ApiUser::generate_username();
+        log::info!("Registering new application key for {name:?}");
+
+        let mut user = ApiUser::new(name);
+        if generate_client_key {
+            user = user.with_client_key();
+        }
+
+        self.state.add_user(username.clone(), user);
+        self.state_updates.notify_waiters();
+
+        let user = &self.state.users()[&username];
+        (username, user)
+    }
+
+    /// Look up an application key, and mark it as used
+    ///
+    /// The usage timestamp is not considered a state change on its own, so it
+    /// will be persisted along with the next proper change.
+    pub fn authorize_user(&mut self, username: &str) -> Option<&ApiUser> {
+        let user = self.state.user_mut(username)?;
+        user.last_used = Utc::now();
+        Some(user)
+    }
+
+    #[must_use]
+    pub fn get_user(&self, username: &str) -> Option<&ApiUser> {
+        self.state.user(username)
+    }
+
+    /// The V1 username of an application, from its application id
+    #[must_use]
+    pub fn get_username_by_app_id(&self, app_id: &Uuid) -> Option<String> {
+        self.state
+            .users()
+            .iter()
+            .find(|(_, user)| user.app_id == *app_id)
+            .map(|(username, _)| username.clone())
+    }
+
+    #[must_use]
+    pub const fn get_users(&self) -> &BTreeMap<String, ApiUser> {
+        self.state.users()
+    }
+
+    /// Entertainment stream keys, indexed by every identity a client might
+    /// present: the application id (CLIP v2 clients), or the username (V1 clients)
+    #[must_use]
+    pub fn get_client_keys(&self) -> BTreeMap<String, HueStreamKey> {
+        let mut keys = BTreeMap::new();
+        for (username, user) in self.state.users() {
+            if let Some(key) = user.client_key {
+                keys.insert(username.clone(), key);
+                keys.insert(user.app_id.to_string(), key);
+            }
+        }
+        keys
+    }
+
+    #[must_use]
+    pub const fn get_rules(&self) -> &BTreeMap<u32, ApiRule> {
+        self.state.rules()
+    }
+
+    pub fn get_rule(&self, id: u32) -> HueResult<&ApiRule> {
+        self.state.rules().get(&id).ok_or(HueError::V1NotFound(id))
+    }
+
+    pub fn add_rule(&mut self, new: ApiRuleNew, owner: &str) -> u32 {
+        let id = self.state.next_rule_id();
+        self.state.add_rule(id, ApiRule::new(new, owner, id));
+        self.state_updates.notify_waiters();
+        id
+    }
+
+    pub fn update_rule(&mut self, id: u32, func: impl FnOnce(&mut ApiRule)) -> HueResult<()> {
+        func(self.state.rule_mut(id)?);
+        self.state_updates.notify_waiters();
+        Ok(())
+    }
+
+    pub fn delete_rule(&mut self, id: u32) -> HueResult<()> {
+        self.state.remove_rule(id)?;
+        self.state_updates.notify_waiters();
+        Ok(())
+    }
+
+    #[must_use]
+    pub const fn get_schedules(&self) -> &BTreeMap<u32, ApiSchedule> {
+        self.state.schedules()
+    }
+
+    pub fn get_schedule(&self, id: u32) -> HueResult<&ApiSchedule> {
+        self.state
+            .schedules()
+            .get(&id)
+            .ok_or(HueError::V1NotFound(id))
+    }
+
+    pub fn add_schedule(&mut self, schedule: ApiSchedule) -> u32 {
+        let id = self.state.next_schedule_id();
+        self.state.add_schedule(id, schedule);
+        self.state_updates.notify_waiters();
+        id
+    }
+
+    pub fn update_schedule(
+        &mut self,
+        id: u32,
+        func: impl FnOnce(&mut ApiSchedule),
+    ) -> HueResult<()> {
+        func(self.state.schedule_mut(id)?);
+        self.state_updates.notify_waiters();
+        Ok(())
+    }
+
+    pub fn delete_schedule(&mut self, id: u32) -> HueResult<()> {
+        self.state.remove_schedule(id)?;
+        self.state_updates.notify_waiters();
+        Ok(())
+    }
+
+    #[must_use]
+    pub const fn get_resourcelinks(&self) -> &BTreeMap<u32, ApiResourceLink> {
+        self.state.resourcelinks()
+    }
+
+    pub fn get_resourcelink(&self, id: u32) -> HueResult<&ApiResourceLink> {
+        self.state
+            .resourcelinks()
+            .get(&id)
+            .ok_or(HueError::V1NotFound(id))
+    }
+
+    pub fn add_resourcelink(&mut self, new: ApiResourceLinkNew, owner: &str) -> u32 {
+        let id = self.state.next_resourcelink_id();
+        self.state
+            .add_resourcelink(id, ApiResourceLink::new(new, owner, id));
+        self.state_updates.notify_waiters();
+        id
+    }
+
+    pub fn update_resourcelink(
+        &mut self,
+        id: u32,
+        func: impl FnOnce(&mut ApiResourceLink),
+    ) -> HueResult<()> {
+        func(self.state.resourcelink_mut(id)?);
+        self.state_updates.notify_waiters();
+        Ok(())
+    }
+
+    /// Delete a resourcelink, along with every linked rule, schedule, sensor
+    /// and resourcelink that is marked as `recycle`, unless another
+    /// resourcelink still links to it.
+    ///
+    /// Linked scenes and groups are always kept: they are v2 resources, which
+    /// have no `recycle` flag (and are reported to v1 clients without it).
+    pub fn delete_resourcelink(&mut self, id: u32) -> HueResult<()> {
+        let mut pending = self.state.remove_resourcelink(id)?.links;
+
+        while let Some(address) = pending.pop() {
+            if self
+                .state
+                .resourcelinks()
+                .values()
+                .any(|link| link.links.contains(&address))
+            {
+                continue;
+            }
+
+            let Some((rtype, id)) = address
+                .trim_start_matches('/')
+                .split_once('/')
+                .and_then(|(rtype, id)| Some((rtype, id.parse::<u32>().ok()?)))
+            else {
+                continue;
+            };
+
+            let recycle = match rtype {
+                "rules" => self.get_rule(id).is_ok_and(|rule| rule.recycle),
+                "schedules" => self.get_schedule(id).is_ok_and(|sched| sched.recycle),
+                "sensors" => self
+                    .get_clip_sensor(id)
+                    .is_ok_and(|sensor| sensor.recycle == Some(true)),
+                "resourcelinks" => self.get_resourcelink(id).is_ok_and(|link| link.recycle),
+                _ => false,
+            };
+
+            if !recycle {
+                continue;
+            }
+
+            log::debug!("Deleting {address}, which is no longer linked");
+            match rtype {
+                "rules" => self.state.remove_rule(id).map(drop)?,
+                "schedules" => self.state.remove_schedule(id).map(drop)?,
+                "sensors" => {
+                    let uuid = self.from_id_v1(id)?;
+                    self.state.remove_clip_sensor(&uuid).map(drop)?;
+                }
+                _ => pending.extend(self.state.remove_resourcelink(id)?.links),
+            }
+        }
+
+        self.state_updates.notify_waiters();
+        Ok(())
+    }
+
+    /// Virtual sensors, by v1 id
+    #[must_use]
+    pub fn get_clip_sensors(&self) -> Vec<(u32, &ApiSensor)> {
+        self.state
+            .clip_sensors()
+            .iter()
+            .filter_map(|(uuid, sensor)| Some((self.state.id_v1(uuid)?, sensor)))
+            .collect()
+    }
+
+    pub fn get_clip_sensor(&self, id: u32) -> HueResult<&ApiSensor> {
+        self.state
+            .clip_sensors()
+            .get(&self.from_id_v1(id)?)
+            .ok_or(HueError::V1NotFound(id))
+    }
+
+    pub fn add_clip_sensor(&mut self, sensor: ApiSensor) -> u32 {
+        let id = self.state.add_clip_sensor(sensor);
+        self.state_updates.notify_waiters();
+        id
+    }
+
+    pub fn update_clip_sensor<R>(
+        &mut self,
+        id: u32,
+        func: impl FnOnce(&mut ApiSensor) -> R,
+    ) -> HueResult<R> {
+        let uuid = self.from_id_v1(id)?;
+        let sensor = self
+            .state
+            .clip_sensor_mut(&uuid)
+            .map_err(|_| HueError::V1NotFound(id))?;
+        let res = func(sensor);
+        self.state_updates.notify_waiters();
+        Ok(res)
+    }
+
+    pub fn delete_clip_sensor(&mut self, id: u32) -> HueResult<()> {
+        let uuid = self.from_id_v1(id)?;
+        self.state
+            .remove_clip_sensor(&uuid)
+            .map_err(|_| HueError::V1NotFound(id))?;
+        self.state_updates.notify_waiters();
+        Ok(())
+    }
+
     #[must_use]
     pub fn state_channel(&self) -> Arc<Notify> {
         self.state_updates.clone()
@@ -627,3 +982,66 @@ impl Resources {
         self.backend_updates.clone()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use hue::version::SwVersion;
+
+    use crate::model::state::State;
+    use crate::resource::Resources;
+
+    fn add_rule(res: &mut Resources, recycle: bool) -> u32 {
+        let new = json!({"recycle": recycle, "conditions": [], "actions": []});
+        res.add_rule(serde_json::from_value(new).unwrap(), "user")
+    }
+
+    fn add_link(res: &mut Resources, recycle: bool, links: &[String]) -> u32 {
+        let new = json!({"classid": 1, "recycle": recycle, "links": links});
+        res.add_resourcelink(serde_json::from_value(new).unwrap(), "user")
+    }
+
+    #[test]
+    fn delete_resourcelink_recycles_members() {
+        let mut res = Resources::new(SwVersion::default(), State::new());
+
+        let recycled = add_rule(&mut res, true);
+        let kept = add_rule(&mut res, false);
+        let nested_rule = add_rule(&mut res, true);
+        let nested = add_link(&mut res, true, &[format!("/rules/{nested_rule}")]);
+        let link = add_link(
+            &mut res,
+            false,
+            &[
+                format!("/rules/{recycled}"),
+                format!("/rules/{kept}"),
+                format!("/resourcelinks/{nested}"),
+                "/scenes/1".to_string(),
+            ],
+        );
+
+        res.delete_resourcelink(link).unwrap();
+
+        assert!(res.get_resourcelink(link).is_err());
+        assert!(res.get_rule(recycled).is_err());
+        assert!(res.get_rule(kept).is_ok());
+        assert!(res.get_resourcelink(nested).is_err());
+        assert!(res.get_rule(nested_rule).is_err());
+    }
+
+    #[test]
+    fn delete_resourcelink_keeps_shared_members() {
+        let mut res = Resources::new(SwVersion::default(), State::new());
+
+        let rule = add_rule(&mut res, true);
+        let first = add_link(&mut res, false, &[format!("/rules/{rule}")]);
+        let second = add_link(&mut res, false, &[format!("/rules/{rule}")]);
+
+        res.delete_resourcelink(first).unwrap();
+        assert!(res.get_rule(rule).is_ok());
+
+        res.delete_resourcelink(second).unwrap();
+        assert!(res.get_rule(rule).is_err());
+    }
+}
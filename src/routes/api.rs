@@ -1,8 +1,9 @@
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 
 use axum::Router;
 use axum::extract::{Path, State};
-use axum::routing::{get, post, put};
+use axum::middleware;
+use axum::routing::{delete, get, post, put};
 use bytes::Bytes;
 use chrono::Utc;
 use hue::devicedb::gradient_product_data;
@@ -13,25 +14,31 @@ use tokio::sync::MutexGuard;
 
 use bifrost_api::backend::BackendRequest;
 use hue::api::{
-    Device, Entertainment, EntertainmentConfiguration, EntertainmentConfigurationAction,
-    EntertainmentConfigurationLocationsNew, EntertainmentConfigurationMetadata,
-    EntertainmentConfigurationNew, EntertainmentConfigurationServiceLocationsNew,
-    EntertainmentConfigurationType, EntertainmentConfigurationUpdate, GroupedLight,
-    GroupedLightUpdate, Light, LightUpdate, RType, ResourceLink, Room, Scene, SceneActive,
-    SceneStatus, SceneUpdate, V1Reply,
+    Button, Device, DevicePower, Entertainment, EntertainmentConfiguration,
+    EntertainmentConfigurationAction, EntertainmentConfigurationLocationsNew,
+    EntertainmentConfigurationMetadata, EntertainmentConfigurationNew,
+    EntertainmentConfigurationServiceLocationsNew, EntertainmentConfigurationType,
+    EntertainmentConfigurationUpdate, GroupedLight, GroupedLightUpdate, Light, LightLevel,
+    LightUpdate, Motion, RType, Resource, ResourceLink, Room, RoomMetadata, RoomMetadataUpdate,
+    RoomUpdate, Scene, SceneAction, SceneActionElement, SceneActive, SceneMetadata,
+    SceneMetadataUpdate, ScenePalette, SceneRecall, SceneStatus, SceneUpdate, Temperature, V1Reply,
+    ZigbeeConnectivity, ZigbeeConnectivityStatus, Zone,
 };
 use hue::error::{HueApiV1Error, HueError, HueResult};
 use hue::legacy_api::{
-    ApiGroup, ApiGroupAction, ApiGroupActionUpdate, ApiGroupClass, ApiGroupNew, ApiGroupState,
-    ApiGroupType, ApiGroupUpdate2, ApiLight, ApiLightStateUpdate, ApiResourceType, ApiScene,
-    ApiSceneAppData, ApiSceneType, ApiSceneVersion, ApiSensor, ApiUserConfig, Capabilities,
-    HueApiResult, NewUser, NewUserReply,
+    ApiConfigUpdate, ApiGroup, ApiGroupAction, ApiGroupActionUpdate, ApiGroupClass, ApiGroupNew,
+    ApiGroupState, ApiGroupType, ApiGroupUpdate2, ApiLight, ApiLightStateUpdate, ApiLightUpdate,
+    ApiMethod, ApiResourceLink, ApiResourceLinkNew, ApiResourceLinkUpdate, ApiResourceType,
+    ApiRule, ApiRuleNew, ApiRuleUpdate, ApiScene, ApiSceneAppData, ApiSceneNew, ApiSceneType,
+    ApiSceneUpdate, ApiSceneVersion, ApiSchedule, ApiScheduleNew, ApiScheduleUpdate, ApiSensor,
+    ApiSensorNew, ApiSensorUpdate, ApiUserConfig, Capabilities, HueApiResult, NewUser,
+    NewUserReply,
 };
 
 use crate::error::{ApiError, ApiResult};
 use crate::resource::Resources;
-use crate::routes::auth::{STANDARD_APPLICATION_ID, STANDARD_CLIENT_KEY};
-use crate::routes::clip::entertainment_configuration;
+use crate::routes::auth;
+use crate::routes::clip::{V2Reply, device, entertainment_configuration, room, scene, zone};
 use crate::routes::extractor::Json;
 use crate::routes::{ApiV1Error, ApiV1Result};
 use crate::server::appstate::AppState;
@@ -40,21 +47,41 @@ async fn get_api_config(State(state): State<AppState>) -> Json<impl Serialize> {
     Json(state.api_short_config().await)
 }
 
-async fn post_api(bytes: Bytes) -> ApiV1Result<Json<impl Serialize>> {
+async fn post_api(
+    State(state): State<AppState>,
+    bytes: Bytes,
+) -> ApiV1Result<Json<impl Serialize>> {
     info!("post: {bytes:?}");
     let json: NewUser = serde_json::from_slice(&bytes)?;
 
+    let mut lock = state.res.lock().await;
+
+    if !lock.link_button_active() {
+        warn!(
+            "Refusing to register {:?}: link button not pressed",
+            json.devicetype
+        );
+        return Err(HueApiV1Error::LinkButtonNotPressed)?;
+    }
+
+    let (username, user) = lock.add_user(&json.devicetype, json.generateclientkey);
+
     let res = NewUserReply {
-        clientkey: if json.generateclientkey {
-            Some(hex::encode_upper(STANDARD_CLIENT_KEY))
-        } else {
-            None
-        },
-        username: STANDARD_APPLICATION_ID.to_string(),
+        clientkey: user.client_key.map(hex::encode_upper),
+        username,
     };
+    drop(lock);
+
     Ok(Json(vec![HueApiResult::Success(res)]))
 }
 
+/// Devices are reachable, unless their zigbee connectivity reports otherwise
+fn is_reachable(res: &Resources, dev: &Device) -> bool {
+    dev.service(RType::ZigbeeConnectivity)
+        .and_then(|link| res.get::<ZigbeeConnectivity>(link).ok())
+        .is_none_or(|zigcon| zigcon.status == ZigbeeConnectivityStatus::Connected)
+}
+
 fn get_lights(res: &MutexGuard<Resources>) -> ApiResult<HashMap<String, ApiLight>> {
     let mut lights = HashMap::new();
 
@@ -63,7 +90,7 @@ fn get_lights(res: &MutexGuard<Resources>) -> ApiResult<HashMap<String, ApiLight
         let dev = res.get::<Device>(&light.owner)?;
         lights.insert(
             res.get_id_v1(rr.id)?,
-            ApiLight::from_dev_and_light(&rr.id, dev, &light),
+            ApiLight::from_dev_and_light(&rr.id, dev, &light, is_reachable(res, dev)),
         );
     }
 
@@ -100,6 +127,26 @@ fn get_groups(res: &MutexGuard<Resources>, group_0: bool) -> ApiResult<HashMap<S
         );
     }
 
+    for rr in res.get_resources_by_type(RType::Zone) {
+        let zone: Zone = rr.obj.try_into()?;
+        let uuid = zone
+            .grouped_light_service()
+            .ok_or(HueError::NotFound(rr.id))?;
+
+        let glight = res.get::<GroupedLight>(uuid)?;
+        let lights: Vec<String> = zone
+            .children
+            .iter()
+            .filter(|rl| rl.rtype == RType::Light)
+            .filter_map(|rl| res.get_id_v1(rl.rid).ok())
+            .collect();
+
+        rooms.insert(
+            res.get_id_v1(rr.id)?,
+            ApiGroup::from_lights_and_zone(glight, lights, zone),
+        );
+    }
+
     for rr in res.get_resources_by_type(RType::EntertainmentConfiguration) {
         let entconf: EntertainmentConfiguration = rr.obj.try_into()?;
 
@@ -143,7 +190,7 @@ fn get_groups(res: &MutexGuard<Resources>, group_0: bool) -> ApiResult<HashMap<S
                 state: ApiGroupState::default(),
                 stream: json!({
                     "active": entconf.active_streamer.is_some(),
-                    "owner": entconf.active_streamer.map(|_st| STANDARD_APPLICATION_ID.to_string()),
+                    "owner": entconf.active_streamer.and_then(|st| res.get_username_by_app_id(&st.rid)),
                     "proxymode": "auto",
                     "proxynode": "/bridge"
                 }),
@@ -154,6 +201,102 @@ fn get_groups(res: &MutexGuard<Resources>, group_0: bool) -> ApiResult<HashMap<S
     Ok(rooms)
 }
 
+fn get_sensors(res: &MutexGuard<Resources>) -> ApiResult<HashMap<u32, ApiSensor>> {
+    let (daylight, lastupdated) = res
+        .sun()
+        .map(|sun| sun.daylight(Utc::now()))
+        .map_or((None, None), |(daylight, since)| (Some(daylight), since));
+
+    let mut sensors = HashMap::from([(
+        res.get_id_v1_index(Resources::DAYLIGHT_SENSOR_ID)?,
+        ApiSensor::builtin_daylight_sensor(daylight, lastupdated),
+    )]);
+
+    for rr in res.get_resources_by_type(RType::Device) {
+        let dev: Device = rr.obj.try_into()?;
+
+        let battery = dev
+            .service(RType::DevicePower)
+            .and_then(|link| res.get::<DevicePower>(link).ok())
+            .and_then(|power| power.power_state.battery_level);
+
+        let buttons: Vec<&Button> = dev
+            .button_services()
+            .into_iter()
+            .filter_map(|link| res.get(link).ok())
+            .collect();
+
+        if !buttons.is_empty() {
+            sensors.insert(
+                res.get_id_v1_index(rr.id)?,
+                ApiSensor::from_dev_and_buttons(&rr.id, &dev, &buttons, battery),
+            );
+        }
+
+        for link in dev.services(RType::Motion) {
+            let motion = res.get::<Motion>(link)?;
+            sensors.insert(
+                res.get_id_v1_index(link.rid)?,
+                ApiSensor::from_dev_and_motion(&rr.id, &dev, motion, battery),
+            );
+        }
+
+        for link in dev.services(RType::LightLevel) {
+            let light_level = res.get::<LightLevel>(link)?;
+            sensors.insert(
+                res.get_id_v1_index(link.rid)?,
+                ApiSensor::from_dev_and_light_level(&rr.id, &dev, light_level, battery),
+            );
+        }
+
+        for link in dev.services(RType::Temperature) {
+            let temperature = res.get::<Temperature>(link)?;
+            sensors.insert(
+                res.get_id_v1_index(link.rid)?,
+                ApiSensor::from_dev_and_temperature(&rr.id, &dev, temperature, battery),
+            );
+        }
+    }
+
+    for (id, sensor) in res.get_clip_sensors() {
+        sensors.insert(id, sensor.clone());
+    }
+
+    Ok(sensors)
+}
+
+fn get_rules(res: &MutexGuard<Resources>) -> HashMap<u32, ApiRule> {
+    res.get_rules()
+        .iter()
+        .map(|(id, rule)| (*id, rule.clone()))
+        .collect()
+}
+
+fn get_schedules(res: &MutexGuard<Resources>) -> HashMap<u32, ApiSchedule> {
+    res.get_schedules()
+        .iter()
+        .map(|(id, schedule)| (*id, schedule.clone()))
+        .collect()
+}
+
+fn get_resourcelinks(res: &MutexGuard<Resources>) -> HashMap<u32, ApiResourceLink> {
+    res.get_resourcelinks()
+        .iter()
+        .map(|(id, link)| (*id, link.clone()))
+        .collect()
+}
+
+/// All v1 resources that rule conditions can refer to, as a single json
+/// document. This allows attributes to be looked up by their v1 address
+/// (e.g. `/sensors/2/state/buttonevent`) as a json pointer.
+pub fn get_v1_state(res: &MutexGuard<Resources>) -> ApiResult<Value> {
+    Ok(json!({
+        "lights": get_lights(res)?,
+        "groups": get_groups(res, true)?,
+        "sensors": get_sensors(res)?,
+    }))
+}
+
 pub fn get_scene(res: &Resources, owner: String, scene: &Scene) -> ApiV1Result<ApiScene> {
     let lights = scene
         .actions
@@ -218,31 +361,32 @@ async fn get_api_user(
     let lock = state.res.lock().await;
 
     Ok(Json(ApiUserConfig {
-        config: state.api_config(username.clone()).await?,
+        config: state.api_config(&lock).await?,
         groups: get_groups(&lock, false)?,
         lights: get_lights(&lock)?,
-        resourcelinks: HashMap::new(),
-        rules: HashMap::new(),
+        resourcelinks: get_resourcelinks(&lock),
+        rules: get_rules(&lock),
         scenes: get_scenes(&username, &lock)?,
-        schedules: HashMap::new(),
-        sensors: HashMap::from([(1, ApiSensor::builtin_daylight_sensor())]),
+        schedules: get_schedules(&lock),
+        sensors: get_sensors(&lock)?,
     }))
 }
 
+#[allow(clippy::significant_drop_tightening)]
 async fn get_api_user_resource(
     State(state): State<AppState>,
     Path((username, artype)): Path<(String, ApiResourceType)>,
 ) -> ApiV1Result<Json<Value>> {
     let lock = &state.res.lock().await;
     match artype {
-        ApiResourceType::Config => Ok(Json(json!(state.api_config(username).await?))),
+        ApiResourceType::Config => Ok(Json(json!(state.api_config(lock).await?))),
         ApiResourceType::Lights => Ok(Json(json!(get_lights(lock)?))),
         ApiResourceType::Groups => Ok(Json(json!(get_groups(lock, false)?))),
         ApiResourceType::Scenes => Ok(Json(json!(get_scenes(&username, lock)?))),
-        ApiResourceType::Resourcelinks
-        | ApiResourceType::Rules
-        | ApiResourceType::Schedules
-        | ApiResourceType::Sensors => Ok(Json(json!({}))),
+        ApiResourceType::Sensors => Ok(Json(json!(get_sensors(lock)?))),
+        ApiResourceType::Rules => Ok(Json(json!(get_rules(lock)))),
+        ApiResourceType::Schedules => Ok(Json(json!(get_schedules(lock)))),
+        ApiResourceType::Resourcelinks => Ok(Json(json!(get_resourcelinks(lock)))),
         ApiResourceType::Capabilities => Ok(Json(json!(Capabilities::new()))),
     }
 }
@@ -277,27 +421,224 @@ fn lights_v1_to_ec_locations(
     Ok(EntertainmentConfigurationLocationsNew { service_locations })
 }
 
+fn lights_v1_to_links(lights: &[String], res: &Resources) -> ApiResult<Vec<ResourceLink>> {
+    lights
+        .iter()
+        .map(|id| {
+            let uuid = res.from_id_v1(id.parse().map_err(ApiError::ParseIntError)?)?;
+            let link = RType::Light.link_to(uuid);
+            res.get::<Light>(&link)?;
+            Ok(link)
+        })
+        .collect()
+}
+
+/// Look up the room, zone or entertainment configuration behind a v1 group id
+fn group_v1_to_link(id: u32, res: &Resources) -> ApiV1Result<ResourceLink> {
+    let uuid = res.from_id_v1(id)?;
+    let rtype = res.get_resource_by_id(&uuid)?.obj.rtype();
+
+    match rtype {
+        RType::Room | RType::Zone | RType::EntertainmentConfiguration => {
+            Ok(ResourceLink::new(uuid, rtype))
+        }
+        _ => Err(HueError::V1NotFound(id))?,
+    }
+}
+
+/// Rooms contain devices rather than lights, so the members of a v1 group
+/// are translated to their devices. Devices without lights (e.g. sensors)
+/// are kept in the room.
+fn lights_to_room_children(
+    room: Option<&Room>,
+    lights: &[ResourceLink],
+    res: &Resources,
+) -> ApiResult<BTreeSet<ResourceLink>> {
+    let mut children: BTreeSet<ResourceLink> = room
+        .iter()
+        .flat_map(|room| &room.children)
+        .filter(|rl| {
+            res.get::<Device>(rl)
+                .is_ok_and(|dev| dev.light_service().is_none())
+        })
+        .copied()
+        .collect();
+
+    for link in lights {
+        children.insert(res.get::<Light>(link)?.owner);
+    }
+
+    Ok(children)
+}
+
+/// Scene actions for the given lights, from the lightstate given for each
+/// light, or otherwise from its current state
+fn scene_actions(
+    lights: &[String],
+    lightstates: &HashMap<String, ApiLightStateUpdate>,
+    res: &Resources,
+) -> ApiResult<Vec<SceneActionElement>> {
+    let links = lights_v1_to_links(lights, res)?;
+
+    lights
+        .iter()
+        .zip(links)
+        .map(|(id, target)| {
+            let action = match lightstates.get(id) {
+                Some(lightstate) => SceneAction::from(lightstate),
+                None => res.get::<Light>(&target)?.as_scene_action(),
+            };
+            Ok(SceneActionElement { action, target })
+        })
+        .collect()
+}
+
+/// Extract the link to the resource created by a v2 request
+fn created_link(mut reply: V2Reply<Value>, artype: ApiResourceType) -> ApiV1Result<ResourceLink> {
+    let data = reply
+        .data
+        .pop()
+        .ok_or(ApiV1Error::V1CreateUnsupported(artype))?;
+
+    Ok(serde_json::from_value(data)?)
+}
+
 async fn post_api_user_resource(
-    state: State<AppState>,
-    Path((_username, resource)): Path<(String, ApiResourceType)>,
+    State(state): State<AppState>,
+    Path((username, resource)): Path<(String, ApiResourceType)>,
     Json(req): Json<Value>,
 ) -> ApiV1Result<Json<Value>> {
-    // FIXME: these are copied from entertainment_configuration
+    match resource {
+        ApiResourceType::Groups => post_api_user_group(&state, req).await,
+        ApiResourceType::Scenes => post_api_user_scene(&state, req).await,
+        ApiResourceType::Rules => {
+            let rule_create: ApiRuleNew = serde_json::from_value(req)?;
+            info!("Create rule request: {rule_create:?}");
+
+            if rule_create.conditions.is_empty() || rule_create.actions.is_empty() {
+                return Err(HueApiV1Error::MissingParametersInBody)?;
+            }
+            if rule_create.conditions.len() > ApiRule::MAX_ITEMS
+                || rule_create.actions.len() > ApiRule::MAX_ITEMS
+            {
+                return Err(HueApiV1Error::TooManyItemsInList)?;
+            }
+            for cond in &rule_create.conditions {
+                cond.validate()?;
+            }
 
-    // We only know how to create entertainment groups
-    let ApiResourceType::Groups = resource else {
-        warn!("POST v1 user resource unsupported");
-        warn!("Request: {req:?}");
-        return Err(ApiV1Error::V1CreateUnsupported(resource));
-    };
+            let id = state.res.lock().await.add_rule(rule_create, &username);
+
+            log::info!("Success: created rule {id}");
+            Ok(Json(json!([{"success": {"id": id.to_string()}}])))
+        }
+        ApiResourceType::Schedules => {
+            let schedule_create: ApiScheduleNew = serde_json::from_value(req)?;
+            info!("Create schedule request: {schedule_create:?}");
+
+            let schedule = ApiSchedule::new(schedule_create)?;
+            let id = state.res.lock().await.add_schedule(schedule);
+
+            log::info!("Success: created schedule {id}");
+            Ok(Json(json!([{"success": {"id": id.to_string()}}])))
+        }
+        ApiResourceType::Resourcelinks => {
+            let link_create: ApiResourceLinkNew = serde_json::from_value(req)?;
+            info!("Create resourcelink request: {link_create:?}");
+
+            if link_create.links.len() > ApiResourceLink::MAX_LINKS {
+                return Err(HueApiV1Error::TooManyItemsInList)?;
+            }
+
+            let id = state
+                .res
+                .lock()
+                .await
+                .add_resourcelink(link_create, &username);
+
+            log::info!("Success: created resourcelink {id}");
+            Ok(Json(json!([{"success": {"id": id.to_string()}}])))
+        }
+        ApiResourceType::Sensors => {
+            let sensor_create: ApiSensorNew = serde_json::from_value(req)?;
+            info!("Create sensor request: {sensor_create:?}");
+
+            // Only virtual sensors can be created by clients
+            if !sensor_create.sensor_type.is_clip() {
+                return Err(HueApiV1Error::InvalidValueForParameter)?;
+            }
 
+            let sensor = ApiSensor::new_clip(sensor_create);
+            let id = state.res.lock().await.add_clip_sensor(sensor);
+
+            log::info!("Success: created sensor {id}");
+            Ok(Json(json!([{"success": {"id": id.to_string()}}])))
+        }
+        _ => {
+            warn!("POST v1 user resource unsupported");
+            warn!("Request: {req:?}");
+            Err(ApiV1Error::V1CreateUnsupported(resource))
+        }
+    }
+}
+
+async fn post_api_user_group(state: &AppState, req: Value) -> ApiV1Result<Json<Value>> {
     let group_create: ApiGroupNew = serde_json::from_value(req)?;
     info!("Create group request: {group_create:?}");
 
-    if group_create.group_type != ApiGroupType::Entertainment {
-        return Err(ApiV1Error::V1CreateUnsupported(resource));
+    if group_create.group_type == ApiGroupType::Entertainment {
+        return post_api_user_entertainment_group(state, group_create).await;
+    }
+
+    // z2m groups can not be created without members
+    if group_create.lights.is_empty() {
+        return Err(HueApiV1Error::InvalidValueForParameter)?;
     }
 
+    let lock = state.res.lock().await;
+    let lights = lights_v1_to_links(&group_create.lights, &lock)?;
+    let metadata = RoomMetadata::new(
+        group_create.class.into(),
+        group_create.name.as_deref().unwrap_or("Group"),
+    );
+
+    let resp = if group_create.group_type == ApiGroupType::Room {
+        let room = Room {
+            children: lights_to_room_children(None, &lights, &lock)?,
+            metadata,
+            services: BTreeSet::new(),
+        };
+        drop(lock);
+
+        log::debug!("Converted to V2 create request: {room:?}");
+        room::post_room(state, serde_json::to_value(room)?).await?
+    } else {
+        let zone = Zone {
+            children: lights.into_iter().collect(),
+            metadata,
+            services: BTreeSet::new(),
+        };
+        drop(lock);
+
+        log::debug!("Converted to V2 create request: {zone:?}");
+        zone::post_zone(state, serde_json::to_value(zone)?).await?
+    };
+
+    let rlink = created_link(resp.0, ApiResourceType::Groups)?;
+
+    // the room or zone is created by the backend, so reserve its id now
+    let id = state.res.lock().await.reserve_id_v1(rlink.rid);
+
+    log::info!("Success: created group {id} ({})", rlink.rid);
+    Ok(Json(json!([{"success": {"id": id.to_string()}}])))
+}
+
+async fn post_api_user_entertainment_group(
+    state: &AppState,
+    group_create: ApiGroupNew,
+) -> ApiV1Result<Json<Value>> {
+    // FIXME: these are copied from entertainment_configuration
+
     let lock = state.res.lock().await;
 
     let locations = lights_v1_to_ec_locations(&group_create.lights, &lock)?;
@@ -316,31 +657,123 @@ async fn post_api_user_resource(
     log::debug!("Converted to V2 create request: {ecnew:?}");
     drop(lock);
 
-    let mut resp =
-        entertainment_configuration::post_resource(&state, serde_json::to_value(ecnew)?).await?;
+    let resp =
+        entertainment_configuration::post_resource(state, serde_json::to_value(ecnew)?).await?;
 
-    // FIXME: ugly unpacking/repacking of post_resource result
-    if let Some(data) = resp.0.data.pop() {
-        let rlink: ResourceLink = serde_json::from_value(data)?;
+    let rlink = created_link(resp.0, ApiResourceType::Groups)?;
 
-        let id = state.res.lock().await.get_id_v1_index(rlink.rid)?;
+    let id = state.res.lock().await.get_id_v1_index(rlink.rid)?;
 
-        let response = json!([{"success": {"id": id}}]);
+    let response = json!([{"success": {"id": id}}]);
 
-        log::info!("Success: created {id} ({})", rlink.rid);
-        Ok(Json(response))
+    log::info!("Success: created {id} ({})", rlink.rid);
+    Ok(Json(response))
+}
+
+async fn post_api_user_scene(state: &AppState, req: Value) -> ApiV1Result<Json<Value>> {
+    let scene_create: ApiSceneNew = serde_json::from_value(req)?;
+    info!("Create scene request: {scene_create:?}");
+
+    let lock = state.res.lock().await;
+
+    let group = match &scene_create.group {
+        Some(group) => {
+            let link = group_v1_to_link(group.parse().map_err(ApiError::ParseIntError)?, &lock)?;
+            if link.rtype == RType::EntertainmentConfiguration {
+                return Err(HueApiV1Error::InvalidValueForParameter)?;
+            }
+            link
+        }
+        None if scene_create.scene_type == ApiSceneType::GroupScene => {
+            return Err(HueApiV1Error::MissingParametersInBody)?;
+        }
+        // v2 scenes always belong to a group, so light scenes are placed in
+        // the room of their first light
+        None => {
+            let first = lights_v1_to_links(&scene_create.lights, &lock)?
+                .into_iter()
+                .next()
+                .ok_or(HueApiV1Error::MissingParametersInBody)?;
+            let owner = lock.get::<Light>(&first)?.owner;
+
+            lock.get_resources_by_type(RType::Room)
+                .into_iter()
+                .find(
+                    |rr| matches!(&rr.obj, Resource::Room(room) if room.children.contains(&owner)),
+                )
+                .map(|rr| RType::Room.link_to(rr.id))
+                .ok_or(HueApiV1Error::InvalidValueForParameter)?
+        }
+    };
+
+    // group scenes without lights include every light in the group
+    let lights = if scene_create.lights.is_empty() {
+        get_groups(&lock, false)?
+            .remove(&lock.get_id_v1(group.rid)?)
+            .map(|grp| grp.lights)
+            .unwrap_or_default()
     } else {
-        Err(ApiV1Error::V1CreateUnsupported(resource))
-    }
+        scene_create.lights
+    };
+
+    let scene = Scene {
+        actions: scene_actions(&lights, &scene_create.lightstates, &lock)?,
+        auto_dynamic: false,
+        group,
+        metadata: SceneMetadata {
+            appdata: None,
+            image: None,
+            name: scene_create.name,
+        },
+        palette: ScenePalette::default(),
+        speed: 0.0,
+        status: None,
+        recall: SceneRecall::default(),
+    };
+    drop(lock);
+
+    log::debug!("Converted to V2 create request: {scene:?}");
+
+    let resp = scene::post_scene(state, serde_json::to_value(scene)?).await?;
+    let rlink = created_link(resp.0, ApiResourceType::Scenes)?;
+
+    // the scene is created by the backend, so reserve its id now
+    let id = state.res.lock().await.reserve_id_v1(rlink.rid);
+
+    log::info!("Success: created scene {id} ({})", rlink.rid);
+    Ok(Json(json!([{"success": {"id": id.to_string()}}])))
 }
 
 async fn put_api_user_resource(
-    Path((_username, _resource)): Path<(String, String)>,
+    State(state): State<AppState>,
+    Path((username, artype)): Path<(String, ApiResourceType)>,
     Json(req): Json<Value>,
-) -> ApiV1Result<Json<impl Serialize>> {
-    warn!("PUT v1 user resource {req:?}");
-    //Json(format!("user {username} resource {resource}"))
-    Ok(Json(vec![HueApiResult::Success(req)]))
+) -> ApiV1Result<Json<Value>> {
+    log::debug!("PUT v1 username={username} resource={artype:?}");
+
+    if artype != ApiResourceType::Config {
+        return Err(HueApiV1Error::MethodNotAvailableForResource)?;
+    }
+
+    let upd: ApiConfigUpdate = serde_json::from_value(req)?;
+
+    let mut lock = state.res.lock().await;
+    if upd.linkbutton == Some(true) {
+        lock.press_link_button();
+    }
+    let bridge = lock.get_bridge_device_link()?;
+    drop(lock);
+
+    if let Some(name) = &upd.name {
+        device::put_device(&state, bridge, json!({"metadata": {"name": name}})).await?;
+    }
+
+    let reply = V1Reply::new("/config".to_string())
+        .add_option("name", upd.name.as_ref())?
+        .add_option("linkbutton", upd.linkbutton)?
+        .json();
+
+    Ok(Json(reply))
 }
 
 #[allow(clippy::significant_drop_tightening)]
@@ -357,7 +790,12 @@ async fn get_api_user_resource_id(
             let light = lock.get::<Light>(&link)?;
             let dev = lock.get::<Device>(&light.owner)?;
 
-            json!(ApiLight::from_dev_and_light(&uuid, dev, light))
+            json!(ApiLight::from_dev_and_light(
+                &uuid,
+                dev,
+                light,
+                is_reachable(&lock, dev)
+            ))
         }
         ApiResourceType::Scenes => {
             let lock = state.res.lock().await;
@@ -376,13 +814,39 @@ async fn get_api_user_resource_id(
 
             json!(group)
         }
+        ApiResourceType::Sensors => {
+            let lock = state.res.lock().await;
+            let sensors = get_sensors(&lock)?;
+            let sensor = sensors.get(&id).ok_or(HueError::V1NotFound(id))?;
+
+            json!(sensor)
+        }
+        ApiResourceType::Rules => {
+            let lock = state.res.lock().await;
+
+            json!(lock.get_rule(id)?)
+        }
+        ApiResourceType::Schedules => {
+            let lock = state.res.lock().await;
+
+            json!(lock.get_schedule(id)?)
+        }
+        ApiResourceType::Resourcelinks => {
+            let lock = state.res.lock().await;
+
+            json!(lock.get_resourcelink(id)?)
+        }
         _ => Err(HueError::V1NotFound(id))?,
     };
 
     Ok(Json(result))
 }
 
-#[allow(clippy::significant_drop_tightening, clippy::single_match)]
+#[allow(
+    clippy::significant_drop_tightening,
+    clippy::single_match,
+    clippy::too_many_lines
+)]
 async fn put_api_user_resource_id(
     State(state): State<AppState>,
     Path((username, artype, id)): Path<(String, ApiResourceType, u32)>,
@@ -391,59 +855,285 @@ async fn put_api_user_resource_id(
     log::debug!("PUT v1 username={username} resource={artype:?} id={id}");
     log::debug!("JSON: {req:?}");
     match artype {
-        ApiResourceType::Groups => {
-            let upd: ApiGroupUpdate2 = serde_json::from_value(req)?;
+        ApiResourceType::Groups => put_api_user_group(&state, &username, id, req).await,
+        ApiResourceType::Lights => {
+            let upd: ApiLightUpdate = serde_json::from_value(req)?;
 
-            let mut v1res = V1Reply::for_group(id);
+            let lock = state.res.lock().await;
+            let uuid = lock.from_id_v1(id)?;
+            let dev = lock.get::<Light>(&RType::Light.link_to(uuid))?.owner;
+            drop(lock);
 
-            let mut ecupd = EntertainmentConfigurationUpdate::new();
+            // v1 light names are the names of their devices
+            if let Some(name) = &upd.name {
+                device::put_device(&state, dev, json!({"metadata": {"name": name}})).await?;
+            }
 
-            let lock = state.res.lock().await;
+            let reply = V1Reply::new(format!("/lights/{id}"))
+                .add_option("name", upd.name.as_ref())?
+                .json();
 
-            let uuid = lock.from_id_v1(id)?;
+            Ok(Json(reply))
+        }
+        ApiResourceType::Scenes => {
+            let upd: ApiSceneUpdate = serde_json::from_value(req)?;
 
-            ecupd.action = upd.stream.map(|stream| {
-                if stream.active {
-                    EntertainmentConfigurationAction::Start
+            let lock = state.res.lock().await;
+            let rlink = RType::Scene.link_to(lock.from_id_v1(id)?);
+            let scene = lock.get::<Scene>(&rlink)?;
+
+            let actions = if upd.lights.is_some() || upd.storelightstate {
+                let lights = match &upd.lights {
+                    Some(lights) => lights.clone(),
+                    None => scene
+                        .actions
+                        .iter()
+                        .map(|sae| lock.get_id_v1(sae.target.rid))
+                        .collect::<HueResult<_>>()?,
+                };
+
+                // unless the current light states are stored, lights
+                // already in the scene keep their lightstate
+                let lightstates = if upd.storelightstate {
+                    HashMap::new()
                 } else {
-                    EntertainmentConfigurationAction::Stop
-                }
-            });
+                    get_scene(&lock, username, scene)?.lightstates
+                };
+
+                Some(scene_actions(&lights, &lightstates, &lock)?)
+            } else {
+                None
+            };
+            drop(lock);
 
-            if let Some(lights) = &upd.lights {
-                ecupd.locations = Some(lights_v1_to_ec_locations(lights, &lock)?.into());
+            let scupd = SceneUpdate {
+                metadata: upd.name.as_ref().map(|name| SceneMetadataUpdate {
+                    name: Some(name.clone()),
+                    ..SceneMetadataUpdate::default()
+                }),
+                ..SceneUpdate::new()
             }
+            .with_actions(actions);
 
-            drop(lock);
+            scene::put_scene(&state, rlink, serde_json::to_value(scupd)?).await?;
 
-            let rlink = RType::EntertainmentConfiguration.link_to(uuid);
+            let reply = V1Reply::for_scene(id)
+                .add_option("name", upd.name.as_ref())?
+                .add_option("lights", upd.lights.as_ref())?
+                .add_option("storelightstate", upd.storelightstate.then_some(true))?
+                .json();
 
-            let resp = entertainment_configuration::put_resource_id(
-                &state,
-                rlink,
-                serde_json::to_value(&ecupd)?,
-            )
-            .await?;
+            Ok(Json(reply))
+        }
+        ApiResourceType::Rules => {
+            let upd: ApiRuleUpdate = serde_json::from_value(req)?;
 
-            if !resp.0.errors.is_empty() {
-                Err(HueApiV1Error::BridgeInternalError)?;
+            if let Some(conditions) = &upd.conditions {
+                if conditions.len() > ApiRule::MAX_ITEMS {
+                    return Err(HueApiV1Error::TooManyItemsInList)?;
+                }
+                for cond in conditions {
+                    cond.validate()?;
+                }
             }
+            if upd
+                .actions
+                .as_ref()
+                .is_some_and(|actions| actions.len() > ApiRule::MAX_ITEMS)
+            {
+                return Err(HueApiV1Error::TooManyItemsInList)?;
+            }
+
+            let reply = V1Reply::for_rule(id)
+                .add_option("name", upd.name.as_ref())?
+                .add_option("status", upd.status)?
+                .add_option("conditions", upd.conditions.as_ref())?
+                .add_option("actions", upd.actions.as_ref())?
+                .json();
+
+            state
+                .res
+                .lock()
+                .await
+                .update_rule(id, |rule| *rule += upd)?;
+
+            Ok(Json(reply))
+        }
+        ApiResourceType::Sensors => {
+            let upd: ApiSensorUpdate = serde_json::from_value(req)?;
+
+            let reply = V1Reply::for_sensor(id)
+                .add_option("name", upd.name.as_ref())?
+                .json();
+
+            // Only virtual sensors are stored as v1 objects
+            state.res.lock().await.update_clip_sensor(id, |sensor| {
+                if let Some(name) = upd.name {
+                    sensor.name = name;
+                }
+            })?;
+
+            Ok(Json(reply))
+        }
+        ApiResourceType::Schedules => {
+            let upd: ApiScheduleUpdate = serde_json::from_value(req)?;
 
-            if let Some(stream) = &upd.stream {
-                v1res = v1res.add("stream/active", stream.active)?;
+            if let Some(localtime) = upd.localtime() {
+                ApiSchedule::parse_localtime(&localtime)?;
             }
 
-            Ok(Json(v1res.json()))
+            let reply = V1Reply::for_schedule(id)
+                .add_option("name", upd.name.as_ref())?
+                .add_option("description", upd.description.as_ref())?
+                .add_option("command", upd.command.as_ref())?
+                .add_option("localtime", upd.localtime.as_ref())?
+                .add_option("time", upd.time.as_ref())?
+                .add_option("status", upd.status)?
+                .add_option("autodelete", upd.autodelete)?
+                .json();
+
+            state
+                .res
+                .lock()
+                .await
+                .update_schedule(id, |schedule| *schedule += upd)?;
+
+            Ok(Json(reply))
         }
-        ApiResourceType::Config
-        | ApiResourceType::Lights
-        | ApiResourceType::Resourcelinks
-        | ApiResourceType::Rules
-        | ApiResourceType::Scenes
-        | ApiResourceType::Schedules
-        | ApiResourceType::Sensors
-        | ApiResourceType::Capabilities => Err(ApiV1Error::V1CreateUnsupported(artype)),
+        ApiResourceType::Resourcelinks => {
+            let upd: ApiResourceLinkUpdate = serde_json::from_value(req)?;
+
+            if upd
+                .links
+                .as_ref()
+                .is_some_and(|links| links.len() > ApiResourceLink::MAX_LINKS)
+            {
+                return Err(HueApiV1Error::TooManyItemsInList)?;
+            }
+
+            let reply = V1Reply::for_resourcelink(id)
+                .add_option("name", upd.name.as_ref())?
+                .add_option("description", upd.description.as_ref())?
+                .add_option("classid", upd.classid)?
+                .add_option("links", upd.links.as_ref())?
+                .json();
+
+            state
+                .res
+                .lock()
+                .await
+                .update_resourcelink(id, |link| *link += upd)?;
+
+            Ok(Json(reply))
+        }
+        ApiResourceType::Config | ApiResourceType::Capabilities => {
+            Err(ApiV1Error::V1CreateUnsupported(artype))
+        }
+    }
+}
+
+async fn put_api_user_group(
+    state: &AppState,
+    username: &str,
+    id: u32,
+    req: Value,
+) -> ApiV1Result<Json<Value>> {
+    let upd: ApiGroupUpdate2 = serde_json::from_value(req)?;
+
+    let lock = state.res.lock().await;
+    let rlink = group_v1_to_link(id, &lock)?;
+
+    if rlink.rtype == RType::EntertainmentConfiguration {
+        drop(lock);
+        return put_api_user_entertainment_group(state, username, id, rlink, upd).await;
+    }
+
+    let children = match &upd.lights {
+        Some(lights) => {
+            let lights = lights_v1_to_links(lights, &lock)?;
+            if rlink.rtype == RType::Room {
+                let room = lock.get::<Room>(&rlink)?;
+                Some(lights_to_room_children(Some(room), &lights, &lock)?)
+            } else {
+                Some(lights.into_iter().collect())
+            }
+        }
+        None => None,
+    };
+    drop(lock);
+
+    let metadata = (upd.name.is_some() || upd.class.is_some()).then(|| RoomMetadataUpdate {
+        name: upd.name.clone(),
+        archetype: upd.class.map(Into::into),
+    });
+
+    let roomupd = RoomUpdate {
+        children,
+        metadata,
+        services: None,
+    };
+
+    log::debug!("Converted to V2 update request: {roomupd:?}");
+
+    let put = serde_json::to_value(&roomupd)?;
+    if rlink.rtype == RType::Room {
+        room::put_room(state, rlink, put).await?;
+    } else {
+        zone::put_zone(state, rlink, put).await?;
+    }
+
+    let reply = V1Reply::for_group(id)
+        .add_option("name", upd.name.as_ref())?
+        .add_option("class", upd.class)?
+        .add_option("lights", upd.lights.as_ref())?
+        .json();
+
+    Ok(Json(reply))
+}
+
+async fn put_api_user_entertainment_group(
+    state: &AppState,
+    username: &str,
+    id: u32,
+    rlink: ResourceLink,
+    upd: ApiGroupUpdate2,
+) -> ApiV1Result<Json<Value>> {
+    let mut v1res = V1Reply::for_group(id);
+
+    let mut ecupd = EntertainmentConfigurationUpdate::new();
+
+    ecupd.action = upd.stream.map(|stream| {
+        if stream.active {
+            EntertainmentConfigurationAction::Start
+        } else {
+            EntertainmentConfigurationAction::Stop
+        }
+    });
+
+    if let Some(lights) = &upd.lights {
+        let lock = state.res.lock().await;
+        ecupd.locations = Some(lights_v1_to_ec_locations(lights, &lock)?.into());
+        drop(lock);
+    }
+
+    let app = state.res.lock().await.get_user(username).cloned();
+    let resp = entertainment_configuration::put_resource_id(
+        state,
+        rlink,
+        serde_json::to_value(&ecupd)?,
+        app.as_ref(),
+    )
+    .await?;
+
+    if !resp.0.errors.is_empty() {
+        Err(HueApiV1Error::BridgeInternalError)?;
     }
+
+    if let Some(stream) = &upd.stream {
+        v1res = v1res.add("stream/active", stream.active)?;
+    }
+
+    Ok(Json(v1res.json()))
 }
 
 async fn put_api_user_resource_id_path(
@@ -481,11 +1171,13 @@ async fn put_api_user_resource_id_path(
 
             let lock = state.res.lock().await;
 
-            let uuid = lock.from_id_v1(id)?;
-            let link = ResourceLink::new(uuid, RType::Room);
-
-            let room: &Room = lock.get(&link)?;
-            let glight = room.grouped_light_service().unwrap();
+            let link = group_v1_to_link(id, &lock)?;
+            let glight = match lock.get_resource(&link)?.obj {
+                Resource::Room(room) => room.grouped_light_service().copied(),
+                Resource::Zone(zone) => zone.grouped_light_service().copied(),
+                _ => None,
+            }
+            .ok_or(HueError::V1NotFound(id))?;
 
             let updv1: ApiGroupActionUpdate = serde_json::from_value(req)?;
 
@@ -493,7 +1185,7 @@ async fn put_api_user_resource_id_path(
                 ApiGroupActionUpdate::LightUpdate(upd) => {
                     let updv2 = GroupedLightUpdate::from(&upd);
 
-                    lock.backend_request(BackendRequest::GroupedLightUpdate(*glight, updv2))?;
+                    lock.backend_request(BackendRequest::GroupedLightUpdate(glight, updv2))?;
                     drop(lock);
 
                     V1Reply::for_group_path(id, &path).with_light_state_update(&upd)?
@@ -548,16 +1240,184 @@ async fn put_api_user_resource_id_path(
             Ok(Json(reply.json()))
         }
 
+        ApiResourceType::Sensors => {
+            let Value::Object(upd) = req else {
+                return Err(HueApiV1Error::BodyContainsInvalidJson)?;
+            };
+
+            let mut lock = state.res.lock().await;
+            let updated = match path.as_str() {
+                "state" => lock.update_clip_sensor(id, |sensor| sensor.update_state(&upd))?,
+                "config" => lock.update_clip_sensor(id, |sensor| sensor.update_config(&upd))?,
+                _ => return Err(HueError::V1NotFound(id))?,
+            };
+            drop(lock);
+
+            let mut reply = V1Reply::for_sensor_path(id, &path);
+            for (name, value) in updated {
+                reply = reply.add(name, value)?;
+            }
+
+            Ok(Json(reply.json()))
+        }
+
         ApiResourceType::Config
         | ApiResourceType::Resourcelinks
         | ApiResourceType::Rules
         | ApiResourceType::Scenes
         | ApiResourceType::Schedules
-        | ApiResourceType::Sensors
         | ApiResourceType::Capabilities => Err(ApiV1Error::V1CreateUnsupported(artype)),
     }
 }
 
+async fn put_api_user_scene_lightstate(
+    State(state): State<AppState>,
+    Path((_username, id, light)): Path<(String, u32, u32)>,
+    Json(req): Json<Value>,
+) -> ApiV1Result<Json<Value>> {
+    let updv1: ApiLightStateUpdate = serde_json::from_value(req)?;
+
+    let lock = state.res.lock().await;
+    let rlink = RType::Scene.link_to(lock.from_id_v1(id)?);
+    let target = RType::Light.link_to(lock.from_id_v1(light)?);
+    lock.get::<Light>(&target)?;
+
+    let mut actions = lock.get::<Scene>(&rlink)?.actions.clone();
+    drop(lock);
+
+    if let Some(sae) = actions.iter_mut().find(|sae| sae.target == target) {
+        sae.action += &updv1;
+    } else {
+        actions.push(SceneActionElement {
+            action: SceneAction::from(&updv1),
+            target,
+        });
+    }
+
+    let upd = SceneUpdate::new().with_actions(Some(actions));
+    scene::put_scene(&state, rlink, serde_json::to_value(upd)?).await?;
+
+    let reply = V1Reply::for_scene_path(id, &format!("lightstates/{light}"))
+        .with_light_state_update(&updv1)?;
+
+    Ok(Json(reply.json()))
+}
+
+async fn delete_api_user_resource_id(
+    State(state): State<AppState>,
+    Path((username, artype, id)): Path<(String, ApiResourceType, u32)>,
+) -> ApiV1Result<Json<Value>> {
+    log::debug!("DELETE v1 username={username} resource={artype:?} id={id}");
+
+    let mut lock = state.res.lock().await;
+    let rtype = match artype {
+        ApiResourceType::Rules => {
+            lock.delete_rule(id)?;
+            "rules"
+        }
+        ApiResourceType::Sensors => {
+            lock.delete_clip_sensor(id)?;
+            "sensors"
+        }
+        ApiResourceType::Schedules => {
+            lock.delete_schedule(id)?;
+            "schedules"
+        }
+        ApiResourceType::Resourcelinks => {
+            lock.delete_resourcelink(id)?;
+            "resourcelinks"
+        }
+        ApiResourceType::Groups => {
+            let rlink = group_v1_to_link(id, &lock)?;
+            lock.backend_request(BackendRequest::Delete(rlink))?;
+            "groups"
+        }
+        ApiResourceType::Lights => {
+            // lights are removed by removing their device
+            let uuid = lock.from_id_v1(id)?;
+            let dev = lock.get::<Light>(&RType::Light.link_to(uuid))?.owner;
+            lock.backend_request(BackendRequest::Delete(dev))?;
+            "lights"
+        }
+        ApiResourceType::Scenes => {
+            let rlink = RType::Scene.link_to(lock.from_id_v1(id)?);
+            lock.get::<Scene>(&rlink)?;
+            lock.backend_request(BackendRequest::Delete(rlink))?;
+            "scenes"
+        }
+        ApiResourceType::Config | ApiResourceType::Capabilities => {
+            return Err(ApiV1Error::V1DeleteUnsupported(artype));
+        }
+    };
+    drop(lock);
+
+    Ok(Json(json!([{"success": format!("/{rtype}/{id} deleted")}])))
+}
+
+/// Perform a request against the v1 api on behalf of `username`, as done by
+/// rule actions and schedule commands.
+///
+/// The address is relative to the user (e.g. `/groups/0/action`), but may
+/// also include the `/api/<username>` prefix.
+pub async fn v1_request(
+    state: &AppState,
+    username: &str,
+    method: ApiMethod,
+    address: &str,
+    body: Value,
+) -> ApiV1Result<Value> {
+    let path = address
+        .strip_prefix("/api/")
+        .and_then(|rest| rest.split_once('/'))
+        .map_or(address, |(_user, path)| path)
+        .trim_start_matches('/');
+
+    let not_found = || HueApiV1Error::ResourceNotfound;
+    let mut parts = path.split('/');
+
+    let artype: ApiResourceType =
+        serde_json::from_value(json!(parts.next().ok_or_else(not_found)?))
+            .map_err(|_| not_found())?;
+    let id = parts.next().map(str::parse::<u32>).transpose();
+    let id = id.map_err(|_| not_found())?;
+    let key = parts.next().map(ToString::to_string);
+    let subkey = parts.next();
+
+    if parts.next().is_some() {
+        return Err(not_found())?;
+    }
+
+    let user = username.to_string();
+    let state = State(state.clone());
+
+    let Json(reply) = match (method, id, key, subkey) {
+        (ApiMethod::Post, None, None, None) => {
+            post_api_user_resource(state, Path((user, artype)), Json(body)).await?
+        }
+        (ApiMethod::Put, None, None, None) => {
+            put_api_user_resource(state, Path((user, artype)), Json(body)).await?
+        }
+        (ApiMethod::Put, Some(id), None, None) => {
+            put_api_user_resource_id(state, Path((user, artype, id)), Json(body)).await?
+        }
+        (ApiMethod::Put, Some(id), Some(key), None) => {
+            put_api_user_resource_id_path(state, Path((user, artype, id, key)), Json(body)).await?
+        }
+        (ApiMethod::Put, Some(id), Some(key), Some(light))
+            if artype == ApiResourceType::Scenes && key == "lightstates" =>
+        {
+            let light = light.parse().map_err(|_| not_found())?;
+            put_api_user_scene_lightstate(state, Path((user, id, light)), Json(body)).await?
+        }
+        (ApiMethod::Delete, Some(id), None, None) => {
+            delete_api_user_resource_id(state, Path((user, artype, id))).await?
+        }
+        _ => return Err(HueApiV1Error::MethodNotAvailableForResource)?,
+    };
+
+    Ok(reply)
+}
+
 /// This generates a workaround necessary for iConnectHue (iPhone app)
 ///
 /// For some reason, iConnectHue has been observed to try the endpoint GET /api/newUser,
@@ -568,20 +1428,32 @@ pub async fn workaround_iconnect_hue() -> ApiV1Result<()> {
     Err(HueApiV1Error::UnauthorizedUser)?
 }
 
-pub fn router() -> Router<AppState> {
-    Router::new()
-        .route("/", post(post_api))
-        .route("/config", get(get_api_config))
-        .route("/nouser/config", get(get_api_config))
-        .route("/newUser", get(workaround_iconnect_hue))
+pub fn router(appstate: &AppState) -> Router<AppState> {
+    let user_routes = Router::new()
         .route("/{user}", get(get_api_user))
         .route("/{user}/{rtype}", get(get_api_user_resource))
         .route("/{user}/{rtype}", post(post_api_user_resource))
         .route("/{user}/{rtype}", put(put_api_user_resource))
         .route("/{user}/{rtype}/{id}", get(get_api_user_resource_id))
         .route("/{user}/{rtype}/{id}", put(put_api_user_resource_id))
+        .route("/{user}/{rtype}/{id}", delete(delete_api_user_resource_id))
         .route(
             "/{user}/{rtype}/{id}/{key}",
             put(put_api_user_resource_id_path),
         )
+        .route(
+            "/{user}/scenes/{id}/lightstates/{light}",
+            put(put_api_user_scene_lightstate),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            appstate.clone(),
+            auth::require_username,
+        ));
+
+    Router::new()
+        .route("/", post(post_api))
+        .route("/config", get(get_api_config))
+        .route("/nouser/config", get(get_api_config))
+        .route("/newUser", get(workaround_iconnect_hue))
+        .merge(user_routes)
 }
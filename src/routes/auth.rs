@@ -1,27 +1,83 @@
 use axum::Router;
-use axum::http::HeaderValue;
-use axum::response::IntoResponse;
+use axum::extract::{Path, Request, State};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
 use axum::routing::get;
 use hyper::HeaderMap;
+use serde::Deserialize;
 use serde_json::json;
 
-use hue::api::HueStreamKey;
+use hue::error::HueApiV1Error;
 
+use crate::error::{ApiError, ApiResult};
+use crate::routes::ApiV1Result;
 use crate::routes::extractor::Json;
 use crate::server::appstate::AppState;
 
-pub const STANDARD_APPLICATION_ID: &str = "01010101-0202-0303-0404-050505050505";
+pub const HUE_APPLICATION_KEY: &str = "hue-application-key";
 
-/// This 16-byte key is used for all DTLS entertainment streams
-pub const STANDARD_CLIENT_KEY: HueStreamKey = HueStreamKey::new(*b"BifrostHueTlsKey");
+fn application_key(headers: &HeaderMap) -> ApiResult<&str> {
+    headers
+        .get(HUE_APPLICATION_KEY)
+        .and_then(|value| value.to_str().ok())
+        .ok_or(ApiError::Unauthorized)
+}
+
+pub async fn auth_v1(State(state): State<AppState>, headers: HeaderMap) -> ApiResult<Response> {
+    let key = application_key(&headers)?;
+
+    let app_id = state
+        .res
+        .lock()
+        .await
+        .authorize_user(key)
+        .ok_or(ApiError::Unauthorized)?
+        .app_id;
+
+    Ok((
+        [("hue-application-id", app_id.to_string())],
+        Json(json!({})),
+    )
+        .into_response())
+}
+
+/// Middleware for CLIP v2 routes: reject requests without a known `hue-application-key`
+pub async fn require_application_key(
+    State(state): State<AppState>,
+    mut request: Request,
+    next: Next,
+) -> ApiResult<Response> {
+    let key = application_key(request.headers())?;
 
-pub async fn auth_v1() -> impl IntoResponse {
-    let value = HeaderValue::from_static(STANDARD_APPLICATION_ID);
+    let Some(user) = state.res.lock().await.authorize_user(key).cloned() else {
+        log::warn!("Rejecting request with unknown application key");
+        return Err(ApiError::Unauthorized);
+    };
+
+    // handlers can find the requesting application through the extensions
+    request.extensions_mut().insert(user);
+
+    Ok(next.run(request).await)
+}
+
+#[derive(Deserialize)]
+pub struct UserPath {
+    user: String,
+}
 
-    let mut headers = HeaderMap::new();
-    headers.append("hue-application-id", value);
+/// Middleware for V1 routes: reject requests for `/api/{user}/..` with unknown usernames
+pub async fn require_username(
+    State(state): State<AppState>,
+    Path(path): Path<UserPath>,
+    request: Request,
+    next: Next,
+) -> ApiV1Result<Response> {
+    if state.res.lock().await.authorize_user(&path.user).is_none() {
+        log::warn!("Rejecting V1 request with unknown username");
+        return Err(HueApiV1Error::UnauthorizedUser)?;
+    }
 
-    (headers, Json(json!({})))
+    Ok(next.run(request).await)
 }
 
 pub fn router() -> Router<AppState> {
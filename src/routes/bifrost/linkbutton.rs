@@ -0,0 +1,44 @@
+use std::net::SocketAddr;
+
+use axum::Router;
+use axum::extract::{ConnectInfo, State};
+use axum::routing::get;
+
+use bifrost_api::linkbutton::LinkButton;
+
+use crate::routes::bifrost::{BifrostApiError, BifrostApiResult};
+use crate::routes::extractor::Json;
+use crate::server::appstate::AppState;
+
+async fn get_linkbutton(State(state): State<AppState>) -> BifrostApiResult<Json<LinkButton>> {
+    let active = state.res.lock().await.link_button_active();
+
+    Ok(Json(LinkButton { active }))
+}
+
+async fn post_linkbutton(
+    State(state): State<AppState>,
+    ConnectInfo(addr): ConnectInfo<SocketAddr>,
+) -> BifrostApiResult<Json<LinkButton>> {
+    // pressing the link button lets anyone pair a new application, so only
+    // allow it from the local host, unless enabled in the config
+    if !addr.ip().is_loopback() && !state.config().bifrost.remote_linkbutton {
+        log::warn!("Refused link button press from {addr}");
+        return Err(BifrostApiError::forbidden(
+            "Link button can only be pressed from localhost",
+        ));
+    }
+
+    log::info!("Link button pressed, accepting new applications");
+
+    let mut lock = state.res.lock().await;
+    lock.press_link_button();
+    let active = lock.link_button_active();
+    drop(lock);
+
+    Ok(Json(LinkButton { active }))
+}
+
+pub fn router() -> Router<AppState> {
+    Router::new().route("/", get(get_linkbutton).post(post_linkbutton))
+}
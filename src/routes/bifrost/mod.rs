@@ -1,4 +1,5 @@
 pub mod backend;
+pub mod linkbutton;
 pub mod service;
 pub mod websocket;
 
@@ -25,23 +26,29 @@ use crate::server::appstate::AppState;
 /// [`BifrostError`] comes from [`bifrost_api`], we can't implement
 /// [`IntoResponse`] for it, without gaining a dependency on [`axum`] for that
 /// crate. So for now, we use this thin wrapper for an [`IntoResponse`] impl.
-struct BifrostApiError(String);
+struct BifrostApiError(#[serde(skip)] StatusCode, String);
 
 type BifrostApiResult<T> = Result<T, BifrostApiError>;
 
 impl<E: Error> From<E> for BifrostApiError {
     fn from(value: E) -> Self {
-        Self(value.to_string())
+        Self(StatusCode::INTERNAL_SERVER_ERROR, value.to_string())
+    }
+}
+
+impl BifrostApiError {
+    fn forbidden(msg: impl Into<String>) -> Self {
+        Self(StatusCode::FORBIDDEN, msg.into())
     }
 }
 
 impl IntoResponse for BifrostApiError {
     fn into_response(self) -> Response {
-        log::error!("Request failed: {}", self.0);
+        log::error!("Request failed: {}", self.1);
 
-        let res = json!({"error": self.0});
+        let res = json!({"error": self.1});
 
-        (StatusCode::INTERNAL_SERVER_ERROR, Json(res)).into_response()
+        (self.0, Json(res)).into_response()
     }
 }
 
@@ -53,6 +60,7 @@ pub fn router() -> Router<AppState> {
     Router::new()
         .nest("/service", service::router())
         .nest("/backend", backend::router())
+        .nest("/linkbutton", linkbutton::router())
         .route("/config", get(get_config))
         .route("/ws", any(websocket))
 }
@@ -21,6 +21,10 @@ pub async fn put_device(state: &AppState, rlink: ResourceLink, put: Value) -> Ap
     }
 
     lock.update::<Device>(&rlink.rid, |obj| *obj += &upd)?;
+
+    if upd.metadata.is_some() {
+        lock.backend_request(BackendRequest::DeviceUpdate(rlink, upd))?;
+    }
     drop(lock);
 
     V2Reply::ok(rlink)
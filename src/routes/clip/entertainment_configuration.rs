@@ -2,7 +2,7 @@ use bifrost_api::backend::BackendRequest;
 use hue::devicedb::gradient_product_data;
 use hue::error::HueError;
 use serde_json::Value;
-use uuid::{Uuid, uuid};
+use uuid::Uuid;
 
 use hue::api::{
     Bridge, Device, Entertainment, EntertainmentConfiguration, EntertainmentConfigurationAction,
@@ -15,8 +15,8 @@ use hue::api::{
 };
 
 use crate::error::ApiResult;
+use crate::model::state::ApiUser;
 use crate::resource::Resources;
-use crate::routes::auth::STANDARD_APPLICATION_ID;
 use crate::routes::clip::{ApiV2Result, V2Reply};
 use crate::server::appstate::AppState;
 
@@ -159,7 +159,14 @@ fn find_bridge_entertainment(lock: &Resources) -> ApiResult<ResourceLink> {
     Ok(bridge_ent)
 }
 
-pub async fn put_resource_id(state: &AppState, rlink: ResourceLink, put: Value) -> ApiV2Result {
+/// Update an entertainment configuration. Streams started by an application
+/// (`app`) are reported as owned by it.
+pub async fn put_resource_id(
+    state: &AppState,
+    rlink: ResourceLink,
+    put: Value,
+    app: Option<&ApiUser>,
+) -> ApiV2Result {
     let upd: EntertainmentConfigurationUpdate = serde_json::from_value(put)?;
 
     let mut lock = state.res.lock().await;
@@ -235,8 +242,7 @@ pub async fn put_resource_id(state: &AppState, rlink: ResourceLink, put: Value)
         if let Some(action) = &upd.action {
             match action {
                 EntertainmentConfigurationAction::Start => {
-                    ec.active_streamer =
-                        Some(RType::AuthV1.link_to(uuid!(STANDARD_APPLICATION_ID)));
+                    ec.active_streamer = app.map(|app| RType::AuthV1.link_to(app.app_id));
                     ec.status = EntertainmentConfigurationStatus::Active;
                 }
                 EntertainmentConfigurationAction::Stop => {
@@ -0,0 +1,31 @@
+use chrono::Local;
+use serde_json::Value;
+
+use hue::api::{Geolocation, GeolocationUpdate, ResourceLink};
+
+use crate::routes::clip::{ApiV2Result, V2Reply};
+use crate::server::appstate::AppState;
+
+pub async fn put_geolocation(state: &AppState, rlink: ResourceLink, put: Value) -> ApiV2Result {
+    let upd: GeolocationUpdate = serde_json::from_value(put)?;
+
+    let mut lock = state.res.lock().await;
+    lock.get::<Geolocation>(&rlink)?;
+
+    // the coordinates are kept in the aux data, since the resource never
+    // reports them back
+    let aux = lock.aux_get(&rlink).cloned().unwrap_or_default();
+    let latitude = upd.latitude.or(aux.latitude);
+    let longitude = upd.longitude.or(aux.longitude);
+    let aux = aux.with_location(latitude, longitude);
+    let sun = aux.sun();
+    lock.aux_set(&rlink, aux);
+
+    let today = Local::now().date_naive();
+    lock.update::<Geolocation>(&rlink.rid, |geoloc| {
+        geoloc.update_sun_today(sun.as_ref(), today);
+    })?;
+    drop(lock);
+
+    V2Reply::ok(rlink)
+}
@@ -1,15 +1,20 @@
 pub mod behavior_instance;
 pub mod device;
+pub mod device_software_update;
 pub mod entertainment_configuration;
+pub mod geolocation;
 pub mod grouped_light;
 pub mod light;
 pub mod room;
 pub mod scene;
+pub mod smart_scene;
 pub mod zigbee_device_discovery;
+pub mod zone;
 
 use bifrost_api::backend::BackendRequest;
 use entertainment_configuration as ent_conf;
 
+use axum::Extension;
 use axum::Router;
 use axum::extract::{Path, State};
 use axum::routing::{delete, get, post, put};
@@ -18,6 +23,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
 use crate::error::{ApiError, ApiResult};
+use crate::model::state::ApiUser;
 use crate::routes::extractor::Json;
 use crate::server::appstate::AppState;
 
@@ -79,13 +85,12 @@ async fn post_resource(
         RType::EntertainmentConfiguration => ent_conf::post_resource(&state, req).await,
         RType::Scene => scene::post_scene(&state, req).await,
         RType::BehaviorInstance => behavior_instance::post_behavior_instance(&state, req).await,
+        RType::Room => room::post_room(&state, req).await,
+        RType::Zone => zone::post_zone(&state, req).await,
+        RType::SmartScene => smart_scene::post_smart_scene(&state, req).await,
 
         /* Not supported yet by Bifrost */
-        RType::GeofenceClient
-        | RType::Room
-        | RType::ServiceGroup
-        | RType::SmartScene
-        | RType::Zone => {
+        RType::GeofenceClient | RType::ServiceGroup => {
             let err = ApiError::CreateNotYetSupported(rtype);
             log::warn!("{err}");
             Err(err)
@@ -139,6 +144,7 @@ pub async fn get_resource_id(
 async fn put_resource_id(
     State(state): State<AppState>,
     Path(rlink): Path<ResourceLink>,
+    Extension(user): Extension<ApiUser>,
     Json(put): Json<Value>,
 ) -> ApiV2Result {
     log::info!("PUT {rlink:?}");
@@ -147,17 +153,25 @@ async fn put_resource_id(
     match rlink.rtype {
         /* Allowed + supported */
         RType::Device => device::put_device(&state, rlink, put).await,
-        RType::EntertainmentConfiguration => ent_conf::put_resource_id(&state, rlink, put).await,
+        RType::EntertainmentConfiguration => {
+            ent_conf::put_resource_id(&state, rlink, put, Some(&user)).await
+        }
         RType::GroupedLight => grouped_light::put_grouped_light(&state, rlink, put).await,
         RType::Light => light::put_light(&state, rlink, put).await,
         RType::Scene => scene::put_scene(&state, rlink, put).await,
         RType::Room => room::put_room(&state, rlink, put).await,
+        RType::Zone => zone::put_zone(&state, rlink, put).await,
+        RType::Geolocation => geolocation::put_geolocation(&state, rlink, put).await,
+        RType::SmartScene => smart_scene::put_smart_scene(&state, rlink, put).await,
         RType::ZigbeeDeviceDiscovery => {
             zigbee_device_discovery::put_zigbee_device_discovery(&state, rlink, put).await
         }
         RType::BehaviorInstance => {
             behavior_instance::put_behavior_instance(&state, rlink, put).await
         }
+        RType::DeviceSoftwareUpdate => {
+            device_software_update::put_device_software_update(&state, rlink, put).await
+        }
 
         /* Allowed, but support is missing in Bifrost */
         RType::Bridge
@@ -165,10 +179,8 @@ async fn put_resource_id(
         | RType::CameraMotion
         | RType::Contact
         | RType::DevicePower
-        | RType::DeviceSoftwareUpdate
         | RType::Entertainment
         | RType::GeofenceClient
-        | RType::Geolocation
         | RType::GroupedLightLevel
         | RType::GroupedMotion
         | RType::Homekit
@@ -177,11 +189,9 @@ async fn put_resource_id(
         | RType::Motion
         | RType::RelativeRotary
         | RType::ServiceGroup
-        | RType::SmartScene
         | RType::Temperature
         | RType::ZgpConnectivity
-        | RType::ZigbeeConnectivity
-        | RType::Zone => {
+        | RType::ZigbeeConnectivity => {
             /* check that the resource exists, otherwise we should return 404 */
             state.res.lock().await.get_resource(&rlink)?;
 
@@ -214,7 +224,7 @@ async fn delete_resource_id(
 
     match rlink.rtype {
         /* Allowed (delete from state) */
-        RType::BehaviorInstance => {
+        RType::BehaviorInstance | RType::SmartScene => {
             let mut lock = state.res.lock().await;
 
             /* check that the resource exists, otherwise we should return 404 */
@@ -234,7 +244,6 @@ async fn delete_resource_id(
         | RType::Room
         | RType::Scene
         | RType::ServiceGroup
-        | RType::SmartScene
         | RType::Zone => {
             let lock = state.res.lock().await;
 
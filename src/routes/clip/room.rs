@@ -1,20 +1,35 @@
 use serde_json::Value;
+use uuid::Uuid;
 
 use bifrost_api::backend::BackendRequest;
-use hue::api::{ResourceLink, Room, RoomUpdate};
+use hue::api::{RType, ResourceLink, Room, RoomUpdate};
 
 use crate::routes::clip::{ApiV2Result, V2Reply};
 use crate::server::appstate::AppState;
 
+pub async fn post_room(state: &AppState, req: Value) -> ApiV2Result {
+    let room: Room = serde_json::from_value(req)?;
+
+    let rlink = ResourceLink::new(Uuid::new_v4(), RType::Room);
+
+    let lock = state.res.lock().await;
+    lock.check_group_children(RType::Room, &room.children)?;
+    lock.backend_request(BackendRequest::RoomCreate(rlink, room))?;
+    drop(lock);
+
+    V2Reply::ok(rlink)
+}
+
 pub async fn put_room(state: &AppState, rlink: ResourceLink, put: Value) -> ApiV2Result {
     let mut lock = state.res.lock().await;
     lock.get::<Room>(&rlink)?;
 
-    let mut upd: RoomUpdate = serde_json::from_value(put)?;
+    let upd: RoomUpdate = serde_json::from_value(put)?;
 
-    if let Some(metadata) = upd.metadata.take() {
+    // metadata is updated right away, and renames are forwarded to the backend
+    if let Some(metadata) = &upd.metadata {
         lock.update(&rlink.rid, |room: &mut Room| {
-            room.metadata += &metadata;
+            room.metadata += metadata;
         })?;
     }
 
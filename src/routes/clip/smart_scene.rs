@@ -0,0 +1,55 @@
+use serde_json::Value;
+use uuid::Uuid;
+
+use hue::api::{
+    RType, Resource, ResourceLink, SmartScene, SmartSceneNew, SmartSceneRecallAction,
+    SmartSceneState, SmartSceneUpdate,
+};
+
+use crate::routes::clip::{ApiV2Result, V2Reply};
+use crate::server::appstate::AppState;
+
+pub async fn post_smart_scene(state: &AppState, post: Value) -> ApiV2Result {
+    let new: SmartSceneNew = serde_json::from_value(post)?;
+
+    let mut lock = state.res.lock().await;
+
+    /* check that the room or zone exists */
+    lock.get_resource(&new.group)?;
+
+    let active = new
+        .recall
+        .is_some_and(|recall| recall.action == SmartSceneRecallAction::Activate);
+
+    let obj = Resource::SmartScene(SmartScene {
+        active_timeslot: None,
+        group: new.group,
+        metadata: new.metadata,
+        state: if active {
+            SmartSceneState::Active
+        } else {
+            SmartSceneState::Inactive
+        },
+        transition_duration: new.transition_duration,
+        week_timeslots: new.week_timeslots,
+    });
+
+    let rlink = RType::SmartScene.link_to(Uuid::new_v4());
+
+    lock.add(&rlink, obj)?;
+    drop(lock);
+
+    V2Reply::ok(rlink)
+}
+
+pub async fn put_smart_scene(state: &AppState, rlink: ResourceLink, put: Value) -> ApiV2Result {
+    let upd: SmartSceneUpdate = serde_json::from_value(put)?;
+
+    state
+        .res
+        .lock()
+        .await
+        .update::<SmartScene>(&rlink.rid, |ss| *ss += &upd)?;
+
+    V2Reply::ok(rlink)
+}
@@ -0,0 +1,40 @@
+use serde_json::Value;
+use uuid::Uuid;
+
+use bifrost_api::backend::BackendRequest;
+use hue::api::{RType, ResourceLink, Zone, ZoneUpdate};
+
+use crate::routes::clip::{ApiV2Result, V2Reply};
+use crate::server::appstate::AppState;
+
+pub async fn post_zone(state: &AppState, req: Value) -> ApiV2Result {
+    let zone: Zone = serde_json::from_value(req)?;
+
+    let rlink = ResourceLink::new(Uuid::new_v4(), RType::Zone);
+
+    let lock = state.res.lock().await;
+    lock.check_group_children(RType::Zone, &zone.children)?;
+    lock.backend_request(BackendRequest::ZoneCreate(rlink, zone))?;
+    drop(lock);
+
+    V2Reply::ok(rlink)
+}
+
+pub async fn put_zone(state: &AppState, rlink: ResourceLink, put: Value) -> ApiV2Result {
+    let mut lock = state.res.lock().await;
+    lock.get::<Zone>(&rlink)?;
+
+    let upd: ZoneUpdate = serde_json::from_value(put)?;
+
+    if let Some(metadata) = &upd.metadata {
+        lock.update(&rlink.rid, |zone: &mut Zone| {
+            zone.metadata += metadata;
+        })?;
+    }
+
+    lock.backend_request(BackendRequest::ZoneUpdate(rlink, upd))?;
+
+    drop(lock);
+
+    V2Reply::ok(rlink)
+}
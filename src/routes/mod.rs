@@ -1,5 +1,6 @@
 use axum::Router;
 use axum::extract::DefaultBodyLimit;
+use axum::middleware;
 use axum::response::{IntoResponse, Response};
 use hue::error::{HueApiV1Error, HueError};
 use hue::legacy_api::ApiResourceType;
@@ -38,6 +39,9 @@ pub enum ApiV1Error {
 
     #[error("Cannot create resources of type: {0:?}")]
     V1CreateUnsupported(ApiResourceType),
+
+    #[error("Cannot delete resources of type: {0:?}")]
+    V1DeleteUnsupported(ApiResourceType),
 }
 
 impl ApiV1Error {
@@ -49,6 +53,7 @@ impl ApiV1Error {
             | Self::HueError(_)
             | Self::SerdeJsonError(_)
             | Self::V1CreateUnsupported(_)
+            | Self::V1DeleteUnsupported(_)
             | Self::HueApiV1(
                 HueApiV1Error::UnauthorizedUser
                 | HueApiV1Error::BodyContainsInvalidJson
@@ -59,7 +64,8 @@ impl ApiV1Error {
                 | HueApiV1Error::InvalidValueForParameter
                 | HueApiV1Error::ParameterNotModifiable
                 | HueApiV1Error::TooManyItemsInList
-                | HueApiV1Error::PortalConnectionIsRequired,
+                | HueApiV1Error::PortalConnectionIsRequired
+                | HueApiV1Error::LinkButtonNotPressed,
             ) => StatusCode::OK,
 
             Self::HueApiV1(HueApiV1Error::BridgeInternalError) => StatusCode::INTERNAL_SERVER_ERROR,
@@ -71,11 +77,14 @@ impl ApiV1Error {
             Self::HueError(HueError::V1NotFound(_) | HueError::WrongType(_, _)) => {
                 HueApiV1Error::ResourceNotfound.error_code()
             }
+            Self::HueError(HueError::InvalidTimePattern(_)) => {
+                HueApiV1Error::InvalidValueForParameter.error_code()
+            }
             Self::HueApiV1(err) => err.error_code(),
             Self::ApiError(_) | Self::HueError(_) | Self::SerdeJsonError(_) => {
                 HueApiV1Error::BridgeInternalError.error_code()
             }
-            Self::V1CreateUnsupported(_) => {
+            Self::V1CreateUnsupported(_) | Self::V1DeleteUnsupported(_) => {
                 HueApiV1Error::MethodNotAvailableForResource.error_code()
             }
         }
@@ -127,6 +136,7 @@ impl IntoResponse for ApiError {
                 | HueError::UuidError(_)
                 | HueError::HueEntertainmentBadHeader
                 | HueError::EffectDurationOutOfRange(_)
+                | HueError::InvalidTimePattern(_)
                 | HueError::HueZigbeeUnknownFlags(_) => StatusCode::BAD_REQUEST,
 
                 HueError::NotFound(_) | HueError::V1NotFound(_) | HueError::WrongType(_, _) => {
@@ -144,13 +154,16 @@ impl IntoResponse for ApiError {
 
             Self::AuxNotFound(_) => StatusCode::NOT_FOUND,
 
+            Self::EmptyGroup(_) | Self::UnknownChild(_) => StatusCode::BAD_REQUEST,
+
             Self::CreateNotAllowed(_) | Self::UpdateNotAllowed(_) | Self::DeleteNotAllowed(_) => {
                 StatusCode::METHOD_NOT_ALLOWED
             }
 
             Self::CreateNotYetSupported(_)
             | Self::UpdateNotYetSupported(_)
-            | Self::DeleteNotYetSupported(_) => StatusCode::FORBIDDEN,
+            | Self::DeleteNotYetSupported(_)
+            | Self::Unauthorized => StatusCode::FORBIDDEN,
 
             _ => StatusCode::INTERNAL_SERVER_ERROR,
         };
@@ -160,14 +173,19 @@ impl IntoResponse for ApiError {
 }
 
 pub fn router(appstate: AppState) -> Router<()> {
+    let auth_v2 = middleware::from_fn_with_state(appstate.clone(), auth::require_application_key);
+
     Router::new()
-        .nest("/api", api::router())
+        .nest("/api", api::router(&appstate))
         .nest("/auth", auth::router())
         .nest("/updater", updater::router())
         .nest("/licenses", licenses::router())
         .nest("/description.xml", upnp::router())
-        .nest("/clip/v2/resource", clip::router())
-        .nest("/eventstream", eventstream::router())
+        .nest(
+            "/clip/v2/resource",
+            clip::router().route_layer(auth_v2.clone()),
+        )
+        .nest("/eventstream", eventstream::router().route_layer(auth_v2))
         .nest("/bifrost", bifrost::router())
         .with_state(appstate)
         .layer(DefaultBodyLimit::max(100 * 1024 * 1024))
@@ -1,4 +1,3 @@
-use std::collections::HashMap;
 use std::fs::{self, File};
 use std::sync::Arc;
 
@@ -8,6 +7,7 @@ use tokio::sync::Mutex;
 use tokio::sync::broadcast::Sender;
 
 use bifrost_api::backend::BackendRequest;
+use hue::api::Device;
 use hue::legacy_api::{ApiConfig, ApiShortConfig, Whitelist};
 use svc::manager::SvmClient;
 
@@ -66,6 +66,11 @@ impl AppState {
                     State::from_v2(yaml)?
                 }
             };
+            if state.users().is_empty() {
+                log::warn!(
+                    "No paired applications found. Clients paired with an earlier version of Bifrost must pair again"
+                );
+            }
             res = Resources::new(swversion, state);
         } else {
             log::debug!("No state file found, initializing..");
@@ -109,24 +114,39 @@ impl AppState {
         ApiShortConfig::from_mac_and_version(mac, self.upd.lock().await.get().await)
     }
 
-    pub async fn api_config(&self, username: String) -> ApiResult<ApiConfig> {
+    pub async fn api_config(&self, res: &Resources) -> ApiResult<ApiConfig> {
         let tz = tzfile::Tz::named(&self.conf.bridge.timezone)?;
         let localtime = Utc::now().with_timezone(&&tz).naive_local();
 
+        let mut short_config = self.api_short_config().await;
+
+        // the bridge name is shared with the bridge device, so it can be
+        // changed from both api versions
+        if let Ok(link) = res.get_bridge_device_link()
+            && let Ok(dev) = res.get::<Device>(&link)
+        {
+            short_config.name.clone_from(&dev.metadata.name);
+        }
+
         let res = ApiConfig {
-            short_config: self.api_short_config().await,
+            short_config,
             ipaddress: self.conf.bridge.ipaddress,
             netmask: self.conf.bridge.netmask,
             gateway: self.conf.bridge.gateway,
             timezone: self.conf.bridge.timezone.clone(),
-            whitelist: HashMap::from([(
-                username,
-                Whitelist {
-                    create_date: Utc::now(),
-                    last_use_date: Utc::now(),
-                    name: "User#foo".to_string(),
-                },
-            )]),
+            whitelist: res
+                .get_users()
+                .iter()
+                .map(|(username, user)| {
+                    let entry = Whitelist {
+                        create_date: user.created,
+                        last_use_date: user.last_used,
+                        name: user.name.clone(),
+                    };
+                    (username.clone(), entry)
+                })
+                .collect(),
+            linkbutton: res.link_button_active(),
             localtime,
             ..ApiConfig::default()
         };
@@ -2,8 +2,7 @@ use std::collections::HashSet;
 use std::sync::Arc;
 
 use bifrost_api::backend::BackendRequest;
-use chrono::offset::LocalResult;
-use chrono::{DateTime, Datelike, Days, Local, NaiveTime, TimeZone, Weekday};
+use chrono::{DateTime, Datelike, Days, Local, NaiveDate, TimeDelta, Weekday};
 use itertools::Itertools;
 use tokio::spawn;
 use tokio::sync::Mutex;
@@ -14,6 +13,7 @@ use hue::api::{
     LightTimedEffect, LightTimedEffectsUpdate, LightUpdate, On, RType, Resource, ResourceLink,
     Room, WakeupConfiguration, WakeupStyle,
 };
+use hue::sun::Sun;
 use uuid::Uuid;
 
 use crate::error::ApiError;
@@ -28,38 +28,27 @@ pub struct WakeupJob {
 }
 
 impl WakeupJob {
-    fn start_datetime(&self, now: DateTime<Local>) -> ApiResult<DateTime<Local>> {
-        let start_time = self.start_time()?;
-        let next = match now.with_time(start_time) {
-            LocalResult::Single(time) => time,
-            LocalResult::Ambiguous(_, latest) => latest,
-            LocalResult::None => {
-                return Err(ApiError::InvalidDateTimeConversion(now));
-            }
-        };
-        let wakeup_datetime = if next < now {
-            next.checked_add_days(Days::new(1))
-                .ok_or(ApiError::InvalidDateTimeConversion(next))?
-        } else {
-            next
-        };
-        Ok(wakeup_datetime)
-    }
-
-    fn start_time(&self) -> ApiResult<NaiveTime> {
-        let job_time = self.configuration.when.time_point.time();
-        let scheduled_wakeup_time = NaiveTime::from_hms_opt(job_time.hour, job_time.minute, 0)
-            .ok_or(ApiError::InvalidNaiveTime)?;
+    /// Start of the fade in, for a wake up on the given date
+    fn start_on(&self, date: NaiveDate, sun: Option<&Sun>) -> Option<DateTime<Local>> {
+        let wakeup = self
+            .configuration
+            .when
+            .time_point
+            .on_date(date, sun, &Local)?;
         // although the scheduled time in the Hue app is the time when lights are at full brightness
         // the job start time is considered to be when the fade in effects starts
-        let fade_in_duration = self.configuration.fade_in_duration.to_std();
-        Ok(scheduled_wakeup_time - fade_in_duration)
+        let fade_in_duration =
+            TimeDelta::seconds(self.configuration.fade_in_duration.seconds.into());
+        Some(wakeup - fade_in_duration)
     }
 
+    /// Find the next time the fade in should start, on one of the given
+    /// weekdays (or any day, if `weekdays` is `None`)
     #[allow(clippy::needless_continue)]
-    fn next_weekday_occurrence(
-        weekdays: &HashSet<Weekday>,
-        time: NaiveTime,
+    fn next_occurrence(
+        &self,
+        weekdays: Option<&HashSet<Weekday>>,
+        sun: Option<&Sun>,
         now: &DateTime<Local>,
     ) -> ApiResult<DateTime<Local>> {
         let now_date = now.date_naive();
@@ -72,31 +61,29 @@ impl WakeupJob {
                 // unlikely to happen as we're dealing with a naive date, but let's skip if something weird happens
                 continue;
             };
-            if !weekdays.contains(&next_date.weekday()) {
+            if weekdays.is_some_and(|weekdays| !weekdays.contains(&next_date.weekday())) {
                 continue;
             }
-            let datetime = next_date.and_time(time);
-            match Local.from_local_datetime(&datetime) {
-                LocalResult::Single(candidate) | LocalResult::Ambiguous(_, candidate) => {
-                    if &candidate >= now {
-                        return Ok(candidate);
-                    }
-                }
-                LocalResult::None => {
-                    // Time does not exist on this day (e.g. DST jump forward).
-                    continue;
-                }
+            // The time point might not exist on this day (e.g. DST jump
+            // forward, or no sunrise/sunset in polar regions)
+            let Some(candidate) = self.start_on(next_date, sun) else {
+                continue;
+            };
+            if &candidate >= now {
+                return Ok(candidate);
             }
         }
-        Err(ApiError::NoNextWeekdayOccurence(time, weekdays.clone()))
+        Err(ApiError::NoNextWeekdayOccurence(
+            self.configuration.when.time_point.clone(),
+            weekdays.cloned().unwrap_or_default(),
+        ))
     }
 
     pub async fn create(self) {
-        let now = Local::now();
         let config = self.configuration.clone();
         let result = match &self.schedule_type {
             ScheduleType::Recurring(weekdays) => self.create_recurring(weekdays.clone()).await,
-            ScheduleType::Once() => self.run_once(now),
+            ScheduleType::Once() => self.run_once().await,
         };
         if let Err(err) = result {
             log::error!(
@@ -108,14 +95,14 @@ impl WakeupJob {
     }
 
     async fn create_recurring(&self, weekdays: HashSet<Weekday>) -> ApiResult<()> {
-        let fade_in_start = self.start_time()?;
         loop {
             let now = Local::now();
-            let fade_in_datetime = Self::next_weekday_occurrence(&weekdays, fade_in_start, &now)?;
+            let sun = self.res.lock().await.sun();
+            let fade_in_datetime = self.next_occurrence(Some(&weekdays), sun.as_ref(), &now)?;
             log::debug!(
-                "Recurring wakeup task for {:?}, {} will run at {}",
+                "Recurring wakeup task for {:?}, {:?} will run at {}",
                 &weekdays,
-                &fade_in_start,
+                &self.configuration.when.time_point,
                 &fade_in_datetime
             );
             let time_until_fade_in = (fade_in_datetime - now).to_std()?;
@@ -124,14 +111,15 @@ impl WakeupJob {
         }
     }
 
-    fn run_once(self, now: DateTime<Local>) -> ApiResult<()> {
-        let start_time = self.start_time()?;
-        let fade_in_datetime = self.start_datetime(now)?;
+    async fn run_once(self) -> ApiResult<()> {
+        let now = Local::now();
+        let sun = self.res.lock().await.sun();
+        let fade_in_datetime = self.next_occurrence(None, sun.as_ref(), &now)?;
         let time_until_fade_in = (fade_in_datetime - now).to_std()?;
         spawn(async move {
             log::debug!(
-                "Wakeup once task for {} will run at {}",
-                start_time,
+                "Wakeup once task for {:?} will run at {}",
+                self.configuration.when.time_point,
                 fade_in_datetime
             );
 
@@ -11,6 +11,7 @@ use async_trait::async_trait;
 use chrono::Utc;
 use nix::sys::socket;
 use nix::sys::socket::sockopt::RcvBuf;
+use openssl::ex_data::Index;
 use openssl::ssl::{Ssl, SslContext, SslMethod};
 use tokio::io::AsyncReadExt;
 use tokio::net::UdpSocket;
@@ -22,7 +23,7 @@ use udp_stream::{UdpListenBuilder, UdpListener, UdpStream};
 use uuid::Uuid;
 
 use bifrost_api::backend::BackendRequest;
-use hue::api::{Device, EntertainmentConfiguration, Light, RType};
+use hue::api::{Device, EntertainmentConfiguration, HueStreamKey, Light, RType};
 use hue::error::HueError;
 use hue::stream::{
     HueStreamLightsV1, HueStreamLightsV2, HueStreamPacket, HueStreamPacketV1, HueStreamPacketV2,
@@ -32,7 +33,6 @@ use svc::traits::Service;
 
 use crate::error::{ApiError, ApiResult};
 use crate::resource::Resources;
-use crate::routes::auth::STANDARD_CLIENT_KEY;
 
 struct EntertainmentSocket {
     res: Arc<Mutex<Resources>>,
@@ -251,10 +251,14 @@ impl EntertainmentSocket {
     }
 }
 
+/// Client keys known when a connection was accepted, indexed by identity
+type ClientKeys = BTreeMap<String, HueStreamKey>;
+
 pub struct EntertainmentService {
     addr: SocketAddr,
     udp: Option<Arc<UdpListener>>,
     ctx: Option<SslContext>,
+    keys: Option<Index<Ssl, ClientKeys>>,
     res: Arc<Mutex<Resources>>,
     backend_updates: Sender<Arc<BackendRequest>>,
 }
@@ -270,6 +274,7 @@ impl EntertainmentService {
             addr: SocketAddr::new(addr.into(), port),
             udp: None,
             ctx: None,
+            keys: None,
             res,
             backend_updates,
         };
@@ -284,17 +289,26 @@ impl Service for EntertainmentService {
 
     async fn configure(&mut self) -> Result<(), Self::Error> {
         let mut bldr = SslContext::builder(SslMethod::dtls_server())?;
+        let index = Ssl::new_ex_index()?;
 
-        bldr.set_psk_server_callback(|_sslref, cid, psk| {
+        bldr.set_psk_server_callback(move |sslref, cid, psk| {
             let client_id = String::from_utf8_lossy(cid.unwrap_or_default());
+            let keys: Option<&ClientKeys> = sslref.ex_data(index);
+
+            let Some(key) = keys.and_then(|keys| keys.get(client_id.as_ref())) else {
+                log::warn!("No PSK found for {client_id}, rejecting entertainment stream");
+                return Ok(0);
+            };
+
             log::debug!("Setting PSK for {client_id}",);
-            STANDARD_CLIENT_KEY.write_to_slice(psk).unwrap();
+            key.write_to_slice(psk).unwrap();
 
             log::trace!("psk: {}", hex::encode(&psk[..16]));
             Ok(16)
         });
 
         self.ctx = Some(bldr.build());
+        self.keys = Some(index);
         Ok(())
     }
 
@@ -317,13 +331,14 @@ impl Service for EntertainmentService {
             return Err(ApiError::service_error("Udp not initialized"));
         };
 
-        let Some(ctx) = self.ctx.as_ref() else {
+        let (Some(ctx), Some(index)) = (self.ctx.as_ref(), self.keys) else {
             return Err(ApiError::service_error("Ctx not initialized"));
         };
 
         loop {
             let (socket, _addr) = udp.accept().await?;
-            let ssl = Ssl::new(ctx)?;
+            let mut ssl = Ssl::new(ctx)?;
+            ssl.set_ex_data(index, self.res.lock().await.get_client_keys());
             let stream = SslStream::new(ssl, socket)?;
             let entertainment_socket =
                 EntertainmentSocket::new(self.res.clone(), self.backend_updates.clone());
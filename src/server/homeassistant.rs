@@ -0,0 +1,590 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use rumqttc::{AsyncClient, Event, LastWill, MqttOptions, Packet, QoS};
+use serde::Serialize;
+use serde_json::{Value, json};
+use tokio::select;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+use uuid::Uuid;
+
+use bifrost_api::config::HomeAssistantConfig;
+use hue::api::{RType, Resource, ResourceLink, Room, Zone};
+use hue::event::Event as HueEvent;
+use svc::traits::Service;
+
+use crate::error::{ApiError, ApiResult};
+use crate::resource::Resources;
+use crate::routes::clip::{behavior_instance, entertainment_configuration, scene};
+use crate::server::appstate::AppState;
+
+/// Time to wait before reconnecting to the mqtt broker
+const RETRY: Duration = Duration::from_secs(5);
+
+/// Resource types exported to Home Assistant
+const RTYPES: [RType; 3] = [
+    RType::Scene,
+    RType::EntertainmentConfiguration,
+    RType::BehaviorInstance,
+];
+
+#[derive(Debug, Clone, Serialize)]
+struct DiscoveryDevice {
+    identifiers: Vec<String>,
+    name: String,
+    manufacturer: &'static str,
+    model: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct DiscoveryConfig {
+    name: String,
+    unique_id: String,
+    command_topic: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    state_topic: Option<String>,
+    availability_topic: String,
+    device: DiscoveryDevice,
+}
+
+/// A Home Assistant entity, as last published
+#[derive(Debug, Clone, PartialEq)]
+struct Entity {
+    component: &'static str,
+    config: Value,
+    state: Option<&'static str>,
+}
+
+/// Topics and entity configs for Home Assistant MQTT discovery
+struct Discovery {
+    conf: HomeAssistantConfig,
+    device: DiscoveryDevice,
+}
+
+impl Discovery {
+    fn availability_topic(&self) -> String {
+        format!("{}/status", self.conf.get_base_topic())
+    }
+
+    fn resource_topic(&self, link: &ResourceLink) -> ApiResult<String> {
+        let rtype = serde_json::to_value(link.rtype)?;
+        let rtype = rtype.as_str().unwrap_or_default();
+        Ok(format!(
+            "{}/{rtype}/{}",
+            self.conf.get_base_topic(),
+            link.rid
+        ))
+    }
+
+    fn state_topic(&self, link: &ResourceLink) -> ApiResult<String> {
+        Ok(format!("{}/state", self.resource_topic(link)?))
+    }
+
+    fn config_topic(&self, component: &str, id: &Uuid) -> String {
+        format!(
+            "{}/{component}/bifrost/{id}/config",
+            self.conf.get_discovery_prefix()
+        )
+    }
+
+    /// Parse a command topic (`<base>/<rtype>/<id>/set`) into a resource link
+    fn parse_command_topic(&self, topic: &str) -> Option<ResourceLink> {
+        let rest = topic
+            .strip_prefix(self.conf.get_base_topic())?
+            .strip_prefix('/')?
+            .strip_suffix("/set")?;
+
+        let (rtype, id) = rest.split_once('/')?;
+        let rtype: RType = serde_json::from_value(json!(rtype)).ok()?;
+        let id = id.parse().ok()?;
+
+        Some(ResourceLink::new(id, rtype))
+    }
+
+    fn scene_name(res: &Resources, group: &ResourceLink, name: &str) -> String {
+        let group_name = match group.rtype {
+            RType::Room => res.get::<Room>(group).ok().map(|room| &room.metadata.name),
+            RType::Zone => res.get::<Zone>(group).ok().map(|zone| &zone.metadata.name),
+            _ => None,
+        };
+
+        group_name.map_or_else(|| name.to_string(), |group| format!("{group} {name}"))
+    }
+
+    /// The entity for a resource, or `None` if it is not exported
+    fn entity(&self, res: &Resources, link: &ResourceLink) -> ApiResult<Option<Entity>> {
+        let Ok(rr) = res.get_resource(link) else {
+            return Ok(None);
+        };
+
+        let (component, name, state) = match &rr.obj {
+            Resource::Scene(scene) => (
+                "scene",
+                Self::scene_name(res, &scene.group, &scene.metadata.name),
+                None,
+            ),
+            Resource::EntertainmentConfiguration(ent) => (
+                "switch",
+                ent.metadata.name.clone(),
+                Some(ent.is_streaming()),
+            ),
+            Resource::BehaviorInstance(bi) => {
+                ("switch", bi.metadata.name.clone(), Some(bi.enabled))
+            }
+            _ => return Ok(None),
+        };
+
+        let topic = self.resource_topic(link)?;
+        let config = DiscoveryConfig {
+            name,
+            unique_id: format!("bifrost_{}", link.rid),
+            command_topic: format!("{topic}/set"),
+            state_topic: state.map(|_| format!("{topic}/state")),
+            availability_topic: self.availability_topic(),
+            device: self.device.clone(),
+        };
+
+        Ok(Some(Entity {
+            component,
+            config: serde_json::to_value(config)?,
+            state: state.map(|on| if on { "ON" } else { "OFF" }),
+        }))
+    }
+}
+
+/// Publishes Home Assistant MQTT discovery configs for the hue concepts that
+/// zigbee2mqtt knows nothing about, and handles their commands:
+///
+///  - scenes become `scene` entities, which recall the scene
+///  - entertainment areas become switches, which start or stop streaming
+///  - behavior instances become switches, which enable or disable them
+pub struct HomeAssistantService {
+    state: AppState,
+    conf: HomeAssistantConfig,
+    discovery: Discovery,
+    entities: HashMap<Uuid, Entity>,
+    poller: Option<JoinHandle<()>>,
+}
+
+impl HomeAssistantService {
+    #[must_use]
+    pub fn new(state: AppState, conf: HomeAssistantConfig) -> Self {
+        let bconf = &state.config().bridge;
+        let device = DiscoveryDevice {
+            identifiers: vec![format!("bifrost_{}", bconf.mac)],
+            name: bconf.name.clone(),
+            manufacturer: "Bifrost",
+            model: "Hue bridge emulator",
+        };
+
+        let discovery = Discovery {
+            conf: conf.clone(),
+            device,
+        };
+
+        Self {
+            state,
+            conf,
+            discovery,
+            entities: HashMap::new(),
+            poller: None,
+        }
+    }
+
+    fn mqtt_options(&self) -> ApiResult<MqttOptions> {
+        let url = &self.conf.url;
+        let host = url
+            .host_str()
+            .ok_or_else(|| ApiError::InvalidMqttUrl(url.clone()))?;
+        let port = url.port().unwrap_or(HomeAssistantConfig::DEFAULT_PORT);
+
+        let client_id = format!("bifrost-{}", self.state.config().bridge.mac);
+        let mut opts = MqttOptions::new(client_id, host, port);
+        opts.set_keep_alive(Duration::from_secs(30));
+        opts.set_last_will(LastWill::new(
+            self.discovery.availability_topic(),
+            "offline",
+            QoS::AtLeastOnce,
+            true,
+        ));
+
+        if let Some(username) = &self.conf.username {
+            opts.set_credentials(username, self.conf.password.as_deref().unwrap_or_default());
+        }
+
+        Ok(opts)
+    }
+
+    /// Publish the config and state of a resource, if they have changed, or
+    /// remove its entity if the resource is gone
+    async fn sync_entity(&mut self, client: &AsyncClient, link: &ResourceLink) -> ApiResult<()> {
+        let entity = {
+            let lock = self.state.res.lock().await;
+            self.discovery.entity(&lock, link)?
+        };
+
+        let old = self.entities.remove(&link.rid);
+
+        let Some(entity) = entity else {
+            if let Some(old) = old {
+                log::debug!("Removing Home Assistant entity for {link:?}");
+                let topic = self.discovery.config_topic(old.component, &link.rid);
+                Self::publish(client, topic, vec![]).await?;
+
+                // clear the retained state too, so it does not linger on the broker
+                if old.state.is_some() {
+                    let topic = self.discovery.state_topic(link)?;
+                    Self::publish(client, topic, vec![]).await?;
+                }
+            }
+            return Ok(());
+        };
+
+        if old.as_ref().map(|old| &old.config) != Some(&entity.config) {
+            log::debug!("Publishing Home Assistant entity for {link:?}");
+            let topic = self.discovery.config_topic(entity.component, &link.rid);
+            Self::publish(client, topic, serde_json::to_vec(&entity.config)?).await?;
+        }
+
+        if let Some(state) = entity.state
+            && old.and_then(|old| old.state) != Some(state)
+        {
+            let topic = self.discovery.state_topic(link)?;
+            Self::publish(client, topic, state).await?;
+        }
+
+        self.entities.insert(link.rid, entity);
+
+        Ok(())
+    }
+
+    /// (Re)announce all entities, after connecting to the broker
+    async fn announce(&mut self, client: &AsyncClient) -> ApiResult<()> {
+        let topic = format!("{}/+/+/set", self.conf.get_base_topic());
+        client.subscribe(topic, QoS::AtLeastOnce).await?;
+
+        Self::publish(client, self.discovery.availability_topic(), "online").await?;
+
+        let links: Vec<ResourceLink> = {
+            let lock = self.state.res.lock().await;
+            RTYPES
+                .iter()
+                .flat_map(|rtype| {
+                    lock.get_resource_ids_by_type(*rtype)
+                        .into_iter()
+                        .map(|id| rtype.link_to(id))
+                })
+                .collect()
+        };
+
+        // the broker might have lost retained messages, so publish everything
+        self.entities.clear();
+        for link in links {
+            self.sync_entity(client, &link).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn publish(
+        client: &AsyncClient,
+        topic: String,
+        payload: impl Into<Vec<u8>>,
+    ) -> ApiResult<()> {
+        Ok(client
+            .publish(topic, QoS::AtLeastOnce, true, payload)
+            .await?)
+    }
+
+    async fn handle_command(&self, topic: &str, payload: &[u8]) -> ApiResult<()> {
+        let Some(link) = self.discovery.parse_command_topic(topic) else {
+            log::warn!("Ignoring Home Assistant command on {topic}");
+            return Ok(());
+        };
+
+        let on = payload != b"OFF";
+        log::info!(
+            "Home Assistant command for {link:?}: {}",
+            String::from_utf8_lossy(payload)
+        );
+
+        match link.rtype {
+            RType::Scene => {
+                let upd = json!({"recall": {"action": "active"}});
+                scene::put_scene(&self.state, link, upd).await?;
+            }
+            RType::EntertainmentConfiguration => {
+                let upd = json!({"action": if on { "start" } else { "stop" }});
+                entertainment_configuration::put_resource_id(&self.state, link, upd, None).await?;
+            }
+            RType::BehaviorInstance => {
+                let upd = json!({"enabled": on});
+                behavior_instance::put_behavior_instance(&self.state, link, upd).await?;
+            }
+            _ => log::warn!("Ignoring Home Assistant command for {link:?}"),
+        }
+
+        Ok(())
+    }
+
+    async fn handle_packet(&mut self, client: &AsyncClient, packet: Packet) -> ApiResult<()> {
+        match packet {
+            Packet::ConnAck(_) => {
+                log::info!("Connected to mqtt broker {}", self.conf.url);
+                self.announce(client).await
+            }
+            Packet::Publish(msg) => self.handle_command(&msg.topic, &msg.payload).await,
+            _ => Ok(()),
+        }
+    }
+
+    async fn handle_hue_event(&mut self, client: &AsyncClient, event: HueEvent) -> ApiResult<()> {
+        let links: Vec<ResourceLink> = match event {
+            HueEvent::Add(add) => add
+                .data
+                .iter()
+                .map(|rr| rr.obj.rtype().link_to(rr.id))
+                .collect(),
+            HueEvent::Update(update) => update
+                .data
+                .iter()
+                .map(|obj| obj.rtype.link_to(obj.id))
+                .collect(),
+            HueEvent::Delete(delete) => delete
+                .data
+                .iter()
+                .map(|obj| obj.rtype.link_to(obj.id))
+                .collect(),
+            HueEvent::Error(_) => vec![],
+        };
+
+        for link in links {
+            match link.rtype {
+                RType::Scene | RType::EntertainmentConfiguration | RType::BehaviorInstance => {
+                    self.sync_entity(client, &link).await?;
+                }
+                // scene names include the name of their room or zone
+                RType::Room | RType::Zone => {
+                    let scenes = self.state.res.lock().await.get_scenes_for_room(&link.rid);
+                    for id in scenes {
+                        self.sync_entity(client, &RType::Scene.link_to(id)).await?;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Poll the mqtt event loop in a separate task, so publishing never waits
+    /// for incoming messages to be handled
+    fn start_poller(&mut self) -> ApiResult<(AsyncClient, UnboundedReceiver<Packet>)> {
+        let (client, mut eventloop) = AsyncClient::new(self.mqtt_options()?, 64);
+        let (tx, rx) = mpsc::unbounded_channel();
+        let url = self.conf.url.clone();
+
+        self.poller = Some(tokio::spawn(async move {
+            loop {
+                match eventloop.poll().await {
+                    Ok(Event::Incoming(packet)) => {
+                        if tx.send(packet).is_err() {
+                            return;
+                        }
+                    }
+                    Ok(Event::Outgoing(_)) => {}
+                    Err(err) => {
+                        log::error!("Connection to mqtt broker {url} failed: {err}");
+                        sleep(RETRY).await;
+                    }
+                }
+            }
+        }));
+
+        Ok((client, rx))
+    }
+}
+
+#[async_trait]
+impl Service for HomeAssistantService {
+    type Error = ApiError;
+
+    async fn run(&mut self) -> Result<(), Self::Error> {
+        let mut hue_events = self.state.res.lock().await.hue_event_stream().subscribe();
+        let (client, mut packets) = self.start_poller()?;
+
+        loop {
+            let res = select! {
+                packet = packets.recv() => {
+                    let Some(packet) = packet else {
+                        return Ok(());
+                    };
+                    self.handle_packet(&client, packet).await
+                }
+                event = hue_events.recv() => {
+                    match event {
+                        Ok(event) => self.handle_hue_event(&client, event.block.event).await,
+                        Err(err) => {
+                            log::error!("Failed to read event {err}");
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            if let Err(err) = res {
+                log::error!("Home Assistant export failed: {err}");
+            }
+        }
+    }
+
+    async fn stop(&mut self) -> Result<(), Self::Error> {
+        if let Some(poller) = self.poller.take() {
+            poller.abort();
+        }
+        self.entities.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use uuid::Uuid;
+
+    use bifrost_api::config::HomeAssistantConfig;
+    use hue::api::{RType, Resource};
+    use hue::version::SwVersion;
+
+    use crate::model::state::State;
+    use crate::resource::Resources;
+    use crate::server::homeassistant::{Discovery, DiscoveryDevice};
+
+    fn discovery(base_topic: Option<&str>) -> Discovery {
+        Discovery {
+            conf: HomeAssistantConfig {
+                url: "mqtt://localhost".parse().unwrap(),
+                username: None,
+                password: None,
+                discovery_prefix: None,
+                base_topic: base_topic.map(ToString::to_string),
+            },
+            device: DiscoveryDevice {
+                identifiers: vec!["bifrost_00:11:22:33:44:55".to_string()],
+                name: "Bifrost".to_string(),
+                manufacturer: "Bifrost",
+                model: "Hue bridge emulator",
+            },
+        }
+    }
+
+    #[test]
+    fn config_topic() {
+        let id = Uuid::from_bytes([1; 16]);
+
+        assert_eq!(
+            discovery(None).config_topic("scene", &id),
+            format!("homeassistant/scene/bifrost/{id}/config")
+        );
+    }
+
+    #[test]
+    fn parse_command_topic() {
+        let disc = discovery(Some("hue"));
+        let id = Uuid::from_bytes([1; 16]);
+
+        assert_eq!(
+            disc.parse_command_topic(&format!("hue/scene/{id}/set")),
+            Some(RType::Scene.link_to(id))
+        );
+        assert_eq!(
+            disc.parse_command_topic(&format!("hue/behavior_instance/{id}/set")),
+            Some(RType::BehaviorInstance.link_to(id))
+        );
+
+        // wrong base topic, missing suffix, unknown type and invalid id
+        assert_eq!(
+            disc.parse_command_topic(&format!("bifrost/scene/{id}/set")),
+            None
+        );
+        assert_eq!(disc.parse_command_topic(&format!("hue/scene/{id}")), None);
+        assert_eq!(
+            disc.parse_command_topic(&format!("hue/nonsense/{id}/set")),
+            None
+        );
+        assert_eq!(disc.parse_command_topic("hue/scene/1/set"), None);
+    }
+
+    #[test]
+    fn entity() {
+        let disc = discovery(None);
+        let mut res = Resources::new(SwVersion::default(), State::new());
+
+        let link_room = RType::Room.link_to(Uuid::from_bytes([1; 16]));
+        let link_scene = RType::Scene.link_to(Uuid::from_bytes([2; 16]));
+        let link_bi = RType::BehaviorInstance.link_to(Uuid::from_bytes([3; 16]));
+
+        let room = json!({
+            "children": [],
+            "metadata": {"name": "Kitchen", "archetype": "kitchen"},
+        });
+        let scene = json!({
+            "actions": [],
+            "group": link_room,
+            "metadata": {"name": "Bright"},
+            "status": null,
+        });
+        let bi = json!({
+            "enabled": true,
+            "last_error": null,
+            "metadata": {"name": "Wake up"},
+            "script_id": Uuid::nil(),
+            "status": null,
+            "configuration": {},
+        });
+
+        res.add(
+            &link_room,
+            Resource::Room(serde_json::from_value(room).unwrap()),
+        )
+        .unwrap();
+        res.add(
+            &link_scene,
+            Resource::Scene(serde_json::from_value(scene).unwrap()),
+        )
+        .unwrap();
+        res.add(
+            &link_bi,
+            Resource::BehaviorInstance(serde_json::from_value(bi).unwrap()),
+        )
+        .unwrap();
+
+        // scenes are named after their room, and have no state
+        let ent = disc.entity(&res, &link_scene).unwrap().unwrap();
+        assert_eq!(ent.component, "scene");
+        assert_eq!(ent.config["name"], "Kitchen Bright");
+        assert_eq!(
+            ent.config["command_topic"],
+            format!("bifrost/scene/{}/set", link_scene.rid)
+        );
+        assert_eq!(ent.config.get("state_topic"), None);
+        assert_eq!(ent.state, None);
+
+        // behavior instances are switches
+        let ent = disc.entity(&res, &link_bi).unwrap().unwrap();
+        assert_eq!(ent.component, "switch");
+        assert_eq!(
+            ent.config["state_topic"],
+            format!("bifrost/behavior_instance/{}/state", link_bi.rid)
+        );
+        assert_eq!(ent.state, Some("ON"));
+
+        // rooms are not exported, and deleted resources have no entity
+        assert!(disc.entity(&res, &link_room).unwrap().is_none());
+        let missing = RType::Scene.link_to(Uuid::from_bytes([4; 16]));
+        assert!(disc.entity(&res, &missing).unwrap().is_none());
+    }
+}
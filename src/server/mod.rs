@@ -5,9 +5,13 @@ pub mod appstate;
 pub mod behavior_instance;
 pub mod certificate;
 pub mod entertainment;
+pub mod homeassistant;
 pub mod http;
 pub mod hueevents;
 pub mod mdns;
+pub mod rules;
+pub mod schedules;
+pub mod smart_scene;
 pub mod ssdp;
 pub mod updater;
 
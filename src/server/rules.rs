@@ -0,0 +1,266 @@
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Local, NaiveDateTime, Utc};
+use serde_json::Value;
+use tokio::select;
+use tokio::time::sleep;
+
+use hue::legacy_api::{ApiRule, ApiRuleCondition, ApiRuleOperator, ApiRuleStatus};
+use hue::legacy_time::{ApiTimeInterval, parse_duration};
+use svc::traits::Service;
+
+use crate::error::{ApiError, ApiResult};
+use crate::routes::api::{get_v1_state, v1_request};
+use crate::server::appstate::AppState;
+
+/// Address of the local time, which is only used with the `in` and `not in` operators
+const LOCALTIME: &str = "/config/localtime";
+
+/// Time based conditions (`ddx`, `stable`) are checked at this interval
+const TICK: Duration = Duration::from_secs(1);
+
+#[derive(Debug)]
+struct Attribute {
+    value: Value,
+    changed: DateTime<Utc>,
+}
+
+/// Evaluates the v1 rules, and runs the actions of every rule whose
+/// conditions have become true
+///
+/// A rule is triggered when all its conditions are true, and either they
+/// were not all true at the previous evaluation, or one of the attributes
+/// the conditions refer to has changed since then.
+pub struct RulesService {
+    state: AppState,
+    /// Last seen value of every attribute referenced by a rule
+    attributes: HashMap<String, Attribute>,
+    /// Rules whose conditions were all true at the previous evaluation
+    active: HashSet<u32>,
+    last_eval: DateTime<Utc>,
+}
+
+impl RulesService {
+    #[must_use]
+    pub fn new(state: AppState) -> Self {
+        Self {
+            state,
+            attributes: HashMap::new(),
+            active: HashSet::new(),
+            last_eval: Utc::now(),
+        }
+    }
+
+    /// Refresh the attributes referenced by `rules`, and return the
+    /// addresses of those that have changed
+    fn update_attributes(
+        &mut self,
+        rules: &[(u32, ApiRule)],
+        v1: &Value,
+        now: DateTime<Utc>,
+    ) -> HashSet<String> {
+        let addresses: HashSet<&str> = rules
+            .iter()
+            .flat_map(|(_, rule)| &rule.conditions)
+            .map(|cond| cond.address.as_str())
+            .filter(|addr| *addr != LOCALTIME)
+            .collect();
+
+        self.attributes
+            .retain(|addr, _| addresses.contains(addr.as_str()));
+
+        let mut changed = HashSet::new();
+
+        for addr in addresses {
+            let value = v1.pointer(addr).cloned().unwrap_or_default();
+
+            match self.attributes.entry(addr.to_string()) {
+                Entry::Occupied(mut ent) => {
+                    if ent.get().value != value {
+                        ent.insert(Attribute {
+                            value,
+                            changed: now,
+                        });
+                        changed.insert(addr.to_string());
+                    }
+                }
+                Entry::Vacant(ent) => {
+                    // the first value seen is not considered a change
+                    ent.insert(Attribute {
+                        value,
+                        changed: now,
+                    });
+                }
+            }
+        }
+
+        changed
+    }
+
+    fn condition(
+        &self,
+        cond: &ApiRuleCondition,
+        changed: &HashSet<String>,
+        now: DateTime<Utc>,
+        localtime: &NaiveDateTime,
+    ) -> bool {
+        let value = cond.value.as_deref().unwrap_or_default();
+
+        if cond.address == LOCALTIME {
+            let Ok(interval) = value.parse::<ApiTimeInterval>() else {
+                return false;
+            };
+
+            return match cond.operator {
+                ApiRuleOperator::In => interval.contains(localtime),
+                ApiRuleOperator::NotIn => !interval.contains(localtime),
+                _ => false,
+            };
+        }
+
+        let Some(attr) = self.attributes.get(&cond.address) else {
+            return false;
+        };
+
+        let duration = parse_duration(value).ok();
+
+        match cond.operator {
+            ApiRuleOperator::Eq | ApiRuleOperator::Gt | ApiRuleOperator::Lt => {
+                cond.compare(&attr.value)
+            }
+            ApiRuleOperator::Dx => changed.contains(&cond.address),
+            ApiRuleOperator::Ddx => duration.is_some_and(|dur| {
+                let at = attr.changed + dur;
+                self.last_eval < at && at <= now
+            }),
+            ApiRuleOperator::Stable => duration.is_some_and(|dur| now - attr.changed >= dur),
+            ApiRuleOperator::NotStable => duration.is_some_and(|dur| now - attr.changed < dur),
+            ApiRuleOperator::In | ApiRuleOperator::NotIn => false,
+        }
+    }
+
+    async fn evaluate(&mut self) -> ApiResult<()> {
+        let now = Utc::now();
+        let localtime = Local::now().naive_local();
+
+        let lock = self.state.res.lock().await;
+        let rules: Vec<(u32, ApiRule)> = lock
+            .get_rules()
+            .iter()
+            .filter(|(_, rule)| rule.status == ApiRuleStatus::Enabled)
+            .map(|(id, rule)| (*id, rule.clone()))
+            .collect();
+
+        if rules.is_empty() {
+            drop(lock);
+            self.attributes.clear();
+            self.active.clear();
+            return Ok(());
+        }
+
+        let v1 = get_v1_state(&lock)?;
+        drop(lock);
+
+        let changed = self.update_attributes(&rules, &v1, now);
+
+        let mut active = HashSet::new();
+        let mut triggered = vec![];
+
+        for (id, rule) in rules {
+            let all = !rule.conditions.is_empty()
+                && rule
+                    .conditions
+                    .iter()
+                    .all(|cond| self.condition(cond, &changed, now, &localtime));
+
+            if !all {
+                continue;
+            }
+
+            let refreshed = rule
+                .conditions
+                .iter()
+                .any(|cond| changed.contains(&cond.address));
+
+            if !self.active.contains(&id) || refreshed {
+                triggered.push((id, rule));
+            }
+            active.insert(id);
+        }
+
+        self.active = active;
+        self.last_eval = now;
+
+        for (id, rule) in triggered {
+            self.trigger(id, &rule).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn trigger(&self, id: u32, rule: &ApiRule) -> ApiResult<()> {
+        log::info!("Rule {id} ({:?}) triggered", rule.name);
+
+        for action in &rule.actions {
+            log::debug!(
+                "Rule {id}: {:?} {} {}",
+                action.method,
+                action.address,
+                action.body
+            );
+
+            let res = v1_request(
+                &self.state,
+                &rule.owner,
+                action.method,
+                &action.address,
+                action.body.clone(),
+            )
+            .await;
+
+            if let Err(err) = res {
+                log::error!("Rule {id}: action {} failed: {err}", action.address);
+            }
+        }
+
+        self.state
+            .res
+            .lock()
+            .await
+            .update_rule(id, ApiRule::trigger)?;
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Service for RulesService {
+    type Error = ApiError;
+
+    async fn run(&mut self) -> Result<(), Self::Error> {
+        let lock = self.state.res.lock().await;
+        let mut hue_events = lock.hue_event_stream().subscribe();
+        let state_updates = lock.state_channel();
+        drop(lock);
+
+        loop {
+            select! {
+                event = hue_events.recv() => {
+                    if let Err(err) = event {
+                        log::error!("Failed to read event {err}");
+                        continue;
+                    }
+                }
+                () = state_updates.notified() => {}
+                () = sleep(TICK) => {}
+            }
+
+            if let Err(err) = self.evaluate().await {
+                log::error!("Failed to evaluate rules: {err}");
+            }
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::{DateTime, Local, NaiveDateTime, TimeDelta, Utc};
+use rand::Rng;
+use tokio::select;
+use tokio::time::sleep;
+
+use hue::legacy_api::{ApiSchedule, ApiScheduleStatus};
+use svc::traits::Service;
+
+use crate::error::{ApiError, ApiResult};
+use crate::routes::api::v1_request;
+use crate::server::appstate::AppState;
+
+/// Upper limit on the time between checks, to keep up with changes to the
+/// system clock (or time zone)
+const MAX_WAIT: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+struct ScheduleJob {
+    /// The schedule time, and timer start, that the job was planned from
+    localtime: String,
+    starttime: Option<DateTime<Utc>>,
+    /// When the job is due (in local time), including randomization
+    due: NaiveDateTime,
+}
+
+/// Runs the commands of v1 schedules, as they become due
+pub struct ScheduleService {
+    state: AppState,
+    jobs: HashMap<u32, ScheduleJob>,
+}
+
+impl ScheduleService {
+    #[must_use]
+    pub fn new(state: AppState) -> Self {
+        Self {
+            state,
+            jobs: HashMap::new(),
+        }
+    }
+
+    /// Work out when a schedule is next due, or `None` if it never will be
+    fn plan(schedule: &ApiSchedule, now: NaiveDateTime) -> ApiResult<Option<ScheduleJob>> {
+        let time = schedule.schedule_time()?;
+
+        let start = schedule
+            .starttime
+            .unwrap_or(schedule.created)
+            .with_timezone(&Local)
+            .naive_local();
+
+        let Some(next) = time.next_after(now, start) else {
+            return Ok(None);
+        };
+
+        let delay = time.random.map_or(TimeDelta::zero(), |random| {
+            TimeDelta::seconds(rand::rng().random_range(0..=random.num_seconds()))
+        });
+
+        Ok(Some(ScheduleJob {
+            localtime: schedule.localtime.clone(),
+            starttime: schedule.starttime,
+            due: next + delay,
+        }))
+    }
+
+    /// Run all schedules that are due, and return the time until the next one
+    async fn run_due(&mut self) -> ApiResult<Duration> {
+        let now = Local::now().naive_local();
+
+        let schedules: HashMap<u32, ApiSchedule> = self
+            .state
+            .res
+            .lock()
+            .await
+            .get_schedules()
+            .iter()
+            .filter(|(_, schedule)| schedule.status == ApiScheduleStatus::Enabled)
+            .map(|(id, schedule)| (*id, schedule.clone()))
+            .collect();
+
+        self.jobs.retain(|id, job| {
+            schedules.get(id).is_some_and(|schedule| {
+                job.localtime == schedule.localtime && job.starttime == schedule.starttime
+            })
+        });
+
+        for (id, schedule) in &schedules {
+            if self.jobs.contains_key(id) {
+                continue;
+            }
+
+            match Self::plan(schedule, now) {
+                Ok(Some(job)) => {
+                    log::debug!("Schedule {id} ({:?}) due at {}", schedule.name, job.due);
+                    self.jobs.insert(*id, job);
+                }
+                Ok(None) => {}
+                Err(err) => log::error!("Schedule {id} ({:?}): {err}", schedule.name),
+            }
+        }
+
+        let due: Vec<u32> = self
+            .jobs
+            .iter()
+            .filter(|(_, job)| job.due <= now)
+            .map(|(id, _)| *id)
+            .collect();
+
+        for id in due {
+            self.jobs.remove(&id);
+            self.trigger(id, &schedules[&id]).await?;
+        }
+
+        let wait = self
+            .jobs
+            .values()
+            .filter_map(|job| (job.due - now).to_std().ok())
+            .min()
+            .unwrap_or(MAX_WAIT);
+
+        Ok(wait.min(MAX_WAIT))
+    }
+
+    async fn trigger(&self, id: u32, schedule: &ApiSchedule) -> ApiResult<()> {
+        log::info!("Schedule {id} ({:?}) triggered", schedule.name);
+
+        let cmd = &schedule.command;
+
+        // commands are given as full paths, including the username
+        let username = cmd
+            .address
+            .strip_prefix("/api/")
+            .and_then(|rest| rest.split_once('/'))
+            .map_or("", |(username, _)| username);
+
+        let res = v1_request(
+            &self.state,
+            username,
+            cmd.method,
+            &cmd.address,
+            cmd.body.clone(),
+        )
+        .await;
+
+        if let Err(err) = res {
+            log::error!("Schedule {id}: command {} failed: {err}", cmd.address);
+        }
+
+        if schedule.schedule_time()?.is_recurring() {
+            return Ok(());
+        }
+
+        let mut lock = self.state.res.lock().await;
+        if schedule.autodelete() {
+            log::debug!("Schedule {id} has expired, deleting");
+            lock.delete_schedule(id)?;
+        } else {
+            lock.update_schedule(id, |schedule| {
+                schedule.status = ApiScheduleStatus::Disabled;
+            })?;
+        }
+        drop(lock);
+
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Service for ScheduleService {
+    type Error = ApiError;
+
+    async fn run(&mut self) -> Result<(), Self::Error> {
+        let state_updates = self.state.res.lock().await.state_channel();
+
+        loop {
+            // start listening before checking, to not miss any changes made meanwhile
+            let notified = state_updates.notified();
+            tokio::pin!(notified);
+            notified.as_mut().enable();
+
+            let wait = match self.run_due().await {
+                Ok(wait) => wait,
+                Err(err) => {
+                    log::error!("Failed to run schedules: {err}");
+                    MAX_WAIT
+                }
+            };
+
+            select! {
+                () = notified => {},
+                () = sleep(wait) => {},
+            }
+        }
+    }
+
+    async fn stop(&mut self) -> Result<(), Self::Error> {
+        self.jobs.clear();
+        Ok(())
+    }
+}
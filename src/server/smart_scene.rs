@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use bifrost_api::backend::BackendRequest;
+use chrono::{Days, Local, NaiveTime, TimeZone};
+use tokio::select;
+use tokio::sync::Mutex;
+use tokio::task::JoinHandle;
+use tokio::time::sleep;
+use uuid::Uuid;
+
+use hue::api::{
+    Geolocation, RType, Resource, SceneRecall, SceneStatusEnum, SceneUpdate, SmartScene,
+    SmartSceneDayTimeslots, SmartSceneState,
+};
+use hue::event::Event;
+use svc::traits::Service;
+
+use crate::error::{ApiError, ApiResult};
+use crate::resource::Resources;
+
+/// Runs the timeslot schedule of all active smart scenes
+#[derive(Debug)]
+pub struct SmartSceneService {
+    res: Arc<Mutex<Resources>>,
+    jobs: HashMap<Uuid, SmartSceneJob>,
+}
+
+#[derive(Debug)]
+struct SmartSceneJob {
+    week_timeslots: Vec<SmartSceneDayTimeslots>,
+    task: JoinHandle<()>,
+}
+
+impl Drop for SmartSceneJob {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl SmartSceneService {
+    pub fn new(res: Arc<Mutex<Resources>>) -> Self {
+        Self {
+            res,
+            jobs: HashMap::new(),
+        }
+    }
+
+    /// Start, restart or stop the job for a smart scene, to match its current state
+    async fn sync_job(&mut self, id: Uuid) {
+        let smart_scene = self.res.lock().await.get_id::<SmartScene>(id).cloned();
+
+        match smart_scene {
+            Ok(ss) if ss.state == SmartSceneState::Active => {
+                // the job itself updates `active_timeslot`, so only restart
+                // it if the schedule has actually changed
+                if self
+                    .jobs
+                    .get(&id)
+                    .is_some_and(|job| job.week_timeslots == ss.week_timeslots)
+                {
+                    return;
+                }
+
+                log::debug!("Starting smart scene job {id}");
+                let task = tokio::spawn(run_smart_scene(id, self.res.clone()));
+                self.jobs.insert(
+                    id,
+                    SmartSceneJob {
+                        week_timeslots: ss.week_timeslots,
+                        task,
+                    },
+                );
+            }
+            _ => {
+                if self.jobs.remove(&id).is_some() {
+                    log::debug!("Stopped smart scene job {id}");
+                }
+            }
+        }
+    }
+
+    async fn sync_all_jobs(&mut self) {
+        let ids = self
+            .res
+            .lock()
+            .await
+            .get_resource_ids_by_type(RType::SmartScene);
+
+        for id in ids {
+            self.sync_job(id).await;
+        }
+    }
+
+    async fn update_sun_today(&self) -> ApiResult<()> {
+        let today = Local::now().date_naive();
+
+        let mut lock = self.res.lock().await;
+        let sun = lock.sun();
+        for id in lock.get_resource_ids_by_type(RType::Geolocation) {
+            lock.update::<Geolocation>(&id, |geoloc| {
+                geoloc.update_sun_today(sun.as_ref(), today);
+            })?;
+        }
+        drop(lock);
+
+        Ok(())
+    }
+
+    fn time_until_midnight() -> Duration {
+        let now = Local::now();
+        now.date_naive()
+            .checked_add_days(Days::new(1))
+            .and_then(|date| {
+                Local
+                    .from_local_datetime(&date.and_time(NaiveTime::MIN))
+                    .earliest()
+            })
+            .and_then(|midnight| (midnight - now).to_std().ok())
+            .unwrap_or(Duration::from_secs(3600))
+    }
+}
+
+#[async_trait]
+impl Service for SmartSceneService {
+    type Error = ApiError;
+
+    async fn configure(&mut self) -> Result<(), Self::Error> {
+        self.update_sun_today().await?;
+        self.sync_all_jobs().await;
+
+        Ok(())
+    }
+
+    async fn run(&mut self) -> Result<(), Self::Error> {
+        let mut hue_events = self.res.lock().await.hue_event_stream().subscribe();
+
+        loop {
+            let event = select! {
+                event = hue_events.recv() => event,
+                () = sleep(Self::time_until_midnight()) => {
+                    self.update_sun_today().await?;
+                    continue;
+                }
+            };
+
+            let event = match event {
+                Ok(event) => event,
+                Err(err) => {
+                    log::error!("Failed to read event {err}");
+                    continue;
+                }
+            };
+
+            match event.block.event {
+                Event::Add(add) => {
+                    for obj in add.data {
+                        if let Resource::SmartScene(_) = obj.obj {
+                            self.sync_job(obj.id).await;
+                        }
+                    }
+                }
+                Event::Update(update) => {
+                    for obj in update.data {
+                        match obj.rtype {
+                            RType::SmartScene => self.sync_job(obj.id).await,
+                            RType::Geolocation => {
+                                // sunrise and sunset times have changed, so
+                                // every job needs to reschedule
+                                self.jobs.clear();
+                                self.sync_all_jobs().await;
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                Event::Delete(delete) => {
+                    for obj in delete.data {
+                        if obj.rtype == RType::SmartScene {
+                            self.jobs.remove(&obj.id);
+                        }
+                    }
+                }
+                Event::Error(_) => {}
+            }
+        }
+    }
+
+    async fn stop(&mut self) -> Result<(), Self::Error> {
+        self.jobs.clear();
+        Ok(())
+    }
+}
+
+async fn run_smart_scene(id: Uuid, res: Arc<Mutex<Resources>>) {
+    if let Err(err) = smart_scene_schedule(id, &res).await {
+        log::error!("Smart scene job {id} failed: {err}");
+    }
+}
+
+async fn smart_scene_schedule(id: Uuid, res: &Mutex<Resources>) -> ApiResult<()> {
+    loop {
+        let now = Local::now();
+
+        let mut lock = res.lock().await;
+        let sun = lock.sun();
+        let ss = lock.get_id::<SmartScene>(id)?.clone();
+
+        if let Some((active, target)) = ss.timeslot_at(&now, sun.as_ref())
+            && ss.active_timeslot != Some(active)
+        {
+            log::info!(
+                "Smart scene {:?}: recalling timeslot {} for {:?}",
+                ss.metadata.name,
+                active.timeslot_id,
+                active.weekday
+            );
+
+            let upd = SceneUpdate {
+                recall: Some(SceneRecall {
+                    action: Some(SceneStatusEnum::Active),
+                    duration: Some(ss.transition_duration),
+                    dimming: None,
+                }),
+                ..SceneUpdate::default()
+            };
+            lock.backend_request(BackendRequest::SceneUpdate(target, upd))?;
+            lock.update::<SmartScene>(&id, |ss| ss.active_timeslot = Some(active))?;
+        }
+        drop(lock);
+
+        let Some(next) = ss.next_start(&now, sun.as_ref()) else {
+            log::warn!(
+                "Smart scene {:?} has no upcoming timeslots",
+                ss.metadata.name
+            );
+            return Ok(());
+        };
+
+        log::debug!(
+            "Smart scene {:?}: next timeslot at {next}",
+            ss.metadata.name
+        );
+        sleep((next - now).to_std()?).await;
+    }
+}